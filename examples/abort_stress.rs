@@ -0,0 +1,73 @@
+//! Repeatedly open and drop streams whose callback aborts on its first call.
+//!
+//! Each stream's callback owns some heap-allocated state and returns `Abort` immediately, after
+//! which the stream is closed in a different way on each iteration. Running this under a memory
+//! checker such as AddressSanitizer (`RUSTFLAGS=-Zsanitizer=address` on nightly) verifies that
+//! the callback's state is never accessed after it has been freed.
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const CHANNELS: i32 = 2;
+const SAMPLE_RATE: f64 = 44_100.0;
+const FRAMES_PER_BUFFER: u32 = 64;
+const ITERATIONS: usize = 200;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+    let settings = pa.default_output_stream_settings(CHANNELS, SAMPLE_RATE, FRAMES_PER_BUFFER)?;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    for i in 0..ITERATIONS {
+        let state = vec![0.0f32; 1024];
+        let calls_in_callback = calls.clone();
+        let callback = move |pa::OutputStreamCallbackArgs { buffer, .. }| {
+            calls_in_callback.fetch_add(1, Ordering::SeqCst);
+            for (out, &sample) in buffer.iter_mut().zip(state.iter().cycle()) {
+                *out = sample;
+            }
+            pa::Abort
+        };
+
+        let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+        stream.start()?;
+        while stream.is_active()? {
+            pa.sleep(1);
+        }
+
+        // Alternate between closing explicitly and leaving it to `Drop`.
+        match i % 3 {
+            0 => stream.close()?,
+            1 => {
+                stream.stop()?;
+                stream.close()?;
+            }
+            _ => (),
+        }
+    }
+
+    // Each callback returns `Abort`, so it should only ever have been called once per stream.
+    let calls = calls.load(Ordering::SeqCst);
+    assert_eq!(
+        calls, ITERATIONS,
+        "a callback was called after returning Abort"
+    );
+    println!(
+        "Opened {} streams, each called back exactly once.",
+        ITERATIONS
+    );
+
+    Ok(())
+}
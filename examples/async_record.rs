@@ -0,0 +1,68 @@
+//! Record from the default input device on a tokio runtime and play the recording back.
+//!
+//! The blocks are received from an **AsyncInputStream** and, once recording has finished, sent
+//! to an **AsyncOutputStream**. Requires the `tokio` feature:
+//!
+//! ```text
+//! cargo run --example async_record --features tokio
+//! ```
+
+extern crate portaudio;
+extern crate tokio;
+
+use portaudio as pa;
+
+const SAMPLE_RATE: f64 = 44_100.0;
+const CHANNELS: i32 = 1;
+const FRAMES: u32 = 1024;
+const INTERLEAVED: bool = true;
+const NUM_SECONDS: usize = 3;
+// Up to a second of audio may be queued in either direction.
+const CAPACITY: usize = SAMPLE_RATE as usize / FRAMES as usize;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build the tokio runtime");
+
+    let input = pa.default_input_device()?;
+    let latency = pa.device_info(input)?.default_low_input_latency;
+    let params = pa::StreamParameters::<f32>::new(input, CHANNELS, INTERLEAVED, latency);
+    let settings = pa::InputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+    let mut recorder = pa::AsyncInputStream::open(&pa, settings, CAPACITY)?;
+
+    println!("Recording for {} seconds.", NUM_SECONDS);
+    let total = NUM_SECONDS * SAMPLE_RATE as usize * CHANNELS as usize;
+    let mut recording = Vec::with_capacity(total);
+    while recording.len() < total {
+        match runtime.block_on(recorder.next_block()) {
+            Some(block) => recording.extend(block),
+            None => break,
+        }
+    }
+    recorder.close()?;
+
+    let output = pa.default_output_device()?;
+    let latency = pa.device_info(output)?.default_low_output_latency;
+    let params = pa::StreamParameters::<f32>::new(output, CHANNELS, INTERLEAVED, latency);
+    let settings = pa::OutputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+    let player = pa::AsyncOutputStream::open(&pa, settings, CAPACITY)?;
+
+    println!("Playing back {} samples.", recording.len());
+    for block in recording.chunks(FRAMES as usize * CHANNELS as usize) {
+        if runtime.block_on(player.send_block(block.to_vec())).is_err() {
+            break;
+        }
+    }
+    player.close()
+}
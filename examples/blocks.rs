@@ -0,0 +1,48 @@
+//! Print the level of the default input device, reading it one block at a time via
+//! `Stream::blocks`.
+
+extern crate portaudio;
+
+use portaudio as pa;
+
+const SAMPLE_RATE: f64 = 44_100.0;
+const CHANNELS: i32 = 1;
+const FRAMES: u32 = 4_410;
+const INTERLEAVED: bool = true;
+const NUM_BLOCKS: usize = 50;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+
+    let input = pa.default_input_device()?;
+    let latency = pa.device_info(input)?.default_high_input_latency;
+    let params = pa::StreamParameters::<f32>::new(input, CHANNELS, INTERLEAVED, latency);
+    let settings = pa::InputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+
+    let mut blocks = stream.blocks();
+    for block in blocks.by_ref().take(NUM_BLOCKS) {
+        match block {
+            Ok(samples) => {
+                let rms = pa::meter::rms(&samples);
+                println!("{}", "#".repeat((rms * 200.0) as usize));
+            }
+            Err(pa::Error::InputOverflowed) => println!("(input overflowed)"),
+            Err(err) => return Err(err),
+        }
+    }
+
+    let mut stream = blocks.into_inner();
+    stream.stop()?;
+    stream.close()
+}
@@ -0,0 +1,80 @@
+//! Play a metronome click that is scheduled against the DAC clock.
+//!
+//! Each callback is told when the first frame of its output buffer will reach the DAC via
+//! `time.buffer_dac`. Clicks are scheduled at absolute DAC times, so each one lands on the exact
+//! frame that will be heard at that time regardless of the buffer size or how early the callback
+//! happens to run.
+
+extern crate portaudio;
+
+use portaudio as pa;
+
+const CHANNELS: i32 = 2;
+const NUM_SECONDS: i32 = 5;
+const SAMPLE_RATE: f64 = 44_100.0;
+const FRAMES_PER_BUFFER: u32 = 256;
+/// The interval between clicks in seconds.
+const CLICK_INTERVAL: f64 = 0.5;
+/// How far ahead of the first callback's DAC time to schedule the first click.
+const START_DELAY: f64 = 0.25;
+/// The length of each click in frames.
+const CLICK_FRAMES: u32 = 200;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+
+    let settings = pa.default_output_stream_settings(CHANNELS, SAMPLE_RATE, FRAMES_PER_BUFFER)?;
+
+    // The DAC time of the next click, set by the first callback.
+    let mut next_click: Option<pa::Time> = None;
+    // The number of frames of the current click that remain to be played.
+    let mut click_remaining = 0;
+
+    let callback = move |pa::OutputStreamCallbackArgs { buffer, time, .. }| {
+        let next = next_click.get_or_insert(time.buffer_dac + START_DELAY);
+        for (i, frame) in buffer.chunks_mut(CHANNELS as usize).enumerate() {
+            let frame_time = time.buffer_dac + i as f64 / SAMPLE_RATE;
+            if frame_time >= *next {
+                click_remaining = CLICK_FRAMES;
+                *next += CLICK_INTERVAL;
+            }
+            let sample = if click_remaining > 0 {
+                click_remaining -= 1;
+                // A decaying burst alternating in sign.
+                let amp = click_remaining as f32 / CLICK_FRAMES as f32;
+                if click_remaining % 2 == 0 {
+                    amp * 0.5
+                } else {
+                    -amp * 0.5
+                }
+            } else {
+                0.0
+            };
+            for out in frame.iter_mut() {
+                *out = sample;
+            }
+        }
+        pa::Continue
+    };
+
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+
+    stream.start()?;
+
+    println!("Play clicks for {} seconds.", NUM_SECONDS);
+    pa.sleep(NUM_SECONDS * 1_000);
+
+    stream.stop()?;
+    stream.close()?;
+
+    Ok(())
+}
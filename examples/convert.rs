@@ -0,0 +1,100 @@
+//! Check the conversions between sample formats, then play a sine wave generated as `f32`
+//! through a stream opened in the `i16` format.
+//!
+//! The checks don't need an audio device, so they run even if opening the stream fails.
+
+extern crate portaudio;
+
+use portaudio as pa;
+use portaudio::Sample;
+use std::f32::consts::PI;
+
+const CHANNELS: i32 = 1;
+const NUM_SECONDS: usize = 2;
+const SAMPLE_RATE: f64 = 44_100.0;
+const FRAMES_PER_BUFFER: u32 = 256;
+const INTERLEAVED: bool = true;
+
+fn main() {
+    check_clipping();
+    check_round_trip();
+    check_dither();
+    println!("Sample conversions are correct.");
+
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn check_clipping() {
+    assert_eq!(i16::from_f32_sample(1.0), 32767);
+    assert_eq!(i16::from_f32_sample(-1.0), -32767);
+    assert_eq!(i16::from_f32_sample(1.5), 32767);
+    assert_eq!(i16::from_f32_sample(-1.5), -32767);
+    assert_eq!(i16::from_f32_sample(f32::NAN), 0);
+    assert_eq!(i16::MIN.to_f32_sample(), -1.0);
+    assert_eq!(u8::from_f32_sample(-2.0), 1);
+    assert_eq!(u8::from_f32_sample(0.0), 128);
+    assert_eq!(i8::from_f32_sample(2.0), 127);
+    assert_eq!(pa::I24::from_f32_sample(1.0).to_i32(), pa::I24::MAX);
+    assert_eq!(i32::from_f32_sample(-1.0), -i32::MAX);
+}
+
+fn check_round_trip() {
+    for value in -32767..=32767i16 {
+        assert_eq!(i16::from_f32_sample(value.to_f32_sample()), value);
+    }
+    for value in 0..=255u8 {
+        let expected = if value == 0 { 1 } else { value };
+        assert_eq!(u8::from_f32_sample(value.to_f32_sample()), expected);
+    }
+    let mut converter = pa::Converter::default();
+    let src: Vec<i16> = (-100..100).map(|s| s * 300).collect();
+    let mut floats = vec![0.0f32; src.len()];
+    let mut dst = vec![0i16; src.len()];
+    converter.convert(&src, &mut floats);
+    converter.convert(&floats, &mut dst);
+    assert_eq!(src, dst);
+}
+
+fn check_dither() {
+    // Dither should never move a sample by more than one step of the destination format.
+    let mut converter = pa::Converter::new(pa::Dither::Triangular);
+    let src: Vec<f32> = (0..10_000).map(|i| (i as f32 * 0.01).sin()).collect();
+    let mut dst = vec![0i16; src.len()];
+    converter.convert(&src, &mut dst);
+    for (&s, &d) in src.iter().zip(&dst) {
+        let exact = s * 32767.0;
+        assert!(
+            (d as f32 - exact).abs() <= 1.5,
+            "{} dithered to {}",
+            exact,
+            d
+        );
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+
+    let device = pa.default_output_device()?;
+    let latency = pa.device_info(device)?.default_low_output_latency;
+    let params = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, latency);
+    let settings = pa::OutputStreamSettings::new(params, SAMPLE_RATE, FRAMES_PER_BUFFER);
+    let stream = pa.open_blocking_stream(settings)?;
+    let mut stream = pa::ConvertingStream::new(stream).with_dither(pa::Dither::Triangular);
+
+    let samples: Vec<f32> = (0..NUM_SECONDS * SAMPLE_RATE as usize)
+        .map(|i| (i as f32 * 440.0 * 2.0 * PI / SAMPLE_RATE as f32).sin() * 0.2)
+        .collect();
+
+    stream.stream_mut().start()?;
+    println!("Play for {} seconds.", NUM_SECONDS);
+    stream.write(&samples)?;
+    let mut stream = stream.into_inner();
+    stream.stop()?;
+    stream.close()
+}
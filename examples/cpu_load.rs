@@ -0,0 +1,79 @@
+//! Play a sine wave for several seconds while logging the stream's CPU load from another thread.
+//!
+//! The callback adds a number of detuned partials to the sine wave, so the number of `PARTIALS`
+//! can be increased to watch the load climb.
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const CHANNELS: i32 = 2;
+const NUM_SECONDS: u64 = 5;
+const SAMPLE_RATE: f64 = 44_100.0;
+const FRAMES_PER_BUFFER: u32 = 256;
+const PARTIALS: usize = 64;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+
+    let settings = pa.default_output_stream_settings(CHANNELS, SAMPLE_RATE, FRAMES_PER_BUFFER)?;
+
+    let mut phase = 0.0;
+    let callback = move |pa::OutputStreamCallbackArgs { buffer, .. }| {
+        for frame in buffer.chunks_mut(CHANNELS as usize) {
+            let mut value = 0.0;
+            for partial in 0..PARTIALS {
+                let freq = 220.0 * (1.0 + partial as f64 * 0.001);
+                value += (phase * freq * 2.0 * PI).sin();
+            }
+            let sample = (value / PARTIALS as f64 * 0.2) as f32;
+            for out in frame.iter_mut() {
+                *out = sample;
+            }
+            phase += 1.0 / SAMPLE_RATE;
+        }
+        pa::Continue
+    };
+
+    let stream = pa.open_non_blocking_stream(settings, callback)?;
+    let stream = Arc::new(Mutex::new(stream));
+
+    stream.lock().unwrap().start()?;
+
+    // Log the CPU load once per second from a separate thread.
+    let logger = {
+        let stream = stream.clone();
+        thread::spawn(move || {
+            for second in 1..NUM_SECONDS + 1 {
+                thread::sleep(Duration::from_secs(1));
+                let load = stream.lock().unwrap().cpu_load();
+                println!("{}s: CPU load {:.1}%", second, load * 100.0);
+            }
+        })
+    };
+
+    logger.join().unwrap();
+
+    // The logger has finished, so its clone of the stream is gone and the stream can be closed.
+    let mut stream = match Arc::try_unwrap(stream) {
+        Ok(stream) => stream.into_inner().unwrap(),
+        Err(_) => unreachable!("the logger still holds the stream"),
+    };
+    stream.stop()?;
+    stream.close()?;
+
+    Ok(())
+}
@@ -22,6 +22,8 @@ fn main() {
 fn run() -> Result<(), pa::Error> {
     let pa = pa::PortAudio::new()?;
 
+    println!("PortAudio version: {:?}", pa.version_info());
+
     let num_devices = pa.device_count()?;
     println!("Number of devices = {}", num_devices);
 
@@ -33,6 +35,10 @@ fn run() -> Result<(), pa::Error> {
         let (idx, info) = device?;
         println!("--------------------------------------- {:?}", idx);
         println!("{:#?}", &info);
+        println!(
+            "Sample rates supported for mono 16-bit audio: {:?}",
+            pa.supported_sample_rates(idx, 1, pa::SampleFormat::I16)
+        );
 
         let in_channels = info.max_input_channels;
         let input_params = pa::StreamParameters::<i16>::new(idx, in_channels, INTERLEAVED, LATENCY);
@@ -0,0 +1,67 @@
+//! Echo the default input device to the default output device for several seconds.
+//!
+//! The stream is opened via `open_duplex_stream`, which reports the rates each device supports
+//! if they can't share the requested one. Beware of feedback!
+
+extern crate portaudio;
+
+use portaudio as pa;
+
+const SAMPLE_RATE: f64 = 44_100.0;
+const FRAMES_PER_BUFFER: u32 = 256;
+const CHANNELS: i32 = 1;
+const NUM_SECONDS: i32 = 5;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        Err(pa::Error::DuplexRateMismatch { input, output }) => {
+            eprintln!(
+                "The devices can't both run at {} Hz: the input supports {} Hz and the output {} Hz",
+                SAMPLE_RATE, input, output
+            );
+        }
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+
+    let input = pa.default_input_device()?;
+    let output = pa.default_output_device()?;
+    if input != output {
+        println!("The input and output are different devices, so their clocks may drift apart.");
+    }
+
+    let in_params = pa::StreamParameters::<f32>::builder(input)
+        .channels(CHANNELS)
+        .build_input(&pa)?;
+    let out_params = pa::StreamParameters::<f32>::builder(output)
+        .channels(CHANNELS)
+        .build_output(&pa)?;
+    let settings =
+        pa::DuplexStreamSettings::new(in_params, out_params, SAMPLE_RATE, FRAMES_PER_BUFFER);
+
+    let callback = move |pa::DuplexStreamCallbackArgs {
+                             in_buffer,
+                             out_buffer,
+                             ..
+                         }| {
+        out_buffer.copy_from_slice(in_buffer);
+        pa::Continue
+    };
+
+    let mut stream = pa.open_duplex_stream(settings, callback)?;
+    stream.start()?;
+
+    println!("Echoing for {} seconds.", NUM_SECONDS);
+    pa.sleep(NUM_SECONDS * 1_000);
+
+    stream.stop()?;
+    stream.close()?;
+
+    Ok(())
+}
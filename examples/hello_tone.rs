@@ -0,0 +1,31 @@
+//! Play a 440 Hz tone on the default output device for two seconds, with the default settings.
+
+extern crate portaudio;
+
+use pa::tone::SineGenerator;
+use portaudio as pa;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+    let info = pa.device_info(pa.default_output_device()?)?;
+    let channels = std::cmp::min(2, info.max_output_channels) as usize;
+    let mut tone = SineGenerator::new(440.0, info.default_sample_rate, 0.2, channels);
+    let mut stream = pa.open_default_output_stream(move |args| {
+        tone.fill(args.buffer);
+        pa::Continue
+    })?;
+
+    stream.start()?;
+    pa.sleep(2_000);
+    stream.stop()?;
+    stream.close()
+}
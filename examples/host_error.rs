@@ -0,0 +1,42 @@
+//! Demonstrates how to retrieve the host API's own description of an error.
+//!
+//! Many host API failures are reported by PortAudio as `UnanticipatedHostError`. When that
+//! happens, `PortAudio::last_host_error_info` can be used to find out what actually went wrong.
+
+extern crate portaudio;
+
+use portaudio as pa;
+
+const SAMPLE_RATE: f64 = 44_100.0;
+const CHANNELS: i32 = 2;
+const FRAMES: u32 = 256;
+
+fn main() {
+    let pa = match pa::PortAudio::new() {
+        Ok(pa) => pa,
+        Err(err) => {
+            eprintln!("Failed to initialise PortAudio: {}", err);
+            return;
+        }
+    };
+
+    match run(&pa) {
+        Ok(_) => println!("Opened and started the default output stream without error"),
+        Err(pa::Error::UnanticipatedHostError) => {
+            let info = pa.last_host_error_info();
+            eprintln!(
+                "{:?} failed with error code {}: {}",
+                info.host_api_type, info.error_code, info.error_text
+            );
+        }
+        Err(err) => eprintln!("Example failed with the following: {}", err),
+    }
+}
+
+fn run(pa: &pa::PortAudio) -> Result<(), pa::Error> {
+    let settings = pa.default_output_stream_settings::<f32>(CHANNELS, SAMPLE_RATE, FRAMES)?;
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+    stream.stop()?;
+    Ok(())
+}
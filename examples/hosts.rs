@@ -15,10 +15,23 @@ fn main() {
 fn run() -> Result<(), pa::Error> {
     let pa = pa::PortAudio::new()?;
 
-    println!("Default Host API: {:?}", pa.default_host_api());
+    let (default_idx, default_info) = pa.default_host_api_info()?;
+    println!(
+        "Default Host API: {:?} ({})",
+        default_idx, default_info.name
+    );
+    // The convenience method should agree with looking the default host API up in two steps.
+    let idx = pa.default_host_api()?;
+    assert_eq!(default_idx, idx);
+    assert_eq!(Some(default_info), pa.host_api_info(idx));
+
     println!("All Host APIs:");
-    for host in pa.host_apis() {
-        println!("{:#?}", host);
+    for (idx, info) in pa.host_apis() {
+        println!("{:#?}", info);
+        for device in pa.host_api_devices(idx)? {
+            let (device_idx, device_info) = device?;
+            println!("    {:?}: {}", device_idx, device_info.name);
+        }
     }
 
     Ok(())
@@ -0,0 +1,67 @@
+//! Select devices by name, as a `--device "Scarlett"` command line option would, among devices
+//! with crafted names listed by the `mock-backend` feature:
+//!
+//! ```text
+//! cargo run --example mock_find_device --features mock-backend
+//! ```
+//!
+//! The checks of device selection live in `tests/mock_find_device.rs`.
+
+extern crate portaudio;
+
+use pa::Direction::{Input, Output};
+use portaudio as pa;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let _lock = pa::mock::lock();
+    pa::mock::configure(pa::mock::MockConfig::default());
+    pa::mock::add_device("Focusrite Scarlett 2i2", 2, 2);
+    pa::mock::add_device("Scarlett Solo", 2, 0);
+    pa::mock::add_device("Speakers", 0, 2);
+    pa::mock::add_device("USB Mic", 1, 0);
+    pa::mock::add_device("USB Mic Pro", 1, 0);
+
+    let pa = pa::PortAudio::new()?;
+    for device in pa.devices()? {
+        let (idx, info) = device?;
+        println!("{}: {:?}", idx.0, info.name);
+    }
+
+    // Names are matched regardless of case, and only against devices with channels in the
+    // requested direction. Ambiguous names are reported along with the candidates.
+    for &(name, direction) in &[
+        ("scarlett", Output),
+        ("scarlett", Input),
+        ("speakers", Input),
+        ("usb mic", Input),
+    ] {
+        match pa.find_device(name, direction) {
+            Ok(idx) => println!(
+                "{:?} {:?}: {:?}",
+                name,
+                direction,
+                pa.device_info(idx)?.name
+            ),
+            Err(pa::Error::AmbiguousDevice { .. }) => {
+                let names = pa
+                    .find_devices(name, direction)?
+                    .into_iter()
+                    .map(|idx| pa.device_info(idx).map(|info| info.name))
+                    .collect::<Result<Vec<_>, _>>()?;
+                println!("{:?} {:?}: one of {:?}", name, direction, names);
+            }
+            Err(err) => println!("{:?} {:?}: {}", name, direction, err),
+        }
+    }
+    pa.terminate()
+}
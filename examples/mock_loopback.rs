@@ -0,0 +1,75 @@
+//! Round-trip samples through the virtual loopback device of the `mock-backend` feature, which
+//! needs no audio hardware:
+//!
+//! ```text
+//! cargo run --example mock_loopback --features mock-backend
+//! ```
+//!
+//! The checks of the virtual device's behaviour live in `tests/mock_loopback.rs`.
+
+extern crate portaudio;
+
+use portaudio as pa;
+
+const SAMPLE_RATE: f64 = 48_000.0;
+const CHANNELS: i32 = 2;
+const FRAMES: u32 = 64;
+const DELAY: u32 = 16;
+const BLOCKS: usize = 4;
+const INTERLEAVED: bool = true;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let _lock = pa::mock::lock();
+    pa::mock::configure(pa::mock::MockConfig {
+        input_channels: CHANNELS,
+        output_channels: CHANNELS,
+        sample_rate: SAMPLE_RATE,
+        loopback_delay: DELAY,
+    });
+
+    let pa = pa::PortAudio::new()?;
+    let device = pa.default_output_device()?;
+    println!("Using {:?}", pa.device_info(device)?.name);
+
+    let input = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let output = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::DuplexStreamSettings::new(input, output, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+
+    let block_len = (FRAMES * CHANNELS as u32) as usize;
+    let written: Vec<i16> = (0..(block_len * BLOCKS) as i16).collect();
+    let mut read = Vec::new();
+    for block in written.chunks(block_len) {
+        stream.write(FRAMES, |out| out.copy_from_slice(block))?;
+        read.extend_from_slice(stream.read(FRAMES)?);
+    }
+    stream.stop()?;
+    stream.close()?;
+
+    // The input lags the output by the configured delay, which is filled with silence.
+    let delay = (DELAY * CHANNELS as u32) as usize;
+    let silent = read[..delay].iter().all(|&s| s == 0);
+    let looped_back = read[delay..] == written[..written.len() - delay];
+    println!(
+        "Read back {} samples after {} frames of silence: {}",
+        read.len(),
+        DELAY,
+        if silent && looped_back {
+            "they match the written samples"
+        } else {
+            "they differ from the written samples"
+        }
+    );
+    Ok(())
+}
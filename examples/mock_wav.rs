@@ -0,0 +1,78 @@
+//! Play a WAV file into the loopback of the `mock-backend` feature and record it back:
+//!
+//! ```text
+//! cargo run --example mock_wav --features "mock-backend wav"
+//! ```
+//!
+//! The checks of WAV playback and recording live in `tests/mock_wav.rs`.
+
+extern crate portaudio;
+
+use pa::wav::{WavError, WavReader, WavSpec, WavWriter};
+use portaudio as pa;
+use std::env;
+use std::time::Duration;
+
+const CHANNELS: u16 = 2;
+const SAMPLE_RATE: u32 = 48_000;
+const FRAMES: usize = 12_000;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run() -> Result<(), WavError> {
+    let _lock = pa::mock::lock();
+    pa::mock::configure(pa::mock::MockConfig {
+        input_channels: CHANNELS as i32,
+        output_channels: CHANNELS as i32,
+        sample_rate: SAMPLE_RATE as f64,
+        loopback_delay: 0,
+    });
+    let dir = env::temp_dir();
+    let played = dir.join("portaudio_mock_wav_played.wav");
+    let recorded = dir.join("portaudio_mock_wav_recorded.wav");
+
+    let pa = pa::PortAudio::new()?;
+    let device = pa.default_output_device()?;
+    let duration = Duration::from_millis((FRAMES as u64 * 1000) / SAMPLE_RATE as u64);
+
+    let samples: Vec<i16> = (0..FRAMES * CHANNELS as usize)
+        .map(|i| (i % 2000) as i16 - 1000)
+        .collect();
+    let spec = WavSpec {
+        channels: CHANNELS,
+        sample_rate: SAMPLE_RATE,
+        sample_format: pa::SampleFormat::I16,
+    };
+    let mut writer = WavWriter::create(&played, spec)?;
+    writer.write_samples(&samples)?;
+    writer.finalize()?;
+    println!("Playing {}", played.display());
+    pa.play_wav_blocking(&played, device)?;
+
+    let recording = pa.record_to_wav::<i16, _>(device, duration, &recorded)?;
+    println!(
+        "Recorded {} frames to {}",
+        recording.frames,
+        recorded.display()
+    );
+    let mut reader = WavReader::open(&recorded)?;
+    let mut read = vec![0i16; samples.len()];
+    let count = reader.read_samples(&mut read)?;
+    println!(
+        "The recording {} the played file",
+        if count == samples.len() && read == samples {
+            "matches"
+        } else {
+            "differs from"
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,69 @@
+//! Play two sine bursts back to back through an `OutputQueue`.
+//!
+//! The first burst ends part way through a callback buffer and the second picks up the phase
+//! where the first left off, so the seam between them is inaudible as long as no silence is
+//! inserted there.
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::f32::consts::PI;
+
+const CHANNELS: i32 = 2;
+const SAMPLE_RATE: f64 = 44_100.0;
+const FRAMES_PER_BUFFER: u32 = 64;
+const FREQUENCIES: [f32; 2] = [440.0, 660.0];
+// Deliberately not a multiple of FRAMES_PER_BUFFER.
+const BURST_FRAMES: usize = 44_100 + 37;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+
+    let settings =
+        pa.default_output_stream_settings::<f32>(CHANNELS, SAMPLE_RATE, FRAMES_PER_BUFFER)?;
+    let (mut queue, mut reader) = pa::OutputQueue::new(BURST_FRAMES * CHANNELS as usize);
+    let callback = move |pa::OutputStreamCallbackArgs { buffer, .. }| reader.fill(buffer);
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+
+    // Accumulate the phase across both bursts, so the second doesn't jump to a new phase.
+    let mut phase = 0.0f32;
+    let mut bursts = FREQUENCIES.iter().map(|&frequency| {
+        let mut burst = Vec::with_capacity(BURST_FRAMES * CHANNELS as usize);
+        for _ in 0..BURST_FRAMES {
+            let sample = phase.sin() * 0.2;
+            burst.extend((0..CHANNELS).map(|_| sample));
+            phase = (phase + 2.0 * PI * frequency / SAMPLE_RATE as f32) % (2.0 * PI);
+        }
+        burst
+    });
+
+    // Queue the first burst before starting so that playback doesn't begin with an underrun.
+    if let Some(burst) = bursts.next() {
+        queue.enqueue(burst);
+    }
+    stream.start()?;
+    println!("Playing {} bursts.", FREQUENCIES.len());
+    for burst in bursts {
+        queue.enqueue(burst);
+    }
+    queue.close();
+
+    while stream.is_active()? {
+        pa.sleep(10);
+    }
+    stream.stop()?;
+    stream.close()?;
+
+    println!("Test finished.");
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! Play a WAV file on the default output device, printing its progress:
+//!
+//! ```text
+//! cargo run --example play_wav --features wav -- recording.wav
+//! ```
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::env;
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::wav::WavError> {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "recording.wav".to_string());
+    let spec = pa::wav::WavReader::open(&path)?.spec();
+
+    let pa = pa::PortAudio::new()?;
+    let device = pa.default_output_device()?;
+    println!(
+        "Playing {} ({} channels at {} Hz, {:?}) on {:?}",
+        path,
+        spec.channels,
+        spec.sample_rate,
+        spec.sample_format,
+        pa.device_info(device)?.name
+    );
+    // Report the progress about once a second while the file plays in the background.
+    let playback = pa.start_wav_playback(&path, device)?;
+    while playback.is_playing()? {
+        let seconds = playback.position() / spec.sample_rate as u64;
+        let total = playback.frames() / spec.sample_rate as u64;
+        println!("{}s / {}s", seconds, total);
+        thread::sleep(Duration::from_secs(1));
+    }
+    playback.wait()?;
+    println!("Finished playing.");
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! Play a sawtooth wave through a stream whose sample format is chosen at runtime.
+//!
+//! Pass `i16` as the first argument to play 16-bit samples, otherwise 32-bit floats are used. The
+//! same stream type is used either way, with the samples being written to the callback's buffer
+//! as bytes.
+
+extern crate portaudio;
+
+use portaudio as pa;
+
+const CHANNELS: i32 = 2;
+const NUM_SECONDS: i32 = 3;
+const SAMPLE_RATE: f64 = 44_100.0;
+const FRAMES_PER_BUFFER: u32 = 64;
+const INTERLEAVED: bool = true;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let format = match std::env::args().nth(1).as_ref().map(|s| &s[..]) {
+        Some("i16") => pa::SampleFormat::I16,
+        _ => pa::SampleFormat::F32,
+    };
+    println!("PortAudio Test: output {:?} sawtooth wave.", format);
+
+    let pa = pa::PortAudio::new()?;
+
+    let device = pa.default_output_device()?;
+    let latency = pa.device_info(device)?.default_low_output_latency;
+    let params = pa::StreamParameters::raw(device, CHANNELS, format, INTERLEAVED, latency);
+    let sample_size = format.size_in_bytes() as usize;
+    let settings = pa::RawOutputStreamSettings::new(params, SAMPLE_RATE, FRAMES_PER_BUFFER);
+
+    let mut value = -1.0f32;
+    let callback = move |pa::OutputStreamCallbackArgs { buffer, .. }| {
+        for frame in buffer.chunks_mut(sample_size * CHANNELS as usize) {
+            for sample in frame.chunks_mut(sample_size) {
+                match format {
+                    pa::SampleFormat::I16 => {
+                        let amp = value * 0.2 * i16::MAX as f32;
+                        sample.copy_from_slice(&(amp as i16).to_ne_bytes());
+                    }
+                    _ => sample.copy_from_slice(&(value * 0.2).to_ne_bytes()),
+                }
+            }
+            value += 0.005;
+            if value >= 1.0 {
+                value -= 2.0;
+            }
+        }
+        pa::Continue
+    };
+
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+
+    stream.start()?;
+
+    println!("Play for {} seconds.", NUM_SECONDS);
+    pa.sleep(NUM_SECONDS * 1_000);
+
+    stream.stop()?;
+    stream.close()?;
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! Record a few seconds from the default input device to a 16-bit WAV file:
+//!
+//! ```text
+//! cargo run --example record_wav --features wav -- recording.wav
+//! ```
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::env;
+use std::time::Duration;
+
+const SECONDS: u64 = 5;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::wav::WavError> {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "recording.wav".to_string());
+
+    let pa = pa::PortAudio::new()?;
+    let device = pa.default_input_device()?;
+    println!(
+        "Recording {} seconds from {:?} to {}",
+        SECONDS,
+        pa.device_info(device)?.name,
+        path
+    );
+
+    let recording = pa.record_to_wav::<i16, _>(device, Duration::from_secs(SECONDS), &path)?;
+    println!(
+        "Recorded {} frames, dropping {} to input overflows.",
+        recording.frames, recording.dropped_frames
+    );
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! Pass a known sequence of samples from one thread to another through a `RingBuffer`.
+//!
+//! The producer writes a counting sequence in uneven chunks while the consumer reads it back in
+//! chunks of a different size, checking that every value arrives exactly once and in order. No
+//! audio device is required.
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::thread;
+
+const CAPACITY: usize = 100;
+const TOTAL: u32 = 1_000_000;
+
+fn main() {
+    let ring = pa::RingBuffer::<u32>::new(CAPACITY);
+    println!(
+        "Requested a capacity of {}, got {}.",
+        CAPACITY,
+        ring.capacity()
+    );
+    let (mut writer, mut reader) = ring.split();
+
+    let producer = thread::spawn(move || {
+        let mut next = 0;
+        let mut chunk = [0u32; 37];
+        while next < TOTAL {
+            let len = std::cmp::min(chunk.len() as u32, TOTAL - next) as usize;
+            for (i, value) in chunk[..len].iter_mut().enumerate() {
+                *value = next + i as u32;
+            }
+            let mut written = 0;
+            while written < len {
+                written += writer.write(&chunk[written..len]);
+                thread::yield_now();
+            }
+            next += len as u32;
+        }
+    });
+
+    let consumer = thread::spawn(move || {
+        let mut expected = 0;
+        let mut chunk = [0u32; 23];
+        while expected < TOTAL {
+            let read = reader.read(&mut chunk);
+            for &value in &chunk[..read] {
+                assert_eq!(value, expected, "the sequence arrived out of order");
+                expected += 1;
+            }
+            if read == 0 {
+                thread::yield_now();
+            }
+        }
+        assert_eq!(reader.read_available(), 0);
+    });
+
+    producer.join().unwrap();
+    consumer.join().unwrap();
+
+    println!("Received all {} values in order.", TOTAL);
+}
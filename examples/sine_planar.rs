@@ -0,0 +1,76 @@
+//! Play a different sine wave on each channel of a non-interleaved stream for several seconds.
+//!
+//! Each channel has its own buffer in a non-interleaved stream, which the callback receives via
+//! the `planar` field of its arguments.
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::f64::consts::PI;
+
+const CHANNELS: i32 = 2;
+const NUM_SECONDS: i32 = 5;
+const SAMPLE_RATE: f64 = 44_100.0;
+const FRAMES_PER_BUFFER: u32 = 64;
+const TABLE_SIZE: usize = 200;
+const INTERLEAVED: bool = false;
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    println!(
+        "PortAudio Test: output non-interleaved sine waves. SR = {}, BufSize = {}",
+        SAMPLE_RATE, FRAMES_PER_BUFFER
+    );
+
+    // Initialise sinusoidal wavetable.
+    let mut sine = [0.0; TABLE_SIZE];
+    for i in 0..TABLE_SIZE {
+        sine[i] = (i as f64 / TABLE_SIZE as f64 * PI * 2.0).sin() as f32;
+    }
+    // The second channel steps through the table three times as fast as the first.
+    let mut phases = [0; CHANNELS as usize];
+    let steps = [1, 3];
+
+    let pa = pa::PortAudio::new()?;
+
+    let device = pa.default_output_device()?;
+    let params = pa::StreamParameters::<f32>::builder(device)
+        .channels(CHANNELS)
+        .interleaved(INTERLEAVED)
+        .build_output(&pa)?;
+    pa.is_output_format_supported(params, SAMPLE_RATE)?;
+    let settings = pa::OutputStreamSettings::new(params, SAMPLE_RATE, FRAMES_PER_BUFFER);
+
+    let callback = move |args: pa::OutputStreamCallbackArgs<f32>| {
+        let mut planar = args.planar.expect("the stream is non-interleaved");
+        for (channel, buffer) in planar.channels_mut().enumerate() {
+            for sample in buffer.iter_mut() {
+                *sample = sine[phases[channel]];
+                phases[channel] = (phases[channel] + steps[channel]) % TABLE_SIZE;
+            }
+        }
+        pa::Continue
+    };
+
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+
+    stream.start()?;
+
+    println!("Play for {} seconds.", NUM_SECONDS);
+    pa.sleep(NUM_SECONDS * 1_000);
+
+    stream.stop()?;
+    stream.close()?;
+
+    println!("Test finished.");
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Play a sine wave that sweeps upwards in pitch, generated by state owned by the callback.
+//!
+//! The callback takes ownership of a `Sweep`, which keeps its phase accumulator and frequency
+//! between calls without needing any global state.
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::f64::consts::PI;
+
+const CHANNELS: i32 = 2;
+const NUM_SECONDS: i32 = 4;
+const SAMPLE_RATE: f64 = 44_100.0;
+const FRAMES_PER_BUFFER: u32 = 64;
+
+/// A sine oscillator whose frequency rises exponentially.
+struct Sweep {
+    /// The phase in cycles, from `0.0` to `1.0`.
+    phase: f64,
+    /// The current frequency in Hz.
+    hz: f64,
+    /// The factor by which the frequency is multiplied each frame.
+    hz_growth: f64,
+}
+
+impl Sweep {
+    fn next_sample(&mut self) -> f32 {
+        let sample = (self.phase * 2.0 * PI).sin() as f32;
+        self.phase = (self.phase + self.hz / SAMPLE_RATE) % 1.0;
+        self.hz *= self.hz_growth;
+        sample
+    }
+}
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+
+    let settings = pa.default_output_stream_settings(CHANNELS, SAMPLE_RATE, FRAMES_PER_BUFFER)?;
+
+    // Sweep from 220Hz to 880Hz over the length of the stream.
+    let frames = SAMPLE_RATE * NUM_SECONDS as f64;
+    let mut sweep = Sweep {
+        phase: 0.0,
+        hz: 220.0,
+        hz_growth: 4.0f64.powf(1.0 / frames),
+    };
+
+    let callback = move |pa::OutputStreamCallbackArgs { buffer, .. }| {
+        for frame in buffer.chunks_mut(CHANNELS as usize) {
+            let sample = sweep.next_sample() * 0.2;
+            for out in frame.iter_mut() {
+                *out = sample;
+            }
+        }
+        pa::Continue
+    };
+
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+
+    stream.start()?;
+
+    println!("Play for {} seconds.", NUM_SECONDS);
+    pa.sleep(NUM_SECONDS * 1_000);
+
+    stream.stop()?;
+    stream.close()?;
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! Record 5 seconds of whatever the default output device is playing to `loopback.wav`.
+//!
+//! This uses WASAPI loopback capture and so only works on Windows. The callback passes the
+//! captured samples to the main thread through a `RingBuffer`, which writes them to the WAV file
+//! as 16-bit PCM.
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+const NUM_SECONDS: u32 = 5;
+const FRAMES_PER_BUFFER: u32 = 512;
+const PATH: &'static str = "loopback.wav";
+
+fn main() {
+    match run() {
+        Ok(_) => {}
+        e => {
+            eprintln!("Example failed with the following: {:?}", e);
+        }
+    }
+}
+
+fn run() -> Result<(), pa::Error> {
+    let pa = pa::PortAudio::new()?;
+
+    // Find the default output device of the WASAPI host API.
+    let wasapi = pa.host_api_type_id_to_index(pa::HostApiTypeId::WASAPI)?;
+    let (output_device, output_info) = pa.default_output_device_for_host_api(wasapi)?;
+    println!("Recording the output of \"{}\".", output_info.name);
+
+    let channels = output_info.max_output_channels;
+    let sample_rate = output_info.default_sample_rate;
+    let latency = output_info.default_low_output_latency;
+    let params = pa::StreamParameters::<f32>::new(output_device, channels, true, latency);
+    let settings = pa::InputStreamSettings::new(params, sample_rate, FRAMES_PER_BUFFER);
+
+    // Leave room for a second of audio in case the main thread falls behind.
+    let ring = pa::RingBuffer::new(sample_rate as usize * channels as usize);
+    let (mut writer, mut reader) = ring.split();
+
+    let callback = move |pa::InputStreamCallbackArgs { buffer, .. }| {
+        writer.write(buffer);
+        pa::Continue
+    };
+
+    let mut stream = pa.open_wasapi_loopback_stream(output_device, settings, callback)?;
+    stream.start()?;
+
+    let total_samples = (sample_rate as u32 * NUM_SECONDS) as usize * channels as usize;
+    let mut samples = Vec::with_capacity(total_samples);
+    let mut chunk = [0.0f32; 4096];
+    while samples.len() < total_samples {
+        let read = reader.read(&mut chunk);
+        samples.extend_from_slice(&chunk[..read]);
+        if read == 0 {
+            pa.sleep(10);
+        }
+    }
+    samples.truncate(total_samples);
+
+    stream.stop()?;
+    stream.close()?;
+
+    write_wav(PATH, channels as u16, sample_rate as u32, &samples).expect("failed to write WAV");
+    println!("Wrote {} seconds of audio to {}.", NUM_SECONDS, PATH);
+
+    Ok(())
+}
+
+/// Write the interleaved samples to a 16-bit PCM WAV file.
+fn write_wav(path: &str, channels: u16, sample_rate: u32, samples: &[f32]) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let data_len = samples.len() as u32 * 2;
+    let block_align = channels * 2;
+    file.write_all(b"RIFF")?;
+    file.write_all(&(36 + data_len).to_le_bytes())?;
+    file.write_all(b"WAVEfmt ")?;
+    file.write_all(&16u32.to_le_bytes())?;
+    file.write_all(&1u16.to_le_bytes())?;
+    file.write_all(&channels.to_le_bytes())?;
+    file.write_all(&sample_rate.to_le_bytes())?;
+    file.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+    file.write_all(&block_align.to_le_bytes())?;
+    file.write_all(&16u16.to_le_bytes())?;
+    file.write_all(b"data")?;
+    file.write_all(&data_len.to_le_bytes())?;
+    for &sample in samples {
+        let sample = (sample.max(-1.0).min(1.0) * i16::max_value() as f32) as i16;
+        file.write_all(&sample.to_le_bytes())?;
+    }
+    file.flush()
+}
@@ -0,0 +1,57 @@
+//! Count the xruns reported to a stream callback from another thread with an `XrunCounter`.
+//!
+//! The flags are synthesised here rather than taken from a stream, so that the counts are known
+//! in advance and the example runs without audio hardware.
+
+extern crate portaudio;
+
+use pa::stream_callback_flags as flags;
+use portaudio as pa;
+use std::thread;
+
+const CALLBACKS: usize = 1000;
+
+fn main() {
+    let counter = pa::XrunCounter::new();
+
+    // Stands in for the stream callback, which would record the `flags` of its arguments.
+    let callback_counter = counter.clone();
+    let callback = thread::spawn(move || {
+        for i in 0..CALLBACKS {
+            let mut flags = pa::StreamCallbackFlags::empty();
+            if i % 10 == 0 {
+                flags |= flags::OUTPUT_UNDERFLOW;
+            }
+            if i % 25 == 0 {
+                flags |= flags::INPUT_OVERFLOW;
+            }
+            if i % 100 == 0 {
+                flags |= flags::INPUT_UNDERFLOW | flags::PRIMING_OUTPUT;
+            }
+            assert_eq!(flags.has_xrun(), i % 10 == 0 || i % 25 == 0 || i % 100 == 0);
+            callback_counter.record(flags);
+        }
+    });
+
+    // Meanwhile the counts can be polled, e.g. by a user interface.
+    while counter.snapshot().output_underflows < (CALLBACKS / 10) as u64 {
+        thread::yield_now();
+    }
+    callback.join().unwrap();
+
+    let stats = counter.snapshot();
+    println!("{:?}", stats);
+    assert_eq!(
+        stats,
+        pa::XrunStats {
+            input_underflows: (CALLBACKS / 100) as u64,
+            input_overflows: (CALLBACKS / 25) as u64,
+            output_underflows: (CALLBACKS / 10) as u64,
+            output_overflows: 0,
+        }
+    );
+    assert_eq!(stats.total(), 10 + 40 + 100);
+
+    counter.reset();
+    assert_eq!(counter.snapshot(), pa::XrunStats::default());
+}
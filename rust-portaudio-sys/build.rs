@@ -19,12 +19,24 @@
 // IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
+#[cfg(feature = "bindgen")]
+extern crate bindgen;
+#[cfg(feature = "pure-rust-extract")]
+extern crate bzip2;
+#[cfg(feature = "pure-rust-extract")]
+extern crate flate2;
 extern crate pkg_config;
+#[cfg(feature = "pure-rust-extract")]
+extern crate tar;
 
 use std::env;
 use std::fmt::Display;
-use std::path::Path;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::thread;
+use std::time::Duration;
 
 #[cfg(all(unix, not(target_os = "linux"), not(target_os = "windows")))]
 use unix_platform as platform;
@@ -32,29 +44,417 @@ use unix_platform as platform;
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
 
+    // The `mock-backend` feature stands in for the library, so there is nothing to find or build.
+    if env::var_os("CARGO_FEATURE_MOCK_BACKEND").is_some() {
+        generate_bindings(&[]);
+        return;
+    }
+
+    println!("cargo:rerun-if-env-changed=PORTAUDIO_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=PORTAUDIO_INCLUDE_DIR");
+    if let Some(lib_dir) = env::var_os("PORTAUDIO_LIB_DIR") {
+        link_lib_dir(Path::new(&lib_dir));
+        let include_dir = env::var_os("PORTAUDIO_INCLUDE_DIR").map(PathBuf::from);
+        generate_bindings(&include_dir.into_iter().collect::<Vec<_>>());
+        return;
+    }
+
     println!("cargo:rerun-if-env-changed=PORTAUDIO_ONLY_STATIC");
-    if env::var("PORTAUDIO_ONLY_STATIC").is_err() {
+    // A system PortAudio is very unlikely to include the ASIO host API, so the `asio` feature
+    // always takes the static build.
+    if env::var("PORTAUDIO_ONLY_STATIC").is_err() && !asio_requested() {
         // If pkg-config finds a library on the system, we are done
-        if pkg_config::Config::new().atleast_version("19").find("portaudio-2.0").is_ok() {
-            return;
+        match pkg_config::Config::new()
+            .atleast_version("19")
+            .probe("portaudio-2.0")
+        {
+            Ok(library) => {
+                generate_bindings(&library.include_paths);
+                return;
+            }
+            // When cross compiling, pkg-config is only consulted once it has been set up for the
+            // target (e.g. via `PKG_CONFIG_SYSROOT_DIR`), as the host's PortAudio can't be linked
+            // into the target. Without it there is no system library to use.
+            Err(pkg_config::Error::CrossCompilation) => {}
+            Err(_) => {}
         }
     }
 
-    build();
+    let include_dir = build();
+    generate_bindings(&[include_dir]);
+}
+
+// The frameworks that PortAudio's CoreAudio host API needs on macOS, for when they can't be read
+// from its pkg-config file.
+const MACOS_FRAMEWORKS: &'static [&'static str] = &[
+    "CoreServices",
+    "CoreFoundation",
+    "AudioUnit",
+    "AudioToolbox",
+    "CoreAudio",
+];
+
+// Links the PortAudio library found in `PORTAUDIO_LIB_DIR`, e.g. a vendored build without a
+// pkg-config file, instead of searching for or building one.
+//
+// A shared library is preferred when the directory holds both. A static library's own
+// dependencies can't be known without pkg-config, so apart from the macOS frameworks they are
+// left to the user to add, e.g. `asound` on Linux via `RUSTFLAGS`.
+fn link_lib_dir(lib_dir: &Path) {
+    if !lib_dir.is_dir() {
+        panic!(
+            "PORTAUDIO_LIB_DIR is set to `{}`, which is not a directory",
+            lib_dir.display()
+        );
+    }
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+
+    let target_os = target_os();
+    if target_os == "windows" {
+        // `portaudio.lib` may be an import library or a static library, which are linked alike.
+        if !lib_dir.join("portaudio.lib").is_file() {
+            panic!(
+                "PORTAUDIO_LIB_DIR is set to `{}`, which does not hold `portaudio.lib`",
+                lib_dir.display()
+            );
+        }
+    } else {
+        let shared = if target_os == "macos" {
+            "libportaudio.dylib"
+        } else {
+            "libportaudio.so"
+        };
+        if !lib_dir.join(shared).exists() {
+            if !lib_dir.join("libportaudio.a").is_file() {
+                panic!(
+                    "PORTAUDIO_LIB_DIR is set to `{}`, which holds neither `{}` nor \
+                     `libportaudio.a`",
+                    lib_dir.display(),
+                    shared
+                );
+            }
+            println!("cargo:rustc-link-lib=static=portaudio");
+        }
+    }
+
+    if target_os == "macos" {
+        for framework in MACOS_FRAMEWORKS {
+            println!("cargo:rustc-link-lib=framework={}", framework);
+        }
+    }
+}
+
+// The operating system being built for, which differs from the one running the build script when
+// cross compiling.
+fn target_os() -> String {
+    env::var("CARGO_CFG_TARGET_OS").unwrap_or_default()
+}
+
+// Whether the `asio` feature is enabled for a Windows target. It is ignored, with a warning, for
+// every other target since ASIO only exists on Windows.
+fn asio_requested() -> bool {
+    if env::var_os("CARGO_FEATURE_ASIO").is_none() {
+        return false;
+    }
+    if target_os() != "windows" {
+        println!("cargo:warning=the `asio` feature only applies to Windows targets and is ignored");
+        return false;
+    }
+    true
+}
+
+// Whether the `prebuilt` feature is enabled for a target other than Windows, which always uses a
+// prebuilt package unless the `asio` feature is enabled.
+fn prebuilt_requested() -> bool {
+    env::var_os("CARGO_FEATURE_PREBUILT").is_some() && target_os() != "windows"
 }
 
-fn build() {
+// Builds (or reuses) the static library and returns the directory holding its headers.
+fn build() -> PathBuf {
     // retrieve cargo deps out dir
     let out_dir_str = env::var("OUT_DIR").unwrap();
     let out_dir = Path::new(&out_dir_str);
 
     let static_lib = out_dir.join("lib/libportaudio.a");
     if let Err(_) = ::std::fs::metadata(static_lib) {
-        platform::download();
-        platform::build(out_dir);
+        if !(prebuilt_requested() && prebuilt::fetch(out_dir)) {
+            let archive = fetch_archive(out_dir);
+            platform::build(out_dir, &archive);
+        }
     }
 
     platform::print_libs(out_dir);
+    platform::include_dir(out_dir)
+}
+
+// Without the `bindgen` feature the bindings committed in `src/portaudio.rs` are used.
+#[cfg(not(feature = "bindgen"))]
+fn generate_bindings(_include_dirs: &[PathBuf]) {}
+
+// Generates `$OUT_DIR/portaudio.rs` from the `portaudio.h` of the library we are linking against,
+// using the same options as the committed bindings (see `src/lib.rs`).
+#[cfg(feature = "bindgen")]
+fn generate_bindings(include_dirs: &[PathBuf]) {
+    let bindings = bindgen::Builder::default()
+        .header_contents("wrapper.h", "#include <portaudio.h>")
+        .clang_args(
+            include_dirs
+                .iter()
+                .map(|dir| format!("-I{}", dir.display())),
+        )
+        .constified_enum("PaHostApiTypeId")
+        .constified_enum("PaErrorCode")
+        .blocklist_type("PaStreamCallbackResult")
+        .generate();
+    let bindings = match bindings {
+        Ok(bindings) => bindings,
+        Err(e) => panic!(
+            "failed to generate bindings for portaudio.h (searched {:?}): {}",
+            include_dirs, e
+        ),
+    };
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    err_to_panic(bindings.write_to_file(out_dir.join("portaudio.rs")));
+}
+
+// Returns the path to the PortAudio archive, only downloading it when the cache does not already
+// hold a copy whose checksum matches the pinned one.
+fn fetch_archive(out_dir: &Path) -> PathBuf {
+    let cache_dir = cache_dir(out_dir);
+    err_to_panic(fs::create_dir_all(&cache_dir));
+
+    let source = Source::from_env();
+
+    let archive = cache_dir.join(&source.tar_name);
+    if is_cached(&archive, &source.sha256) {
+        return archive;
+    }
+
+    // Download next to the final location and only move it into place once complete, so that an
+    // interrupted download is never mistaken for a cached archive.
+    let partial = cache_dir.join(format!("{}.part", source.tar_name));
+    platform::download(&source.url, &partial);
+    verify_checksum(&partial, &source.sha256);
+    err_to_panic(fs::rename(&partial, &archive));
+    archive
+}
+
+// Where the PortAudio archive is fetched from.
+//
+// These overrides only matter once we have decided to build PortAudio ourselves, i.e. when no
+// system library was found or `PORTAUDIO_ONLY_STATIC` is set.
+//
+// - `PORTAUDIO_SRC_URL` replaces the download URL, e.g. to point at a mirror or a patched release.
+// - `PORTAUDIO_TAR_NAME` overrides the archive file name. It defaults to the last path segment of
+//   `PORTAUDIO_SRC_URL`.
+// - `PORTAUDIO_SRC_SHA256` pins the checksum of the archive. It is required for an overridden
+//   archive, as the built-in checksum only applies to the default one, and for a default archive
+//   that has no built-in checksum. An archive is never used unverified.
+struct Source {
+    url: String,
+    tar_name: String,
+    sha256: String,
+}
+
+impl Source {
+    fn from_env() -> Source {
+        println!("cargo:rerun-if-env-changed=PORTAUDIO_SRC_URL");
+        println!("cargo:rerun-if-env-changed=PORTAUDIO_TAR_NAME");
+        println!("cargo:rerun-if-env-changed=PORTAUDIO_SRC_SHA256");
+
+        let url = env::var("PORTAUDIO_SRC_URL").ok();
+        let tar_name = env::var("PORTAUDIO_TAR_NAME").ok();
+        let sha256 = env::var("PORTAUDIO_SRC_SHA256")
+            .ok()
+            .map(|s| s.to_lowercase());
+
+        let (url, tar_name, sha256) = match url {
+            None => (
+                platform::PORTAUDIO_URL.to_string(),
+                tar_name.unwrap_or_else(|| platform::PORTAUDIO_TAR.to_string()),
+                sha256.or_else(|| platform::PORTAUDIO_SHA256.map(|s| s.to_string())),
+            ),
+            Some(url) => {
+                let tar_name = tar_name.unwrap_or_else(|| {
+                    match url.trim_end_matches('/').rsplit('/').next() {
+                        Some(name) if !name.is_empty() => name.to_string(),
+                        _ => panic!(
+                            "could not derive an archive name from PORTAUDIO_SRC_URL `{}`, \
+                             please set PORTAUDIO_TAR_NAME",
+                            url
+                        ),
+                    }
+                });
+                (url, tar_name, sha256)
+            }
+        };
+        match sha256 {
+            Some(sha256) => Source {
+                url: url,
+                tar_name: tar_name,
+                sha256: sha256,
+            },
+            None => panic!(
+                "no checksum is known for the PortAudio archive `{}`, set PORTAUDIO_SRC_SHA256 to \
+                 its sha256 so that it can be verified",
+                tar_name
+            ),
+        }
+    }
+}
+
+// Prebuilt static libraries for the `prebuilt` feature, which spare e.g. CI machines from
+// compiling PortAudio on every clean build.
+//
+// `PORTAUDIO_PREBUILT_URL` is the base URL of a directory holding a `portaudio-<target>.tar.gz`
+// per target triple, along with a `SHA256SUMS` file listing their checksums in the format written
+// by `sha256sum`. Each archive unpacks into the `include` and `lib` directories that the source
+// build installs, including `lib/pkgconfig/portaudio-2.0.pc`. A target that `SHA256SUMS` doesn't
+// list is built from source instead.
+mod prebuilt {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use super::{cache_dir, err_to_panic, extract_archive, is_cached, platform, verify_checksum};
+
+    const CHECKSUMS_NAME: &'static str = "SHA256SUMS";
+
+    // Unpacks the prebuilt library for the target into `out_dir`, or returns `false` if there is
+    // none, in which case PortAudio should be built from source.
+    pub fn fetch(out_dir: &Path) -> bool {
+        println!("cargo:rerun-if-env-changed=PORTAUDIO_PREBUILT_URL");
+        let base_url = match env::var("PORTAUDIO_PREBUILT_URL") {
+            Ok(url) => url.trim_end_matches('/').to_string(),
+            Err(_) => {
+                println!(
+                    "cargo:warning=the `prebuilt` feature needs PORTAUDIO_PREBUILT_URL to be \
+                     set, building PortAudio from source"
+                );
+                return false;
+            }
+        };
+        let tar_name = format!("portaudio-{}.tar.gz", env::var("TARGET").unwrap());
+
+        // The checksums are fetched on every clean build rather than cached, so that a
+        // republished archive replaces the one in the cache.
+        let checksums = out_dir.join(CHECKSUMS_NAME);
+        platform::download(&format!("{}/{}", base_url, CHECKSUMS_NAME), &checksums);
+        let sha256 = match find_checksum(&err_to_panic(fs::read_to_string(&checksums)), &tar_name) {
+            Some(sha256) => sha256,
+            None => {
+                println!(
+                    "cargo:warning=`{}/{}` lists no `{}`, building PortAudio from source",
+                    base_url, CHECKSUMS_NAME, tar_name
+                );
+                return false;
+            }
+        };
+
+        let cache_dir = cache_dir(out_dir);
+        err_to_panic(fs::create_dir_all(&cache_dir));
+        let archive = cache_dir.join(&tar_name);
+        if !is_cached(&archive, &sha256) {
+            let partial = cache_dir.join(format!("{}.part", tar_name));
+            platform::download(&format!("{}/{}", base_url, tar_name), &partial);
+            verify_checksum(&partial, &sha256);
+            err_to_panic(fs::rename(&partial, &archive));
+        }
+
+        extract_archive(&archive, out_dir);
+        if !out_dir.join("lib/libportaudio.a").is_file() {
+            panic!(
+                "the prebuilt archive `{}` does not contain `lib/libportaudio.a`",
+                tar_name
+            );
+        }
+        relocate_pc_file(out_dir, &tar_name);
+        true
+    }
+
+    // The `.pc` file names the directory that PortAudio was installed into on the machine that
+    // built it, so its `prefix` is pointed at where the archive was unpacked instead.
+    fn relocate_pc_file(out_dir: &Path, tar_name: &str) {
+        let pc_file = out_dir.join("lib/pkgconfig/portaudio-2.0.pc");
+        let pc = match fs::read_to_string(&pc_file) {
+            Ok(pc) => pc,
+            Err(_) => panic!(
+                "the prebuilt archive `{}` does not contain \
+                 `lib/pkgconfig/portaudio-2.0.pc`",
+                tar_name
+            ),
+        };
+        let relocated: String = pc
+            .lines()
+            .map(|line| {
+                if line.starts_with("prefix=") {
+                    format!("prefix={}\n", out_dir.display())
+                } else {
+                    format!("{}\n", line)
+                }
+            })
+            .collect();
+        err_to_panic(fs::write(&pc_file, relocated));
+    }
+
+    // The checksum of the file `name` in the contents of a `SHA256SUMS` file, each line of which
+    // holds a digest and a file name, the latter marked with a `*` when checked in binary mode.
+    fn find_checksum(checksums: &str, name: &str) -> Option<String> {
+        checksums
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                match (fields.next(), fields.next()) {
+                    (Some(digest), Some(file)) if file.trim_start_matches('*') == name => {
+                        Some(digest.to_lowercase())
+                    }
+                    _ => None,
+                }
+            })
+            .next()
+    }
+}
+
+// Panics unless the SHA-256 digest of the file at `path` is `expected`.
+//
+// A truncated or tampered download would otherwise only show up as a confusing `tar` failure, or
+// not at all.
+fn verify_checksum(path: &Path, expected: &str) {
+    let actual = err_to_panic(sha256::file_digest(path));
+    if actual != expected {
+        let _ = fs::remove_file(path);
+        panic!(
+            "checksum mismatch for `{}`: expected sha256 {}, found {}",
+            path.display(),
+            expected,
+            actual
+        );
+    }
+}
+
+// The directory in which downloaded archives are kept between builds.
+//
+// `$CARGO_HOME/portaudio-cache` is shared by every project on the machine. When `CARGO_HOME` is
+// not available we fall back to a directory next to cargo's `build` directory (i.e. within
+// `target/<profile>`), which at least survives `cargo clean -p portaudio_sys` and rebuilds
+// triggered by changed flags.
+fn cache_dir(out_dir: &Path) -> PathBuf {
+    match env::var_os("CARGO_HOME") {
+        Some(cargo_home) => Path::new(&cargo_home).join("portaudio-cache"),
+        None => match out_dir.ancestors().nth(3) {
+            Some(profile_dir) => profile_dir.join("portaudio-cache"),
+            None => out_dir.join("portaudio-cache"),
+        },
+    }
+}
+
+// Whether `archive` exists and matches the hash that it should have.
+fn is_cached(archive: &Path, expected_sha256: &str) -> bool {
+    match sha256::file_digest(archive) {
+        Ok(actual) => actual == expected_sha256,
+        Err(_) => false,
+    }
 }
 
 // Similar to unwrap, but panics on just the error value
@@ -74,35 +474,239 @@ fn run(command: &mut Command) {
     }
 }
 
+// How many times a download is attempted before giving up.
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+// Runs the download command made by `command` until it succeeds, retrying failures with an
+// exponential backoff (1s, then 2s, ...) as transient network errors are common on CI.
+//
+// Returns `false` without retrying when the program is not installed, and panics once every
+// attempt has failed.
+fn download_with_retries<F>(mut command: F) -> bool
+where
+    F: FnMut() -> Command,
+{
+    let mut backoff = Duration::from_secs(1);
+    for attempt in 1..DOWNLOAD_ATTEMPTS + 1 {
+        let mut command = command();
+        let string = format!("{:?}", command);
+        let failure = match command.status() {
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return false,
+            Ok(ref status) if status.success() => return true,
+            Ok(status) => format!("{}", status),
+            Err(e) => format!("{}", e),
+        };
+        if attempt == DOWNLOAD_ATTEMPTS {
+            panic!(
+                "`{}` did not execute successfully after {} attempts ({})",
+                string, DOWNLOAD_ATTEMPTS, failure
+            );
+        }
+        println!(
+            "cargo:warning=download attempt {} of {} failed ({}), retrying in {}s",
+            attempt,
+            DOWNLOAD_ATTEMPTS,
+            failure,
+            backoff.as_secs()
+        );
+        thread::sleep(backoff);
+        backoff *= 2;
+    }
+    unreachable!()
+}
+
+// Unpacks `archive` into `dest` using the system `tar`.
+#[cfg(not(feature = "pure-rust-extract"))]
+fn extract_archive(archive: &Path, dest: &Path) {
+    let flags = if is_bzip2(archive) { "-xjf" } else { "xvf" };
+    run(Command::new("tar")
+        .arg(flags)
+        .arg(archive)
+        .arg("-C")
+        .arg(dest));
+}
+
+// Unpacks `archive` into `dest` without relying on a `tar` binary being installed.
+#[cfg(feature = "pure-rust-extract")]
+fn extract_archive(archive: &Path, dest: &Path) {
+    let file = io::BufReader::new(err_to_panic(fs::File::open(archive)));
+    let name = archive.to_string_lossy();
+    let decoder: Box<dyn io::Read> = if is_bzip2(archive) {
+        Box::new(bzip2::read::BzDecoder::new(file))
+    } else if name.ends_with(".tgz") || name.ends_with(".tar.gz") {
+        Box::new(flate2::read::GzDecoder::new(file))
+    } else {
+        panic!(
+            "don't know how to extract `{}`, expected a .tgz or .tar.bz2 archive",
+            name
+        );
+    };
+    err_to_panic(tar::Archive::new(decoder).unpack(dest));
+}
+
+fn is_bzip2(archive: &Path) -> bool {
+    let name = archive.to_string_lossy();
+    name.ends_with(".tar.bz2") || name.ends_with(".tbz2")
+}
+
+// A small SHA-256 implementation so that verifying the archive does not pull in any dependencies.
+mod sha256 {
+    use std::fs::File;
+    use std::io::{self, Read};
+    use std::path::Path;
+
+    // Eight words per row, as the constants are laid out in FIPS 180-4.
+    #[rustfmt::skip]
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+        0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+        0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+        0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+        0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+        0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+        0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+    ];
+
+    #[rustfmt::skip]
+    const H: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+
+    /// Returns the lowercase hex SHA-256 digest of the file at `path`.
+    pub fn file_digest(path: &Path) -> io::Result<String> {
+        let mut file = File::open(path)?;
+        let mut state = H;
+        let mut block = [0u8; 64];
+        let mut filled = 0;
+        let mut len: u64 = 0;
+        let mut chunk = [0u8; 8192];
+        loop {
+            let n = file.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            len += n as u64;
+            for &byte in &chunk[..n] {
+                block[filled] = byte;
+                filled += 1;
+                if filled == 64 {
+                    compress(&mut state, &block);
+                    filled = 0;
+                }
+            }
+        }
+
+        // Padding: a single 1 bit, zeros, then the message length in bits.
+        block[filled] = 0x80;
+        filled += 1;
+        if filled > 56 {
+            for byte in &mut block[filled..] {
+                *byte = 0;
+            }
+            compress(&mut state, &block);
+            filled = 0;
+        }
+        for byte in &mut block[filled..56] {
+            *byte = 0;
+        }
+        block[56..].copy_from_slice(&(len * 8).to_be_bytes());
+        compress(&mut state, &block);
+
+        Ok(state.iter().map(|word| format!("{:08x}", word)).collect())
+    }
+
+    fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+        let mut w = [0u32; 64];
+        for i in 0..16 {
+            w[i] = ((block[i * 4] as u32) << 24)
+                | ((block[i * 4 + 1] as u32) << 16)
+                | ((block[i * 4 + 2] as u32) << 8)
+                | (block[i * 4 + 3] as u32);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let (mut a, mut b, mut c, mut d) = (state[0], state[1], state[2], state[3]);
+        let (mut e, mut f, mut g, mut h) = (state[4], state[5], state[6], state[7]);
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (word, value) in state.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(*value);
+        }
+    }
+}
+
 #[allow(dead_code)]
 mod unix_platform {
     use std::process::Command;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
 
     use std::env;
+    use std::fs;
 
-    use super::{err_to_panic, run};
+    use super::{download_with_retries, err_to_panic, extract_archive, run, MACOS_FRAMEWORKS};
 
-    pub const PORTAUDIO_URL: &'static str = "http://files.portaudio.com/archives/pa_stable_v190700_20210406.tgz";
+    pub const PORTAUDIO_URL: &'static str =
+        "http://files.portaudio.com/archives/pa_stable_v190700_20210406.tgz";
     pub const PORTAUDIO_TAR: &'static str = "pa_stable_v190700_20210406.tgz";
+    pub const PORTAUDIO_SHA256: Option<&'static str> =
+        Some("47efbf42c77c19a05d22e627d42873e991ec0c1357219c0d74ce6a2948cb2def");
     pub const PORTAUDIO_FOLDER: &'static str = "portaudio";
 
-    pub fn download() {
-        run(Command::new("curl").arg(PORTAUDIO_URL).arg("-O"));
+    pub fn download(url: &str, archive: &Path) {
+        let found = download_with_retries(|| {
+            // `--fail` turns an HTTP error into a failed attempt rather than a saved error page.
+            let mut curl = Command::new("curl");
+            curl.arg(url).arg("-o").arg(archive).arg("-L").arg("--fail");
+            curl
+        });
+        if !found {
+            panic!("`curl` was not found on PATH, install it to download PortAudio");
+        }
     }
 
-    pub fn build(out_dir: &Path) {
+    pub fn build(out_dir: &Path, archive: &Path) {
         // untar portaudio sources
-        run(Command::new("tar").arg("xvf").arg(PORTAUDIO_TAR));
+        extract_archive(archive, out_dir);
+        let source_dir = out_dir.join(PORTAUDIO_FOLDER);
 
         // change dir to the portaudio folder
-        err_to_panic(env::set_current_dir(PORTAUDIO_FOLDER));
+        err_to_panic(env::set_current_dir(&source_dir));
 
         // run portaudio autoconf
-        run(Command::new("./configure")
+        let mut configure = Command::new("./configure");
+        configure
             .args(&["--disable-shared", "--enable-static", "--disable-mac-universal"]) // Only build static lib
             .args(&["--prefix", out_dir.to_str().unwrap()]) // Install on the outdir
-            .arg("--with-pic")); // Build position-independent code (required by Rust)
+            .arg("--with-pic"); // Build position-independent code (required by Rust)
+        configure_cross_compilation(&mut configure);
+        run(&mut configure);
 
         // then make
         run(&mut Command::new("make"));
@@ -110,78 +714,258 @@ mod unix_platform {
         // "install" on the outdir
         run(Command::new("make").arg("install"));
 
-        // return to rust-portaudio root
-        err_to_panic(env::set_current_dir(".."));
+        // return to the out dir
+        err_to_panic(env::set_current_dir(out_dir));
+
+        // cleaning portaudio sources, the archive itself stays in the cache
+        run(Command::new("rm").arg("-rf").arg(&source_dir));
+    }
+
+    pub fn include_dir(out_dir: &Path) -> PathBuf {
+        out_dir.join("include")
+    }
+
+    // When cargo is cross compiling, tell autoconf which system we are building for and which
+    // tools to build it with.
+    fn configure_cross_compilation(configure: &mut Command) {
+        let target = env::var("TARGET").unwrap();
+        let host = env::var("HOST").unwrap();
+
+        for tool in &["CC", "AR", "RANLIB"] {
+            if let Some(value) = target_tool(tool, &target, target != host) {
+                configure.env(tool, value);
+            }
+        }
+
+        if target != host {
+            configure.arg(format!("--host={}", autoconf_triple(&target)));
+        }
+    }
+
+    // Looks up a tool the same way the `cc` crate does: `CC_<target>`, then `TARGET_CC` when cross
+    // compiling, then plain `CC`.
+    fn target_tool(tool: &str, target: &str, cross: bool) -> Option<String> {
+        let mut vars = vec![
+            format!("{}_{}", tool, target),
+            format!("{}_{}", tool, target.replace("-", "_")),
+        ];
+        if cross {
+            vars.push(format!("TARGET_{}", tool));
+        }
+        vars.push(tool.to_string());
+
+        for var in &vars {
+            println!("cargo:rerun-if-env-changed={}", var);
+        }
+        vars.iter().filter_map(|var| env::var(var).ok()).next()
+    }
 
-        // cleaning portaudio sources
-        run(Command::new("rm").arg("-rf")
-            .args(&[PORTAUDIO_TAR, PORTAUDIO_FOLDER]));
+    // Translates a Rust target triple into the triple autoconf (and GNU toolchains) expect.
+    fn autoconf_triple(target: &str) -> String {
+        let triple = match target {
+            "arm-unknown-linux-gnueabi" | "armv5te-unknown-linux-gnueabi" => "arm-linux-gnueabi",
+            "arm-unknown-linux-gnueabihf"
+            | "armv7-unknown-linux-gnueabihf"
+            | "thumbv7neon-unknown-linux-gnueabihf" => "arm-linux-gnueabihf",
+            "arm-unknown-linux-musleabihf" | "armv7-unknown-linux-musleabihf" => {
+                "arm-linux-musleabihf"
+            }
+            "riscv64gc-unknown-linux-gnu" => "riscv64-linux-gnu",
+            // e.g. `aarch64-unknown-linux-gnu` -> `aarch64-linux-gnu`
+            _ => return target.replace("-unknown-", "-"),
+        };
+        triple.to_string()
     }
 
     pub fn print_libs(out_dir: &Path) {
         let out_str = out_dir.to_str().unwrap();
-        println!("cargo:rustc-flags=-L native={}/lib -l static=portaudio -l framework=CoreServices -l framework=CoreFoundation -l framework=AudioUnit -l framework=AudioToolbox -l framework=CoreAudio", out_str);
+        println!("cargo:rustc-link-search=native={}/lib", out_str);
+        println!("cargo:rustc-link-lib=static=portaudio");
+
+        // The frameworks PortAudio needs differ between releases and build options (e.g. some
+        // need `Accelerate`), so take them from the pkg-config file that its build installed.
+        let pc_file = out_dir.join("lib/pkgconfig/portaudio-2.0.pc");
+        let frameworks = match fs::read_to_string(&pc_file)
+            .ok()
+            .and_then(|pc| pc_frameworks(&pc))
+        {
+            Some(frameworks) => frameworks,
+            None => {
+                println!(
+                    "cargo:warning=could not read the frameworks to link from `{}`, \
+                     linking the default ones",
+                    pc_file.display()
+                );
+                MACOS_FRAMEWORKS
+                    .iter()
+                    .map(|framework| framework.to_string())
+                    .collect()
+            }
+        };
+        for framework in &frameworks {
+            println!("cargo:rustc-link-lib=framework={}", framework);
+        }
+    }
+
+    // The frameworks given by `-framework <name>` in the `Libs` and `Libs.private` fields of a
+    // pkg-config file, or `None` if there are none.
+    fn pc_frameworks(pc: &str) -> Option<Vec<String>> {
+        let mut frameworks = Vec::new();
+        for line in pc.lines() {
+            let (field, flags) = match line.find(':') {
+                Some(colon) => (line[..colon].trim(), &line[colon + 1..]),
+                None => continue,
+            };
+            if field != "Libs" && field != "Libs.private" {
+                continue;
+            }
+            let mut flags = flags.split_whitespace();
+            while let Some(flag) = flags.next() {
+                if flag == "-framework" {
+                    if let Some(name) = flags.next() {
+                        if !frameworks.iter().any(|framework| framework == name) {
+                            frameworks.push(name.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        if frameworks.is_empty() {
+            None
+        } else {
+            Some(frameworks)
+        }
     }
 }
 
 #[cfg(target_os = "linux")]
 mod platform {
-    use pkg_config;
+    use std::fs;
     use std::process::Command;
     use super::unix_platform;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
+
+    use super::{download_with_retries, err_to_panic};
 
-    use super::{run, err_to_panic};
+    pub use super::unix_platform::{PORTAUDIO_SHA256, PORTAUDIO_TAR, PORTAUDIO_URL};
 
-    pub fn download() {
-        run(Command::new("wget").arg(unix_platform::PORTAUDIO_URL));
+    // Slim images often ship only one of `wget` and `curl`, so use whichever is available.
+    pub fn download(url: &str, archive: &Path) {
+        let wget = || {
+            let mut wget = Command::new("wget");
+            wget.arg(url).arg("-O").arg(archive);
+            wget
+        };
+        if download_with_retries(wget) {
+            return;
+        }
+        let curl = || {
+            let mut curl = Command::new("curl");
+            curl.arg(url).arg("-o").arg(archive).arg("-L").arg("--fail");
+            curl
+        };
+        if download_with_retries(curl) {
+            return;
+        }
+        panic!(
+            "neither `wget` nor `curl` was found on PATH, install one of them to download \
+             PortAudio or install PortAudio itself so that pkg-config can find it"
+        );
+    }
+
+    pub fn build(out_dir: &Path, archive: &Path) {
+        unix_platform::build(out_dir, archive);
     }
 
-    pub fn build(out_dir: &Path) {
-        unix_platform::build(out_dir);
+    pub fn include_dir(out_dir: &Path) -> PathBuf {
+        unix_platform::include_dir(out_dir)
     }
 
+    // The libraries that PortAudio depends on, e.g. `asound`, are taken from the pkg-config file
+    // that its build installed. The file is read directly, as pkg-config itself refuses to run
+    // when cross compiling unless it has been set up for the target.
     pub fn print_libs(out_dir: &Path) {
-        let portaudio_pc_file = out_dir.join("lib/pkgconfig/portaudio-2.0.pc");
-        let portaudio_pc_file = portaudio_pc_file.to_str().unwrap();
+        println!("cargo:rustc-link-search=native={}/lib", out_dir.display());
+        println!("cargo:rustc-link-lib=static=portaudio");
+
+        let pc_file = out_dir.join("lib/pkgconfig/portaudio-2.0.pc");
+        for lib in pc_libs(&err_to_panic(fs::read_to_string(&pc_file))) {
+            println!("cargo:rustc-link-lib={}", lib);
+        }
+    }
 
-        err_to_panic(pkg_config::Config::new().statik(true).find(portaudio_pc_file));
+    // The libraries given by `-l<name>` in the `Libs` and `Libs.private` fields of a pkg-config
+    // file, other than PortAudio itself.
+    fn pc_libs(pc: &str) -> Vec<String> {
+        let mut libs = Vec::new();
+        for line in pc.lines() {
+            let (field, flags) = match line.find(':') {
+                Some(colon) => (line[..colon].trim(), &line[colon + 1..]),
+                None => continue,
+            };
+            if field != "Libs" && field != "Libs.private" {
+                continue;
+            }
+            for flag in flags.split_whitespace() {
+                if flag.starts_with("-l") && flag != "-lportaudio" {
+                    let name = flag[2..].to_string();
+                    if !libs.contains(&name) {
+                        libs.push(name);
+                    }
+                }
+            }
+        }
+        libs
     }
 }
 
 #[cfg(windows)]
 mod platform {
-    use std::path::Path;
-    use std::process::Command;
+    use super::{download_with_retries, err_to_panic, extract_archive};
     use std::env;
-    use super::{run, err_to_panic};
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
 
-    #[cfg(target_arch = "x86_64")]
-    const PORTAUDIO_DOWNLOAD_URL: &'static str = "https://anaconda.org/anaconda/portaudio/19.6.0/download/win-64/portaudio-19.6.0-he774522_4.tar.bz2";
-    #[cfg(target_arch = "x86_64")]
-    const PORTAUDIO_TAR: &'static str = "portaudio-19.6.0-he774522_4.tar.bz2";
+    #[cfg(all(target_arch = "x86_64", not(feature = "asio")))]
+    pub const PORTAUDIO_URL: &'static str = "https://anaconda.org/anaconda/portaudio/19.6.0/download/win-64/portaudio-19.6.0-he774522_4.tar.bz2";
+    #[cfg(all(target_arch = "x86_64", not(feature = "asio")))]
+    pub const PORTAUDIO_TAR: &'static str = "portaudio-19.6.0-he774522_4.tar.bz2";
+    // No checksum has been pinned for the anaconda package yet, so until there is one the build
+    // requires PORTAUDIO_SRC_SHA256 rather than using the package unverified.
+    #[cfg(not(feature = "asio"))]
+    pub const PORTAUDIO_SHA256: Option<&'static str> = None;
+
+    // The anaconda package is built without ASIO, so with the `asio` feature PortAudio is built
+    // from the same source release as on the other platforms.
+    #[cfg(feature = "asio")]
+    pub use super::unix_platform::{PORTAUDIO_SHA256, PORTAUDIO_TAR, PORTAUDIO_URL};
 
     const PORTAUDIO_LIB_DIR: &'static str = "portaudio";
 
-    pub fn download() {
-        run(Command::new("curl").arg(PORTAUDIO_DOWNLOAD_URL).arg("-O").arg("-s").arg("-L"));
+    pub fn download(url: &str, archive: &Path) {
+        let found = download_with_retries(|| {
+            let mut curl = Command::new("curl");
+            curl.arg(url)
+                .arg("-o")
+                .arg(archive)
+                .arg("-s")
+                .arg("-L")
+                .arg("--fail");
+            curl
+        });
+        if !found {
+            panic!("`curl` was not found on PATH, install it to download PortAudio");
+        }
     }
 
-    pub fn build(out_dir: &Path) {
-        // move tar to out dir
-        let current_dir = err_to_panic(std::env::current_dir());
-        let portaudio_location = out_dir.join(PORTAUDIO_TAR);
-
-        err_to_panic(std::fs::rename(
-            current_dir.join(PORTAUDIO_TAR), 
-            &portaudio_location));
-
+    #[cfg(not(feature = "asio"))]
+    pub fn build(out_dir: &Path, archive: &Path) {
         // change dir to the portaudio folder
         err_to_panic(env::set_current_dir(out_dir));
         let current_dir = out_dir;
 
         // untar portaudio sources
-        run(Command::new("tar").arg("-xjf").arg(current_dir.join(PORTAUDIO_TAR).to_str().unwrap()));
+        extract_archive(archive, out_dir);
 
         // move static lib to correct location
         let _ = dbg!(std::fs::create_dir(current_dir.join(PORTAUDIO_LIB_DIR)));
@@ -190,7 +974,99 @@ mod platform {
             &current_dir.join(PORTAUDIO_LIB_DIR).join("portaudio.lib")));
     }
 
+    #[cfg(feature = "asio")]
+    pub fn build(out_dir: &Path, archive: &Path) {
+        let sdk_dir = asio_sdk_dir();
+
+        err_to_panic(env::set_current_dir(out_dir));
+        extract_archive(archive, out_dir);
+        let source_dir = out_dir.join(super::unix_platform::PORTAUDIO_FOLDER);
+        let build_dir = out_dir.join("build");
+
+        // PortAudio's CMake build compiles the SDK's host sources (`asio.cpp`, `asiodrivers.cpp`
+        // and `asiolist.cpp`) into the static library alongside the ASIO host API.
+        super::run(
+            Command::new("cmake")
+                .arg("-S")
+                .arg(&source_dir)
+                .arg("-B")
+                .arg(&build_dir)
+                .arg("-DPA_USE_ASIO=ON")
+                .arg(format!("-DASIOSDK_ROOT_DIR={}", sdk_dir.display()))
+                .arg(format!("-DASIOSDK_PATH_HINT={}", sdk_dir.display()))
+                .arg("-DPA_BUILD_SHARED=OFF")
+                .arg("-DPA_BUILD_STATIC=ON")
+                .arg(format!("-DCMAKE_INSTALL_PREFIX={}", out_dir.display())),
+        );
+        super::run(
+            Command::new("cmake")
+                .arg("--build")
+                .arg(&build_dir)
+                .args(&["--config", "Release", "--target", "install"]),
+        );
+
+        // The static library's name depends on the architecture (e.g. `portaudio_static_x64.lib`),
+        // so pick up whichever one was installed under the name the bindings link against.
+        let lib_dir = out_dir.join(PORTAUDIO_LIB_DIR);
+        let _ = std::fs::create_dir(&lib_dir);
+        let installed = err_to_panic(std::fs::read_dir(out_dir.join("lib")))
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .find(|path| {
+                let name = path.file_name().unwrap().to_string_lossy();
+                name.starts_with("portaudio") && name.ends_with(".lib")
+            });
+        match installed {
+            Some(path) => err_to_panic(std::fs::copy(&path, lib_dir.join("portaudio.lib"))),
+            None => panic!(
+                "the PortAudio build did not install a static library into `{}`",
+                out_dir.join("lib").display()
+            ),
+        };
+
+        err_to_panic(std::fs::remove_dir_all(&source_dir));
+        err_to_panic(std::fs::remove_dir_all(&build_dir));
+    }
+
+    // The Steinberg ASIO SDK may not be redistributed, so it has to be downloaded separately and
+    // pointed to with `ASIO_SDK_DIR`.
+    #[cfg(feature = "asio")]
+    fn asio_sdk_dir() -> PathBuf {
+        println!("cargo:rerun-if-env-changed=ASIO_SDK_DIR");
+        let dir = match env::var_os("ASIO_SDK_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => panic!(
+                "the `asio` feature requires the Steinberg ASIO SDK: download it from \
+                 https://www.steinberg.net/developers/ and set ASIO_SDK_DIR to the \
+                 directory it was unpacked into"
+            ),
+        };
+        if !dir.join("common").join("asio.h").is_file() {
+            panic!(
+                "ASIO_SDK_DIR is set to `{}`, which does not look like the ASIO SDK: expected \
+                 to find `common/asio.h` in it",
+                dir.display()
+            );
+        }
+        dir
+    }
+
     pub fn print_libs(out_dir: &Path) {
         println!("cargo:rustc-link-search={}/{}", out_dir.to_str().unwrap(), PORTAUDIO_LIB_DIR);
+        // The ASIO host API and the SDK's driver list use COM and the registry.
+        if cfg!(feature = "asio") {
+            for lib in &["ole32", "advapi32", "user32"] {
+                println!("cargo:rustc-link-lib={}", lib);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "asio"))]
+    pub fn include_dir(out_dir: &Path) -> PathBuf {
+        out_dir.join("Library").join("include")
+    }
+
+    #[cfg(feature = "asio")]
+    pub fn include_dir(out_dir: &Path) -> PathBuf {
+        out_dir.join("include")
     }
 }
@@ -5,16 +5,32 @@
                        --blacklist-type PaStreamCallbackResult
 */
 
-#[cfg(any(target_os="macos", target_os="linux", target_os="win32", target_os="windows"))]
+#[cfg(all(not(feature = "mock-backend"),
+          any(target_os="macos", target_os="linux", target_os="win32", target_os="windows")))]
 mod c_library {
     #[link(name = "portaudio")]
     extern {}
 }
 
+#[cfg(not(feature = "bindgen"))]
 mod portaudio;
 
+// Bindings generated at build time against the installed `portaudio.h`, see `build.rs`.
+#[cfg(feature = "bindgen")]
+mod portaudio {
+    #![allow(non_upper_case_globals)]
+    #![allow(non_camel_case_types)]
+    #![allow(non_snake_case)]
+
+    include!(concat!(env!("OUT_DIR"), "/portaudio.rs"));
+}
+
 pub use portaudio::*;
 
+// Defines the `Pa_*` symbols itself in place of the PortAudio library.
+#[cfg(feature = "mock-backend")]
+pub mod mock;
+
 pub const PA_NO_DEVICE : PaDeviceIndex = -1;
 
 // Sample format
@@ -68,3 +84,104 @@ pub const PA_ABORT: PaStreamCallbackResult = 2;
 // making a cast necessary in the examples.
 // So it is now a int. Probably not a problem?
 pub type PaStreamCallbackResult = ::std::os::raw::c_int;
+
+// Host-API-specific stream info from `pa_win_wasapi.h`, which is not covered by the generated
+// bindings.
+#[cfg(windows)]
+pub type PaWasapiHostProcessorCallback = Option<
+    unsafe extern "C" fn(
+        input_buffer: *mut ::std::os::raw::c_void,
+        input_frames: ::std::os::raw::c_long,
+        output_buffer: *mut ::std::os::raw::c_void,
+        output_frames: ::std::os::raw::c_long,
+        user_data: *mut ::std::os::raw::c_void,
+    ),
+>;
+
+#[cfg(windows)]
+pub type PaWasapiFlags = ::std::os::raw::c_ulong;
+#[cfg(windows)]
+pub const PA_WIN_WASAPI_EXCLUSIVE: PaWasapiFlags = 1 << 0;
+#[cfg(windows)]
+pub const PA_WIN_WASAPI_REDIRECT_HOST_PROCESSOR: PaWasapiFlags = 1 << 1;
+#[cfg(windows)]
+pub const PA_WIN_WASAPI_USE_CHANNEL_MASK: PaWasapiFlags = 1 << 2;
+#[cfg(windows)]
+pub const PA_WIN_WASAPI_POLLING: PaWasapiFlags = 1 << 3;
+#[cfg(windows)]
+pub const PA_WIN_WASAPI_THREAD_PRIORITY: PaWasapiFlags = 1 << 4;
+#[cfg(windows)]
+pub const PA_WIN_WASAPI_EXPLICIT_SAMPLE_FORMAT: PaWasapiFlags = 1 << 5;
+#[cfg(windows)]
+pub const PA_WIN_WASAPI_AUTO_CONVERT: PaWasapiFlags = 1 << 6;
+
+#[cfg(windows)]
+pub type PaWasapiThreadPriority = ::std::os::raw::c_uint;
+#[cfg(windows)]
+pub type PaWasapiStreamCategory = ::std::os::raw::c_uint;
+#[cfg(windows)]
+pub type PaWasapiStreamOption = ::std::os::raw::c_uint;
+
+#[cfg(windows)]
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+#[allow(non_snake_case)]
+pub struct PaWasapiStreamInfo {
+    pub size: ::std::os::raw::c_ulong,
+    pub hostApiType: PaHostApiTypeId,
+    pub version: ::std::os::raw::c_ulong,
+    pub flags: PaWasapiFlags,
+    pub channelMask: ::std::os::raw::c_ulong,
+    pub hostProcessorOutput: PaWasapiHostProcessorCallback,
+    pub hostProcessorInput: PaWasapiHostProcessorCallback,
+    pub threadPriority: PaWasapiThreadPriority,
+    pub streamCategory: PaWasapiStreamCategory,
+    pub streamOption: PaWasapiStreamOption,
+}
+
+// Host-API-specific stream info from `pa_linux_alsa.h`, which is not covered by the generated
+// bindings.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+#[allow(non_snake_case)]
+pub struct PaAlsaStreamInfo {
+    pub size: ::std::os::raw::c_ulong,
+    pub hostApiType: PaHostApiTypeId,
+    pub version: ::std::os::raw::c_ulong,
+    pub deviceString: *const ::std::os::raw::c_char,
+}
+
+// Functions from `pa_jack.h`, which is not covered by the generated bindings. They are only
+// defined if PortAudio was built with JACK support, see the `jack` feature.
+#[cfg(all(target_os = "linux", feature = "jack"))]
+extern "C" {
+    pub fn PaJack_SetClientName(name: *const ::std::os::raw::c_char) -> PaError;
+    pub fn PaJack_GetClientName(clientName: *mut *const ::std::os::raw::c_char) -> PaError;
+}
+
+// Functions from `pa_mac_core.h`, which is not covered by the generated bindings.
+#[cfg(target_os = "macos")]
+extern "C" {
+    pub fn PaMacCore_GetChannelName(
+        device: ::std::os::raw::c_int,
+        channelIndex: ::std::os::raw::c_int,
+        input: bool,
+    ) -> *const ::std::os::raw::c_char;
+}
+
+// Functions from `pa_asio.h`, which is not covered by the generated bindings. They are only
+// defined if PortAudio was built with ASIO support, see the `asio` feature.
+#[cfg(all(windows, feature = "asio"))]
+extern "C" {
+    pub fn PaAsio_GetInputChannelName(
+        device: PaDeviceIndex,
+        channelIndex: ::std::os::raw::c_int,
+        channelName: *mut *const ::std::os::raw::c_char,
+    ) -> PaError;
+    pub fn PaAsio_GetOutputChannelName(
+        device: PaDeviceIndex,
+        channelIndex: ::std::os::raw::c_int,
+        channelName: *mut *const ::std::os::raw::c_char,
+    ) -> PaError;
+}
@@ -0,0 +1,1173 @@
+//! An in-process stand-in for the PortAudio library, enabled by the `mock-backend` feature.
+//!
+//! The functions below are exported under the same symbol names as the PortAudio C API, so the
+//! declarations in the bindings resolve to them rather than to `libportaudio`. They model a single
+//! host API with a single virtual device, shaped by **configure**, whose output is looped back to
+//! its input after a fixed number of frames.
+//!
+//! Writing to the device output appends frames to the loopback and reading from the device input
+//! takes them off again. Reads are padded with silence when the loopback runs dry, so blocking
+//! streams never wait and return exactly the samples that were written. The frames reported as
+//! available to read are those waiting in the loopback, including the delay.
+//!
+//! Frames are only carried over when the input uses the sample format of the output. The input's
+//! channel `n` receives the output's channel `n`, and any extra input channels are silent.
+//!
+//! Callback streams are driven by a thread that calls the callback once per buffer at the pace of
+//! the configured sample rate. Streams opened with an unspecified number of frames per buffer are
+//! passed a number of frames that varies between callbacks, cycling through `256`, `192` and
+//! `128`, so that callbacks relying on a fixed buffer size are caught. A callback that takes
+//! longer than its buffer lasts is passed `paInputOverflow` and `paOutputUnderflow` the next time
+//! it is called, for the directions of its stream, as PortAudio reports a callback that can't keep
+//! up.
+//!
+//! Further devices can be listed after the virtual device with **add_device**, e.g. to exercise
+//! device selection. They belong to the same host API but can't be streamed through.
+//!
+//! The device is shared by the whole process, so tests that configure it or stream through it
+//! should hold the guard returned by **lock** to avoid interfering with each other.
+
+// The functions and error codes keep the names, and the safety contracts, of the C API.
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(clippy::missing_safety_doc)]
+
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_long, c_ulong, c_void};
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::*;
+
+/// The index of the virtual device and of its host API.
+const MOCK_INDEX: c_int = 0;
+/// The frames per buffer, in turn, of callback streams opened with an unspecified buffer size.
+const UNSPECIFIED_CALLBACK_FRAMES: [usize; 3] = [256, 192, 128];
+/// Reported by `Pa_GetStreamWriteAvailable`, as writes never block.
+const WRITE_AVAILABLE: c_long = 1 << 16;
+/// Version 19.7.0, encoded as by `Pa_GetVersion`.
+const VERSION: c_int = 19 << 16 | 7 << 8;
+
+/// The shape of the virtual device.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MockConfig {
+    /// The maximum number of input channels of the device.
+    pub input_channels: i32,
+    /// The maximum number of output channels of the device.
+    pub output_channels: i32,
+    /// The only sample rate that the device supports, which is also its default.
+    pub sample_rate: f64,
+    /// The number of frames of silence that precede the looped back output on the input.
+    pub loopback_delay: u32,
+}
+
+impl MockConfig {
+    const DEFAULT: MockConfig = MockConfig {
+        input_channels: 2,
+        output_channels: 2,
+        sample_rate: 44_100.0,
+        loopback_delay: 0,
+    };
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        MockConfig::DEFAULT
+    }
+}
+
+/// Reshape the virtual device, empty its loopback and remove any devices added by
+/// **add_device**.
+///
+/// The new configuration is visible to any **DeviceInfo** retrieved afterwards. Streams that are
+/// already open keep the channel counts and sample rate that they were opened with.
+pub fn configure(config: MockConfig) {
+    let mut state = state();
+    state.config = config;
+    state.device_info = device_info(&config);
+    state.extra_devices.clear();
+    state.host_api_info = host_api_info(&config, 0);
+    state.loopback.reset(config.loopback_delay);
+}
+
+/// List another device with the given name and channel counts after those already present,
+/// returning its index.
+///
+/// The device is only enumerated: opening a stream on it, or checking a format against it, fails
+/// with `paInvalidDevice`. **DeviceInfo**s retrieved for it before the next **configure** remain
+/// valid.
+pub fn add_device(name: &str, input_channels: i32, output_channels: i32) -> PaDeviceIndex {
+    let mut state = state();
+    let name = CString::new(name).expect("device names must not contain NUL bytes");
+    let info = Box::new(PaDeviceInfo {
+        name: name.as_ptr(),
+        maxInputChannels: input_channels,
+        maxOutputChannels: output_channels,
+        ..device_info(&state.config)
+    });
+    state.extra_devices.push((name, info));
+    let extra_count = state.extra_devices.len();
+    state.host_api_info = host_api_info(&state.config, extra_count);
+    extra_count as PaDeviceIndex
+}
+
+/// The current configuration of the virtual device.
+pub fn config() -> MockConfig {
+    state().config
+}
+
+/// The number of times that `Pa_CloseStream` has been called, including calls that failed, e.g.
+/// to check that a stream is closed exactly once.
+pub fn close_stream_calls() -> usize {
+    CLOSE_STREAM_CALLS.load(Ordering::SeqCst)
+}
+
+static CLOSE_STREAM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Serialise access to the virtual device, e.g. between tests that run in parallel.
+///
+/// The guard isn't required by any of the PortAudio functions; it only excludes other holders.
+pub fn lock() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+struct State {
+    config: MockConfig,
+    /// The number of `Pa_Initialize` calls that haven't been matched by `Pa_Terminate` yet.
+    init_count: u32,
+    device_info: PaDeviceInfo,
+    /// The devices listed after the virtual device, boxed so that their info stays put.
+    extra_devices: Vec<(CString, Box<PaDeviceInfo>)>,
+    host_api_info: PaHostApiInfo,
+    loopback: Loopback,
+}
+
+// The raw pointers in the info structs only ever point to static strings or to the names held in
+// `extra_devices`.
+unsafe impl Send for State {}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    config: MockConfig::DEFAULT,
+    init_count: 0,
+    device_info: device_info(&MockConfig::DEFAULT),
+    extra_devices: Vec::new(),
+    host_api_info: host_api_info(&MockConfig::DEFAULT, 0),
+    loopback: Loopback {
+        silence: 0,
+        format: 0,
+        channels: 0,
+        data: VecDeque::new(),
+    },
+});
+
+fn state() -> MutexGuard<'static, State> {
+    STATE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn is_initialized() -> bool {
+    state().init_count > 0
+}
+
+const fn device_info(config: &MockConfig) -> PaDeviceInfo {
+    PaDeviceInfo {
+        structVersion: 2,
+        name: b"Mock Device\0".as_ptr() as *const c_char,
+        hostApi: MOCK_INDEX,
+        maxInputChannels: config.input_channels,
+        maxOutputChannels: config.output_channels,
+        defaultLowInputLatency: 0.01,
+        defaultLowOutputLatency: 0.01,
+        defaultHighInputLatency: 0.1,
+        defaultHighOutputLatency: 0.1,
+        defaultSampleRate: config.sample_rate,
+    }
+}
+
+const fn host_api_info(config: &MockConfig, extra_devices: usize) -> PaHostApiInfo {
+    PaHostApiInfo {
+        structVersion: 1,
+        type_: PaHostApiTypeId_paInDevelopment,
+        name: b"Mock\0".as_ptr() as *const c_char,
+        deviceCount: 1 + extra_devices as c_int,
+        defaultInputDevice: if config.input_channels > 0 {
+            MOCK_INDEX
+        } else {
+            PA_NO_DEVICE
+        },
+        defaultOutputDevice: if config.output_channels > 0 {
+            MOCK_INDEX
+        } else {
+            PA_NO_DEVICE
+        },
+    }
+}
+
+/// The frames written to the device output that are yet to be read from its input.
+struct Loopback {
+    /// The number of frames of silence to read before the queued frames.
+    silence: u64,
+    /// The sample format of the queued frames.
+    format: PaSampleFormat,
+    /// The number of channels of the queued frames.
+    channels: usize,
+    /// The queued frames, interleaved.
+    data: VecDeque<u8>,
+}
+
+impl Loopback {
+    fn reset(&mut self, delay: u32) {
+        self.silence = delay as u64;
+        self.data.clear();
+    }
+
+    fn available(&self) -> u64 {
+        let frame_bytes = self.channels * sample_size(self.format).unwrap_or(0);
+        let queued = self.data.len().checked_div(frame_bytes).unwrap_or(0);
+        self.silence + queued as u64
+    }
+
+    /// Queue the interleaved frames in `bytes`, dropping what was queued before if it was written
+    /// in another format.
+    fn write(&mut self, direction: &Direction, bytes: &[u8]) {
+        if direction.format != self.format || direction.channels != self.channels {
+            self.data.clear();
+            self.format = direction.format;
+            self.channels = direction.channels;
+        }
+        self.data.extend(bytes);
+    }
+
+    /// Take `frames` interleaved frames in the layout of `direction`.
+    fn read(&mut self, direction: &Direction, frames: usize) -> Vec<u8> {
+        let size = direction.size;
+        let mut bytes = vec![silence(direction.format); frames * direction.frame_bytes()];
+        let queued_frame_bytes = self.channels * sample_size(self.format).unwrap_or(0);
+        let same_format = direction.format == self.format;
+        for frame in 0..frames {
+            if self.silence > 0 {
+                self.silence -= 1;
+                continue;
+            }
+            if queued_frame_bytes == 0 || self.data.len() < queued_frame_bytes {
+                break;
+            }
+            let queued: Vec<u8> = self.data.drain(..queued_frame_bytes).collect();
+            if same_format {
+                for channel in 0..::std::cmp::min(direction.channels, self.channels) {
+                    let dst = (frame * direction.channels + channel) * size;
+                    bytes[dst..dst + size].copy_from_slice(&queued[channel * size..][..size]);
+                }
+            }
+        }
+        bytes
+    }
+}
+
+fn sample_size(format: PaSampleFormat) -> Option<usize> {
+    match format & !PA_NON_INTERLEAVED {
+        PA_FLOAT_32 | PA_INT_32 => Some(4),
+        PA_INT_24 => Some(3),
+        PA_INT_16 => Some(2),
+        PA_INT_8 | PA_UINT_8 => Some(1),
+        _ => None,
+    }
+}
+
+/// The byte that makes up a silent sample of the given format.
+fn silence(format: PaSampleFormat) -> u8 {
+    if format == PA_UINT_8 {
+        0x80
+    } else {
+        0
+    }
+}
+
+/// The layout of the samples in one direction of a stream.
+#[derive(Copy, Clone, Debug)]
+struct Direction {
+    channels: usize,
+    /// The sample format, without the non-interleaved flag.
+    format: PaSampleFormat,
+    interleaved: bool,
+    /// The size of a sample in bytes.
+    size: usize,
+}
+
+impl Direction {
+    fn frame_bytes(&self) -> usize {
+        self.channels * self.size
+    }
+
+    /// Copy `frames` frames out of a user buffer into interleaved bytes.
+    unsafe fn gather(&self, buffer: *const c_void, frames: usize) -> Vec<u8> {
+        if self.interleaved {
+            return slice::from_raw_parts(buffer as *const u8, frames * self.frame_bytes())
+                .to_vec();
+        }
+        let planes = buffer as *const *const u8;
+        let mut bytes = vec![0; frames * self.frame_bytes()];
+        for channel in 0..self.channels {
+            let plane = *planes.add(channel);
+            for frame in 0..frames {
+                let dst = (frame * self.channels + channel) * self.size;
+                ptr::copy_nonoverlapping(
+                    plane.add(frame * self.size),
+                    bytes[dst..].as_mut_ptr(),
+                    self.size,
+                );
+            }
+        }
+        bytes
+    }
+
+    /// Copy interleaved bytes into a user buffer of `frames` frames.
+    unsafe fn scatter(&self, bytes: &[u8], buffer: *mut c_void, frames: usize) {
+        if self.interleaved {
+            ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                buffer as *mut u8,
+                frames * self.frame_bytes(),
+            );
+            return;
+        }
+        let planes = buffer as *const *mut u8;
+        for channel in 0..self.channels {
+            let plane = *planes.add(channel);
+            for frame in 0..frames {
+                let src = (frame * self.channels + channel) * self.size;
+                ptr::copy_nonoverlapping(
+                    bytes[src..].as_ptr(),
+                    plane.add(frame * self.size),
+                    self.size,
+                );
+            }
+        }
+    }
+}
+
+/// A buffer laid out as it is passed to a stream callback.
+struct CallbackBuffer {
+    direction: Direction,
+    frames: usize,
+    interleaved: Vec<u8>,
+    planes: Vec<Vec<u8>>,
+    pointers: Vec<*mut u8>,
+}
+
+impl CallbackBuffer {
+    fn new(direction: Direction, frames: usize) -> Self {
+        let fill = silence(direction.format);
+        let mut buffer = CallbackBuffer {
+            direction: direction,
+            frames: frames,
+            interleaved: Vec::new(),
+            planes: Vec::new(),
+            pointers: Vec::new(),
+        };
+        if direction.interleaved {
+            buffer.interleaved = vec![fill; frames * direction.frame_bytes()];
+        } else {
+            buffer.planes = (0..direction.channels)
+                .map(|_| vec![fill; frames * direction.size])
+                .collect();
+            buffer.pointers = buffer
+                .planes
+                .iter_mut()
+                .map(|plane| plane.as_mut_ptr())
+                .collect();
+        }
+        buffer
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut c_void {
+        if self.direction.interleaved {
+            self.interleaved.as_mut_ptr() as *mut c_void
+        } else {
+            self.pointers.as_mut_ptr() as *mut c_void
+        }
+    }
+
+    fn fill(&mut self, bytes: &[u8]) {
+        let (direction, frames) = (self.direction, self.frames);
+        unsafe { direction.scatter(bytes, self.as_mut_ptr(), frames) }
+    }
+
+    fn interleaved_bytes(&mut self) -> Vec<u8> {
+        let (direction, frames) = (self.direction, self.frames);
+        unsafe { direction.gather(self.as_mut_ptr(), frames) }
+    }
+}
+
+/// The state of a stream that is shared with the thread driving its callback.
+struct Shared {
+    active: AtomicBool,
+    stop: AtomicBool,
+    /// The number of frames that have passed through the stream.
+    frames: AtomicUsize,
+}
+
+struct MockStream {
+    input: Option<Direction>,
+    output: Option<Direction>,
+    sample_rate: f64,
+    frames_per_buffer: c_ulong,
+    callback: PaStreamCallback,
+    user_data: *mut c_void,
+    finished_callback: PaStreamFinishedCallback,
+    info: PaStreamInfo,
+    is_started: bool,
+    shared: Arc<Shared>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+/// Everything the thread driving a callback stream needs, moved onto that thread.
+struct CallbackRunner {
+    input: Option<Direction>,
+    output: Option<Direction>,
+    sample_rate: f64,
+    /// The requested frames per buffer, or `0` if unspecified.
+    frames_per_buffer: usize,
+    callback: unsafe extern "C" fn(
+        *const c_void,
+        *mut c_void,
+        c_ulong,
+        *const PaStreamCallbackTimeInfo,
+        PaStreamCallbackFlags,
+        *mut c_void,
+    ) -> c_int,
+    user_data: *mut c_void,
+    finished_callback: PaStreamFinishedCallback,
+    shared: Arc<Shared>,
+}
+
+// The user data is handed to the callback just as PortAudio hands it to its audio thread.
+unsafe impl Send for CallbackRunner {}
+
+impl CallbackRunner {
+    fn run(self) {
+        let mut input: Option<CallbackBuffer> = None;
+        let mut output: Option<CallbackBuffer> = None;
+        let mut buffer_frames = 0;
+        let mut callback_count = 0;
+        let mut status_flags = 0;
+        while !self.shared.stop.load(Ordering::SeqCst) {
+            let frames = match self.frames_per_buffer {
+                0 => {
+                    UNSPECIFIED_CALLBACK_FRAMES[callback_count % UNSPECIFIED_CALLBACK_FRAMES.len()]
+                }
+                n => n,
+            };
+            callback_count += 1;
+            if frames != buffer_frames {
+                input = self
+                    .input
+                    .map(|direction| CallbackBuffer::new(direction, frames));
+                output = self
+                    .output
+                    .map(|direction| CallbackBuffer::new(direction, frames));
+                buffer_frames = frames;
+            }
+            let time = self.shared.frames.load(Ordering::SeqCst) as f64 / self.sample_rate;
+            let input_ptr = match input {
+                Some(ref mut buffer) => {
+                    let bytes = state().loopback.read(&buffer.direction, frames);
+                    buffer.fill(&bytes);
+                    buffer.as_mut_ptr() as *const c_void
+                }
+                None => ptr::null(),
+            };
+            let output_ptr = match output {
+                Some(ref mut buffer) => {
+                    let bytes = vec![
+                        silence(buffer.direction.format);
+                        frames * buffer.direction.frame_bytes()
+                    ];
+                    buffer.fill(&bytes);
+                    buffer.as_mut_ptr()
+                }
+                None => ptr::null_mut(),
+            };
+            let time_info = PaStreamCallbackTimeInfo {
+                inputBufferAdcTime: time,
+                currentTime: time,
+                outputBufferDacTime: time,
+            };
+            let started = Instant::now();
+            let result = unsafe {
+                (self.callback)(
+                    input_ptr,
+                    output_ptr,
+                    frames as c_ulong,
+                    &time_info,
+                    status_flags,
+                    self.user_data,
+                )
+            };
+            let period = Duration::from_secs_f64(frames as f64 / self.sample_rate);
+            status_flags = 0;
+            if started.elapsed() > period {
+                if input.is_some() {
+                    status_flags |= INPUT_OVERFLOW;
+                }
+                if output.is_some() {
+                    status_flags |= OUTPUT_UNDERFLOW;
+                }
+            }
+            if result == PA_ABORT {
+                break;
+            }
+            if let Some(ref mut buffer) = output {
+                let bytes = buffer.interleaved_bytes();
+                state().loopback.write(&buffer.direction, &bytes);
+            }
+            self.shared.frames.fetch_add(frames, Ordering::SeqCst);
+            if result != PA_CONTINUE {
+                break;
+            }
+            thread::sleep(period);
+        }
+        self.shared.active.store(false, Ordering::SeqCst);
+        if let Some(finished) = self.finished_callback {
+            unsafe { finished(self.user_data) }
+        }
+    }
+}
+
+unsafe fn mock_stream<'a>(stream: *mut PaStream) -> Option<&'a mut MockStream> {
+    (stream as *mut MockStream).as_mut()
+}
+
+/// Check the parameters of one direction of a stream against the virtual device.
+unsafe fn validate(
+    params: *const PaStreamParameters,
+    max_channels: c_int,
+    sample_rate: f64,
+) -> Result<Option<Direction>, PaError> {
+    let params = match params.as_ref() {
+        Some(params) => params,
+        None => return Ok(None),
+    };
+    if params.device != MOCK_INDEX {
+        return Err(PaErrorCode_paInvalidDevice);
+    }
+    if !params.hostApiSpecificStreamInfo.is_null() {
+        return Err(PaErrorCode_paIncompatibleHostApiSpecificStreamInfo);
+    }
+    if params.channelCount <= 0 || params.channelCount > max_channels {
+        return Err(PaErrorCode_paInvalidChannelCount);
+    }
+    let size = match sample_size(params.sampleFormat) {
+        Some(size) => size,
+        None => return Err(PaErrorCode_paSampleFormatNotSupported),
+    };
+    if sample_rate != state().config.sample_rate {
+        return Err(PaErrorCode_paInvalidSampleRate);
+    }
+    Ok(Some(Direction {
+        channels: params.channelCount as usize,
+        format: params.sampleFormat & !PA_NON_INTERLEAVED,
+        interleaved: params.sampleFormat & PA_NON_INTERLEAVED == 0,
+        size: size,
+    }))
+}
+
+unsafe fn validate_pair(
+    input: *const PaStreamParameters,
+    output: *const PaStreamParameters,
+    sample_rate: f64,
+) -> Result<(Option<Direction>, Option<Direction>), PaError> {
+    if !is_initialized() {
+        return Err(PaErrorCode_paNotInitialized);
+    }
+    if input.is_null() && output.is_null() {
+        return Err(PaErrorCode_paInvalidDevice);
+    }
+    let config = state().config;
+    let input = validate(input, config.input_channels, sample_rate)?;
+    let output = validate(output, config.output_channels, sample_rate)?;
+    Ok((input, output))
+}
+
+struct VersionInfo(PaVersionInfo);
+struct HostErrorInfo(PaHostErrorInfo);
+// Both only point to static strings.
+unsafe impl Sync for VersionInfo {}
+unsafe impl Sync for HostErrorInfo {}
+
+static VERSION_INFO: VersionInfo = VersionInfo(PaVersionInfo {
+    versionMajor: 19,
+    versionMinor: 7,
+    versionSubMinor: 0,
+    versionControlRevision: b"mock\0".as_ptr() as *const c_char,
+    versionText: b"PortAudio V19.7.0-mock\0".as_ptr() as *const c_char,
+});
+
+static HOST_ERROR_INFO: HostErrorInfo = HostErrorInfo(PaHostErrorInfo {
+    hostApiType: PaHostApiTypeId_paInDevelopment,
+    errorCode: 0,
+    errorText: b"\0".as_ptr() as *const c_char,
+});
+
+#[no_mangle]
+pub extern "C" fn Pa_GetVersion() -> c_int {
+    VERSION
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetVersionText() -> *const c_char {
+    VERSION_INFO.0.versionText
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetVersionInfo() -> *const PaVersionInfo {
+    &VERSION_INFO.0
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetErrorText(error_code: PaError) -> *const c_char {
+    let text: &'static [u8] = match error_code {
+        PaErrorCode_paNoError => b"Success\0",
+        PaErrorCode_paNotInitialized => b"PortAudio not initialized\0",
+        PaErrorCode_paUnanticipatedHostError => b"Unanticipated host error\0",
+        PaErrorCode_paInvalidChannelCount => b"Invalid number of channels\0",
+        PaErrorCode_paInvalidSampleRate => b"Invalid sample rate\0",
+        PaErrorCode_paInvalidDevice => b"Invalid device\0",
+        PaErrorCode_paInvalidFlag => b"Invalid flag\0",
+        PaErrorCode_paSampleFormatNotSupported => b"Sample format not supported\0",
+        PaErrorCode_paBadIODeviceCombination => b"Illegal combination of I/O devices\0",
+        PaErrorCode_paInsufficientMemory => b"Insufficient memory\0",
+        PaErrorCode_paBufferTooBig => b"Buffer too big\0",
+        PaErrorCode_paBufferTooSmall => b"Buffer too small\0",
+        PaErrorCode_paNullCallback => b"No callback routine specified\0",
+        PaErrorCode_paBadStreamPtr => b"Invalid stream pointer\0",
+        PaErrorCode_paTimedOut => b"Wait timed out\0",
+        PaErrorCode_paInternalError => b"Internal PortAudio error\0",
+        PaErrorCode_paDeviceUnavailable => b"Device unavailable\0",
+        PaErrorCode_paIncompatibleHostApiSpecificStreamInfo => {
+            b"Incompatible host API specific stream info\0"
+        }
+        PaErrorCode_paStreamIsStopped => b"Stream is stopped\0",
+        PaErrorCode_paStreamIsNotStopped => b"Stream is not stopped\0",
+        PaErrorCode_paInputOverflowed => b"Input overflowed\0",
+        PaErrorCode_paOutputUnderflowed => b"Output underflowed\0",
+        PaErrorCode_paHostApiNotFound => b"Host API not found\0",
+        PaErrorCode_paInvalidHostApi => b"Invalid host API\0",
+        PaErrorCode_paCanNotReadFromACallbackStream => b"Can't read from a callback stream\0",
+        PaErrorCode_paCanNotWriteToACallbackStream => b"Can't write to a callback stream\0",
+        PaErrorCode_paCanNotReadFromAnOutputOnlyStream => {
+            b"Can't read from an output only stream\0"
+        }
+        PaErrorCode_paCanNotWriteToAnInputOnlyStream => b"Can't write to an input only stream\0",
+        PaErrorCode_paIncompatibleStreamHostApi => b"Incompatible stream host API\0",
+        PaErrorCode_paBadBufferPtr => b"Bad buffer pointer\0",
+        _ => b"Invalid error code\0",
+    };
+    text.as_ptr() as *const c_char
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_Initialize() -> PaError {
+    let mut state = state();
+    if state.init_count == 0 {
+        let delay = state.config.loopback_delay;
+        state.loopback.reset(delay);
+    }
+    state.init_count += 1;
+    PaErrorCode_paNoError
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_Terminate() -> PaError {
+    let mut state = state();
+    if state.init_count == 0 {
+        return PaErrorCode_paNotInitialized;
+    }
+    state.init_count -= 1;
+    PaErrorCode_paNoError
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetHostApiCount() -> PaHostApiIndex {
+    if !is_initialized() {
+        return PaErrorCode_paNotInitialized;
+    }
+    1
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetDefaultHostApi() -> PaHostApiIndex {
+    if !is_initialized() {
+        return PaErrorCode_paNotInitialized;
+    }
+    MOCK_INDEX
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetHostApiInfo(host_api: PaHostApiIndex) -> *const PaHostApiInfo {
+    let state = state();
+    if state.init_count == 0 || host_api != MOCK_INDEX {
+        return ptr::null();
+    }
+    &state.host_api_info
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_HostApiTypeIdToHostApiIndex(type_id: PaHostApiTypeId) -> PaHostApiIndex {
+    if !is_initialized() {
+        return PaErrorCode_paNotInitialized;
+    }
+    if type_id != PaHostApiTypeId_paInDevelopment {
+        return PaErrorCode_paHostApiNotFound;
+    }
+    MOCK_INDEX
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_HostApiDeviceIndexToDeviceIndex(
+    host_api: PaHostApiIndex,
+    host_api_device_index: c_int,
+) -> PaDeviceIndex {
+    if !is_initialized() {
+        return PaErrorCode_paNotInitialized;
+    }
+    if host_api != MOCK_INDEX {
+        return PaErrorCode_paInvalidHostApi;
+    }
+    if host_api_device_index < 0 || host_api_device_index >= state().host_api_info.deviceCount {
+        return PaErrorCode_paInvalidDevice;
+    }
+    host_api_device_index
+}
+
+// There is no JACK host API, so a client name is accepted but never used.
+#[cfg(all(target_os = "linux", feature = "jack"))]
+#[no_mangle]
+pub extern "C" fn PaJack_SetClientName(_name: *const c_char) -> PaError {
+    PaErrorCode_paNoError
+}
+
+#[cfg(all(target_os = "linux", feature = "jack"))]
+#[no_mangle]
+pub extern "C" fn PaJack_GetClientName(_client_name: *mut *const c_char) -> PaError {
+    if !is_initialized() {
+        return PaErrorCode_paNotInitialized;
+    }
+    PaErrorCode_paHostApiNotFound
+}
+
+// There is no CoreAudio host API, so no channel has a name.
+#[cfg(target_os = "macos")]
+#[no_mangle]
+pub extern "C" fn PaMacCore_GetChannelName(
+    _device: c_int,
+    _channel_index: c_int,
+    _input: bool,
+) -> *const c_char {
+    ptr::null()
+}
+
+// Nor is there an ASIO host API to which the virtual device could belong.
+#[cfg(all(windows, feature = "asio"))]
+#[no_mangle]
+pub extern "C" fn PaAsio_GetInputChannelName(
+    _device: PaDeviceIndex,
+    _channel_index: c_int,
+    _channel_name: *mut *const c_char,
+) -> PaError {
+    PaErrorCode_paInvalidDevice
+}
+
+#[cfg(all(windows, feature = "asio"))]
+#[no_mangle]
+pub extern "C" fn PaAsio_GetOutputChannelName(
+    _device: PaDeviceIndex,
+    _channel_index: c_int,
+    _channel_name: *mut *const c_char,
+) -> PaError {
+    PaErrorCode_paInvalidDevice
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetLastHostErrorInfo() -> *const PaHostErrorInfo {
+    &HOST_ERROR_INFO.0
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetDeviceCount() -> PaDeviceIndex {
+    if !is_initialized() {
+        return PaErrorCode_paNotInitialized;
+    }
+    state().host_api_info.deviceCount
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetDefaultInputDevice() -> PaDeviceIndex {
+    let state = state();
+    if state.init_count == 0 {
+        return PA_NO_DEVICE;
+    }
+    state.host_api_info.defaultInputDevice
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetDefaultOutputDevice() -> PaDeviceIndex {
+    let state = state();
+    if state.init_count == 0 {
+        return PA_NO_DEVICE;
+    }
+    state.host_api_info.defaultOutputDevice
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetDeviceInfo(device: PaDeviceIndex) -> *const PaDeviceInfo {
+    let state = state();
+    if state.init_count == 0 {
+        return ptr::null();
+    }
+    if device == MOCK_INDEX {
+        return &state.device_info;
+    }
+    match state.extra_devices.get((device - 1) as usize) {
+        Some(&(_, ref info)) if device > MOCK_INDEX => &**info,
+        _ => ptr::null(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_IsFormatSupported(
+    input_parameters: *const PaStreamParameters,
+    output_parameters: *const PaStreamParameters,
+    sample_rate: f64,
+) -> PaError {
+    match validate_pair(input_parameters, output_parameters, sample_rate) {
+        Ok(_) => PaErrorCode_paNoError,
+        Err(err) => err,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_OpenStream(
+    stream: *mut *mut PaStream,
+    input_parameters: *const PaStreamParameters,
+    output_parameters: *const PaStreamParameters,
+    sample_rate: f64,
+    frames_per_buffer: c_ulong,
+    stream_flags: PaStreamFlags,
+    stream_callback: PaStreamCallback,
+    user_data: *mut c_void,
+) -> PaError {
+    if stream.is_null() {
+        return PaErrorCode_paBadStreamPtr;
+    }
+    let (input, output) = match validate_pair(input_parameters, output_parameters, sample_rate) {
+        Ok(directions) => directions,
+        Err(err) => return err,
+    };
+    let known_flags = PA_CLIP_OFF
+        | PA_DITHER_OFF
+        | PA_NEVER_DROP_INPUT
+        | PA_PRIME_OUTPUT_BUFFERS_USING_STREAM_CALLBACK;
+    if stream_flags & !known_flags != 0 {
+        return PaErrorCode_paInvalidFlag;
+    }
+    let latency =
+        |params: *const PaStreamParameters| params.as_ref().map_or(0.0, |p| p.suggestedLatency);
+    let mock = MockStream {
+        input: input,
+        output: output,
+        sample_rate: sample_rate,
+        frames_per_buffer: frames_per_buffer,
+        callback: stream_callback,
+        user_data: user_data,
+        finished_callback: None,
+        info: PaStreamInfo {
+            structVersion: 1,
+            inputLatency: latency(input_parameters),
+            outputLatency: latency(output_parameters),
+            sampleRate: sample_rate,
+        },
+        is_started: false,
+        shared: Arc::new(Shared {
+            active: AtomicBool::new(false),
+            stop: AtomicBool::new(false),
+            frames: AtomicUsize::new(0),
+        }),
+        thread: None,
+    };
+    *stream = Box::into_raw(Box::new(mock)) as *mut PaStream;
+    PaErrorCode_paNoError
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_OpenDefaultStream(
+    stream: *mut *mut PaStream,
+    num_input_channels: c_int,
+    num_output_channels: c_int,
+    sample_format: PaSampleFormat,
+    sample_rate: f64,
+    frames_per_buffer: c_ulong,
+    stream_callback: PaStreamCallback,
+    user_data: *mut c_void,
+) -> PaError {
+    let params = |channels: c_int| PaStreamParameters {
+        device: MOCK_INDEX,
+        channelCount: channels,
+        sampleFormat: sample_format,
+        suggestedLatency: 0.01,
+        hostApiSpecificStreamInfo: ptr::null_mut(),
+    };
+    let (input, output) = (params(num_input_channels), params(num_output_channels));
+    let input_ptr = if num_input_channels > 0 {
+        &input as *const _
+    } else {
+        ptr::null()
+    };
+    let output_ptr = if num_output_channels > 0 {
+        &output as *const _
+    } else {
+        ptr::null()
+    };
+    Pa_OpenStream(
+        stream,
+        input_ptr,
+        output_ptr,
+        sample_rate,
+        frames_per_buffer,
+        PA_NO_FLAG,
+        stream_callback,
+        user_data,
+    )
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_CloseStream(stream: *mut PaStream) -> PaError {
+    CLOSE_STREAM_CALLS.fetch_add(1, Ordering::SeqCst);
+    if mock_stream(stream).is_none() {
+        return PaErrorCode_paBadStreamPtr;
+    }
+    let mut mock = Box::from_raw(stream as *mut MockStream);
+    if mock.is_started {
+        finish(&mut mock);
+    }
+    PaErrorCode_paNoError
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_SetStreamFinishedCallback(
+    stream: *mut PaStream,
+    stream_finished_callback: PaStreamFinishedCallback,
+) -> PaError {
+    let mock = match mock_stream(stream) {
+        Some(mock) => mock,
+        None => return PaErrorCode_paBadStreamPtr,
+    };
+    if mock.is_started {
+        return PaErrorCode_paStreamIsNotStopped;
+    }
+    mock.finished_callback = stream_finished_callback;
+    PaErrorCode_paNoError
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_StartStream(stream: *mut PaStream) -> PaError {
+    let mock = match mock_stream(stream) {
+        Some(mock) => mock,
+        None => return PaErrorCode_paBadStreamPtr,
+    };
+    if mock.is_started {
+        return PaErrorCode_paStreamIsNotStopped;
+    }
+    mock.is_started = true;
+    mock.shared.stop.store(false, Ordering::SeqCst);
+    mock.shared.active.store(true, Ordering::SeqCst);
+    if let Some(callback) = mock.callback {
+        let runner = CallbackRunner {
+            input: mock.input,
+            output: mock.output,
+            sample_rate: mock.sample_rate,
+            frames_per_buffer: mock.frames_per_buffer as usize,
+            callback: callback,
+            user_data: mock.user_data,
+            finished_callback: mock.finished_callback,
+            shared: mock.shared.clone(),
+        };
+        mock.thread = Some(thread::spawn(move || runner.run()));
+    }
+    PaErrorCode_paNoError
+}
+
+/// Stop the stream, waiting for its callback thread to finish if it has one.
+fn finish(mock: &mut MockStream) {
+    mock.shared.stop.store(true, Ordering::SeqCst);
+    match mock.thread.take() {
+        Some(thread) => {
+            let _ = thread.join();
+        }
+        None => {
+            mock.shared.active.store(false, Ordering::SeqCst);
+            if let Some(finished) = mock.finished_callback {
+                unsafe { finished(mock.user_data) }
+            }
+        }
+    }
+    mock.is_started = false;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_StopStream(stream: *mut PaStream) -> PaError {
+    let mock = match mock_stream(stream) {
+        Some(mock) => mock,
+        None => return PaErrorCode_paBadStreamPtr,
+    };
+    if !mock.is_started {
+        return PaErrorCode_paStreamIsStopped;
+    }
+    finish(mock);
+    PaErrorCode_paNoError
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_AbortStream(stream: *mut PaStream) -> PaError {
+    Pa_StopStream(stream)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_IsStreamStopped(stream: *mut PaStream) -> PaError {
+    match mock_stream(stream) {
+        Some(mock) => !mock.is_started as PaError,
+        None => PaErrorCode_paBadStreamPtr,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_IsStreamActive(stream: *mut PaStream) -> PaError {
+    match mock_stream(stream) {
+        Some(mock) => mock.shared.active.load(Ordering::SeqCst) as PaError,
+        None => PaErrorCode_paBadStreamPtr,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_GetStreamInfo(stream: *mut PaStream) -> *const PaStreamInfo {
+    match mock_stream(stream) {
+        Some(mock) => &mock.info,
+        None => ptr::null(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_GetStreamTime(stream: *mut PaStream) -> PaTime {
+    match mock_stream(stream) {
+        Some(mock) => mock.shared.frames.load(Ordering::SeqCst) as f64 / mock.sample_rate,
+        None => 0.0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_GetStreamCpuLoad(_stream: *mut PaStream) -> f64 {
+    0.0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_ReadStream(
+    stream: *mut PaStream,
+    buffer: *mut c_void,
+    frames: c_ulong,
+) -> PaError {
+    let mock = match mock_stream(stream) {
+        Some(mock) => mock,
+        None => return PaErrorCode_paBadStreamPtr,
+    };
+    if mock.callback.is_some() {
+        return PaErrorCode_paCanNotReadFromACallbackStream;
+    }
+    let direction = match mock.input {
+        Some(direction) => direction,
+        None => return PaErrorCode_paCanNotReadFromAnOutputOnlyStream,
+    };
+    if buffer.is_null() {
+        return PaErrorCode_paBadBufferPtr;
+    }
+    if !mock.is_started {
+        return PaErrorCode_paStreamIsStopped;
+    }
+    let bytes = state().loopback.read(&direction, frames as usize);
+    direction.scatter(&bytes, buffer, frames as usize);
+    mock.shared
+        .frames
+        .fetch_add(frames as usize, Ordering::SeqCst);
+    PaErrorCode_paNoError
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_WriteStream(
+    stream: *mut PaStream,
+    buffer: *const c_void,
+    frames: c_ulong,
+) -> PaError {
+    let mock = match mock_stream(stream) {
+        Some(mock) => mock,
+        None => return PaErrorCode_paBadStreamPtr,
+    };
+    if mock.callback.is_some() {
+        return PaErrorCode_paCanNotWriteToACallbackStream;
+    }
+    let direction = match mock.output {
+        Some(direction) => direction,
+        None => return PaErrorCode_paCanNotWriteToAnInputOnlyStream,
+    };
+    if buffer.is_null() {
+        return PaErrorCode_paBadBufferPtr;
+    }
+    if !mock.is_started {
+        return PaErrorCode_paStreamIsStopped;
+    }
+    let bytes = direction.gather(buffer, frames as usize);
+    state().loopback.write(&direction, &bytes);
+    // The stream time of a duplex stream follows its input.
+    if mock.input.is_none() {
+        mock.shared
+            .frames
+            .fetch_add(frames as usize, Ordering::SeqCst);
+    }
+    PaErrorCode_paNoError
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_GetStreamReadAvailable(stream: *mut PaStream) -> c_long {
+    match mock_stream(stream) {
+        Some(ref mock) if mock.input.is_some() => state().loopback.available() as c_long,
+        Some(_) => PaErrorCode_paCanNotReadFromAnOutputOnlyStream as c_long,
+        None => PaErrorCode_paBadStreamPtr as c_long,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Pa_GetStreamWriteAvailable(stream: *mut PaStream) -> c_long {
+    match mock_stream(stream) {
+        Some(ref mock) if mock.output.is_some() => WRITE_AVAILABLE,
+        Some(_) => PaErrorCode_paCanNotWriteToAnInputOnlyStream as c_long,
+        None => PaErrorCode_paBadStreamPtr as c_long,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_GetSampleSize(format: PaSampleFormat) -> PaError {
+    match sample_size(format) {
+        Some(size) => size as PaError,
+        None => PaErrorCode_paSampleFormatNotSupported,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Pa_Sleep(msec: c_long) {
+    thread::sleep(Duration::from_millis(msec.max(0) as u64));
+}
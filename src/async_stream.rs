@@ -0,0 +1,219 @@
+//!
+//! Wrappers that bridge blocking **Stream**s to async code via `tokio` channels.
+//!
+//! PortAudio's blocking reads and writes can't be awaited, so each wrapper moves its **Stream**
+//! onto a dedicated thread which services it in a loop and exchanges blocks of interleaved
+//! samples with the async side over a bounded `tokio::sync::mpsc` channel.
+//!
+//! Only available with the `tokio` feature.
+//!
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::mpsc;
+
+use super::error::Error;
+use super::stream::{InputSettings, OutputSettings};
+use super::{PortAudio, Sample};
+
+/// An **Input** stream whose audio is received asynchronously, one block at a time.
+///
+/// A dedicated thread reads blocks of `frames_per_buffer` frames from a blocking **Stream** and
+/// sends them over a channel that holds at most `capacity` blocks.
+///
+/// # Backpressure
+///
+/// If the consumer lags and the channel fills up, the reading thread waits for room rather than
+/// dropping blocks. While it waits, PortAudio keeps recording into its own buffer; once that
+/// overflows, the next read reports **Error::InputOverflowed** and the block it would have
+/// returned is discarded, after which reading resumes. A larger `capacity` tolerates longer
+/// stalls at the cost of more latency and memory.
+pub struct AsyncInputStream<I> {
+    receiver: mpsc::Receiver<Vec<I>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<Result<(), Error>>>,
+}
+
+/// An **Output** stream that is fed asynchronously, one block at a time.
+///
+/// Blocks of interleaved samples are sent over a channel that holds at most `capacity` blocks,
+/// from which a dedicated thread writes them to a blocking **Stream**. Blocks may be of any
+/// length, although any trailing samples that don't make up a whole frame are ignored.
+///
+/// # Backpressure
+///
+/// **AsyncOutputStream::send_block** waits while the channel is full, so a producer that runs
+/// ahead of the device is slowed down to the device's rate. If the producer lags and the channel
+/// runs dry, the writing thread waits for the next block and the device underflows, typically
+/// playing silence until audio is available again.
+pub struct AsyncOutputStream<O> {
+    sender: Option<mpsc::Sender<Vec<O>>>,
+    thread: Option<thread::JoinHandle<Result<(), Error>>>,
+}
+
+impl<I> AsyncInputStream<I>
+where
+    I: Sample + Copy + Send + 'static,
+{
+    /// Open and start a blocking **Input** stream with the given `settings`, reading it on a
+    /// dedicated thread which buffers at most `capacity` blocks.
+    ///
+    /// Returns **Error::BufferTooSmall** if `settings.frames_per_buffer` is unspecified, as it
+    /// determines the size of each block.
+    pub fn open(
+        port_audio: &PortAudio,
+        settings: InputSettings<I>,
+        capacity: usize,
+    ) -> Result<Self, Error> {
+        let frames = settings.frames_per_buffer;
+        if frames == 0 {
+            return Err(Error::BufferTooSmall);
+        }
+        let mut stream = port_audio.open_blocking_stream(settings)?;
+        stream.start()?;
+
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                let block = match stream.read(frames) {
+                    Ok(samples) => samples.to_vec(),
+                    Err(Error::InputOverflowed) => continue,
+                    Err(err) => return Err(err),
+                };
+                // The receiver has been closed, so no-one is listening any more.
+                if sender.blocking_send(block).is_err() {
+                    break;
+                }
+            }
+            stream.stop()?;
+            stream.close()
+        });
+
+        Ok(AsyncInputStream {
+            receiver: receiver,
+            stop: stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Receive the next block of interleaved samples.
+    ///
+    /// This is an `async fn` in all but name. The future resolves to `None` once the reading
+    /// thread has stopped, either because of an error (which is returned by
+    /// **AsyncInputStream::close**) or because the stream was closed.
+    pub fn next_block<'a>(&'a mut self) -> impl Future<Output = Option<Vec<I>>> + 'a {
+        self.receiver.recv()
+    }
+
+    /// Stop reading, then stop and close the stream.
+    ///
+    /// Any blocks that have not yet been received are discarded. Returns the error that stopped
+    /// the reading thread early, if any.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.shut_down()
+    }
+}
+
+impl<I> AsyncInputStream<I> {
+    fn shut_down(&mut self) -> Result<(), Error> {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wake the reading thread if it is waiting for room in the channel.
+        self.receiver.close();
+        while self.receiver.try_recv().is_ok() {}
+        join(self.thread.take())
+    }
+}
+
+impl<O> AsyncOutputStream<O>
+where
+    O: Sample + Copy + Send + 'static,
+{
+    /// Open and start a blocking **Output** stream with the given `settings`, writing to it on a
+    /// dedicated thread from a channel that holds at most `capacity` blocks.
+    pub fn open(
+        port_audio: &PortAudio,
+        settings: OutputSettings<O>,
+        capacity: usize,
+    ) -> Result<Self, Error> {
+        let mut stream = port_audio.open_blocking_stream(settings)?;
+        stream.start()?;
+
+        let (sender, mut receiver) = mpsc::channel::<Vec<O>>(capacity.max(1));
+        let thread = thread::spawn(move || {
+            while let Some(block) = receiver.blocking_recv() {
+                match stream.write_from(&block) {
+                    Ok(_) | Err(Error::OutputUnderflowed) => (),
+                    Err(err) => return Err(err),
+                }
+            }
+            // Every sender has been dropped. Stopping waits for the queued audio to play.
+            stream.stop()?;
+            stream.close()
+        });
+
+        Ok(AsyncOutputStream {
+            sender: Some(sender),
+            thread: Some(thread),
+        })
+    }
+
+    /// Send a block of interleaved samples to be played, waiting while the channel is full.
+    ///
+    /// The future resolves to an error holding the `block` if the writing thread has stopped
+    /// because of an error, which is returned by **AsyncOutputStream::close**.
+    pub fn send_block<'a>(
+        &'a self,
+        block: Vec<O>,
+    ) -> impl Future<Output = Result<(), mpsc::error::SendError<Vec<O>>>> + 'a {
+        self.sender().send(block)
+    }
+
+    /// Borrow the sending half of the channel, e.g. to clone it for another task.
+    ///
+    /// The stream only finishes once every clone has been dropped.
+    pub fn sender(&self) -> &mpsc::Sender<Vec<O>> {
+        self.sender
+            .as_ref()
+            .expect("the sender is only taken when closing")
+    }
+
+    /// Wait for the blocks sent so far to be played, then stop and close the stream.
+    ///
+    /// This blocks the calling thread until every clone of the **AsyncOutputStream::sender** has
+    /// been dropped, so call it via `tokio::task::spawn_blocking` from within async code. Returns
+    /// the error that stopped the writing thread early, if any.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.shut_down()
+    }
+}
+
+impl<O> AsyncOutputStream<O> {
+    fn shut_down(&mut self) -> Result<(), Error> {
+        self.sender = None;
+        join(self.thread.take())
+    }
+}
+
+/// Wait for a stream's thread to finish, returning its result.
+fn join(thread: Option<thread::JoinHandle<Result<(), Error>>>) -> Result<(), Error> {
+    match thread {
+        Some(thread) => thread.join().unwrap_or(Err(Error::InternalError)),
+        None => Ok(()),
+    }
+}
+
+impl<I> Drop for AsyncInputStream<I> {
+    fn drop(&mut self) {
+        self.shut_down().ok();
+    }
+}
+
+impl<O> Drop for AsyncOutputStream<O> {
+    fn drop(&mut self) {
+        self.shut_down().ok();
+    }
+}
@@ -0,0 +1,182 @@
+//!
+//! Conversion between sample formats, for when the format an application works in differs from
+//! the one that a device accepts.
+//!
+
+use super::error::Error;
+use super::stream::{Blocking, Flow, Reader, Stream, Writer};
+use super::Sample;
+
+/// The dither applied when converting samples to a lower resolution format.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Dither {
+    /// Samples are rounded to the nearest value of the destination format.
+    None,
+    /// Triangular probability density function dither with a peak amplitude of one least
+    /// significant bit of the destination format, which decorrelates the quantisation error from
+    /// the signal at the cost of a slightly raised noise floor.
+    Triangular,
+}
+
+/// Converts buffers of samples between formats via **Sample::to_f32_sample** and
+/// **Sample::from_f32_sample**.
+///
+/// Dither is only applied when the destination format has fewer significant bits than the
+/// source, e.g. from `f32` or `i32` to `i16`.
+#[derive(Clone, Debug)]
+pub struct Converter {
+    dither: Dither,
+    /// The state of the xorshift generator used for the dither noise.
+    rng: u32,
+}
+
+/// A blocking **Stream** whose samples are converted to and from the sample type `S`.
+///
+/// The stream is opened in the device's native format, e.g. `i16`, while the application reads
+/// and writes `S`, e.g. `f32`. Conversions to `i16` use a full scale of `±32767`, and samples
+/// that lie outside of `-1.0..=1.0` are clipped.
+pub struct ConvertingStream<F, S>
+where
+    F: Flow,
+{
+    stream: Stream<Blocking<F::Buffer>, F>,
+    converter: Converter,
+    /// Holds the converted samples of the last read.
+    buffer: Vec<S>,
+}
+
+impl Converter {
+    /// Construct a **Converter** that applies the given **Dither**.
+    pub fn new(dither: Dither) -> Self {
+        Converter {
+            dither: dither,
+            rng: 0x9E37_79B9,
+        }
+    }
+
+    /// The **Dither** applied by the **Converter**.
+    pub fn dither(&self) -> Dither {
+        self.dither
+    }
+
+    /// Convert the samples of `src` into `dst`, stopping at the end of the shorter of the two.
+    pub fn convert<Src, Dst>(&mut self, src: &[Src], dst: &mut [Dst])
+    where
+        Src: Sample,
+        Dst: Sample,
+    {
+        let dither =
+            self.dither == Dither::Triangular && Dst::resolution_bits() < Src::resolution_bits();
+        let lsb = 1.0 / (1u64 << (Dst::resolution_bits() - 1)) as f32;
+        for (&s, d) in src.iter().zip(dst.iter_mut()) {
+            let mut value = s.to_f32_sample();
+            if dither {
+                value += (self.next_noise() - self.next_noise()) * lsb;
+            }
+            *d = <Dst as Sample>::from_f32_sample(value);
+        }
+    }
+
+    /// The next value from the noise generator, in the range `0.0..1.0`.
+    fn next_noise(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        (self.rng >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Converter::new(Dither::None)
+    }
+}
+
+impl<F, S> ConvertingStream<F, S>
+where
+    F: Flow,
+    S: Sample,
+{
+    /// Wrap the given blocking **Stream**, converting its samples without dither.
+    pub fn new(stream: Stream<Blocking<F::Buffer>, F>) -> Self {
+        ConvertingStream {
+            stream: stream,
+            converter: Converter::default(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Apply the given **Dither** when converting to a lower resolution format.
+    pub fn with_dither(mut self, dither: Dither) -> Self {
+        self.converter = Converter::new(dither);
+        self
+    }
+
+    /// Borrow the underlying **Stream**.
+    pub fn stream(&self) -> &Stream<Blocking<F::Buffer>, F> {
+        &self.stream
+    }
+
+    /// Mutably borrow the underlying **Stream**, e.g. to start or stop it.
+    pub fn stream_mut(&mut self) -> &mut Stream<Blocking<F::Buffer>, F> {
+        &mut self.stream
+    }
+
+    /// Unwrap the underlying **Stream**.
+    pub fn into_inner(self) -> Stream<Blocking<F::Buffer>, F> {
+        self.stream
+    }
+}
+
+impl<F, S> ConvertingStream<F, S>
+where
+    F: Flow + Reader,
+    F::Sample: Sample,
+    S: Sample,
+{
+    /// Read `frames` frames from the stream and convert them to `S`.
+    ///
+    /// As with **Stream::read**, `frames` must not exceed the stream's `frames_per_buffer`.
+    pub fn read(&mut self, frames: u32) -> Result<&[S], Error> {
+        let samples = self.stream.read(frames)?;
+        self.buffer.resize(samples.len(), S::default());
+        self.converter.convert(samples, &mut self.buffer);
+        Ok(&self.buffer)
+    }
+}
+
+impl<F, S> ConvertingStream<F, S>
+where
+    F: Flow + Writer,
+    <F as Writer>::Sample: Sample,
+    S: Sample,
+{
+    /// Convert all of the interleaved samples in `buffer` and write them to the stream.
+    ///
+    /// The `buffer` may hold any number of frames, which are converted and written in chunks of
+    /// the stream's `frames_per_buffer`, or all at once if it is unspecified. Any trailing samples
+    /// that don't make up a whole frame are ignored.
+    ///
+    /// Returns the number of frames that were written.
+    pub fn write(&mut self, buffer: &[S]) -> Result<usize, Error> {
+        let channels = Writer::channel_count(self.stream.flow()) as usize;
+        if channels == 0 {
+            return Ok(0);
+        }
+        let frames = buffer.len() / channels;
+        let chunk_frames = match self.stream.frames_per_buffer() {
+            0 => frames,
+            n => n as usize,
+        };
+        let converter = &mut self.converter;
+        let mut done = 0;
+        while done < frames {
+            let n = ::std::cmp::min(chunk_frames, frames - done);
+            let chunk = &buffer[done * channels..(done + n) * channels];
+            self.stream
+                .write(n as u32, |out| converter.convert(chunk, out))?;
+            done += n;
+        }
+        Ok(done)
+    }
+}
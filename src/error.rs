@@ -4,110 +4,397 @@
 //!
 
 use ffi;
+use num::FromPrimitive;
 
-enum_from_primitive! {
-/// Error codes returned by PortAudio functions.
-#[repr(i32)]
-#[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
-pub enum Error {
+/// Declares the **Error** enum along with the mapping between its variants and PortAudio's error
+/// codes, so that the two can't get out of sync.
+///
+/// The variants after the `;` are errors detected by this crate rather than PortAudio, each of
+/// which maps to the PortAudio error code that most closely describes it. They are unit variants
+/// unless they are given fields in braces.
+macro_rules! error_codes {
+    (
+        $( $(#[$attr:meta])* $variant:ident = $code:expr, )*
+        ;
+        $(
+            $(#[$crate_attr:meta])*
+            $crate_variant:ident $({ $( $(#[$field_attr:meta])* $field:ident: $ty:ty, )* })?
+                => $crate_code:expr,
+        )*
+    ) => {
+        /// Error codes returned by PortAudio functions, along with errors detected by this crate
+        /// before calling into PortAudio.
+        #[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
+        pub enum Error {
+            $( $(#[$attr])* $variant, )*
+            $(
+                $(#[$crate_attr])*
+                $crate_variant $({ $( $(#[$field_attr])* $field: $ty, )* })?,
+            )*
+            /// An error code unknown to this crate, e.g. one introduced by a newer PortAudio
+            /// release. The raw code is preserved.
+            Unknown(i32),
+        }
+
+        impl Error {
+            /// Convert a raw PortAudio error code into an **Error**.
+            ///
+            /// Codes that don't correspond to any known variant produce `Error::Unknown(code)`.
+            /// The errors detected by this crate are never produced.
+            ///
+            /// ```
+            /// extern crate portaudio;
+            /// extern crate portaudio_sys;
+            ///
+            /// use portaudio::Error;
+            ///
+            /// # fn main() {
+            /// let first = portaudio_sys::PaErrorCode_paNotInitialized;
+            /// let last = portaudio_sys::PaErrorCode_paBadBufferPtr;
+            /// for code in first..=last {
+            ///     assert_ne!(Error::from_code(code), Error::Unknown(code));
+            ///     assert_eq!(Error::from_code(code).to_code(), code);
+            /// }
+            /// assert_eq!(Error::from_code(0), Error::NoError);
+            ///
+            /// // Codes unknown to this crate are preserved, so every code round-trips.
+            /// assert_eq!(Error::from_code(1), Error::Unknown(1));
+            /// for code in (-20_000..=20_000).chain(vec![i32::MIN, i32::MAX]) {
+            ///     assert_eq!(Error::from_code(code).to_code(), code);
+            /// }
+            /// # }
+            /// ```
+            pub fn from_code(code: i32) -> Error {
+                $( if code == $code {
+                    return Error::$variant;
+                } )*
+                Error::Unknown(code)
+            }
+
+            /// Convert the **Error** back into its raw PortAudio error code.
+            ///
+            /// Errors detected by this crate produce the closest matching PortAudio code.
+            pub fn to_code(&self) -> i32 {
+                match *self {
+                    $( Error::$variant => $code, )*
+                    $( Error::$crate_variant { .. } => $crate_code, )*
+                    Error::Unknown(code) => code,
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
     /// No Error
-    NoError =
-        ffi::PaErrorCode_paNoError,
+    NoError = ffi::PaErrorCode_paNoError,
     /// No audio devices
-    NoDevice =
-        ffi::PA_NO_DEVICE,
+    NoDevice = ffi::PA_NO_DEVICE,
     /// Portaudio not initialized
-    NotInitialized =
-        ffi::PaErrorCode_paNotInitialized,
+    NotInitialized = ffi::PaErrorCode_paNotInitialized,
     /// Unanticipated error from the host
-    UnanticipatedHostError =
-        ffi::PaErrorCode_paUnanticipatedHostError,
+    UnanticipatedHostError = ffi::PaErrorCode_paUnanticipatedHostError,
     /// Invalid channel count
-    InvalidChannelCount =
-        ffi::PaErrorCode_paInvalidChannelCount,
+    InvalidChannelCount = ffi::PaErrorCode_paInvalidChannelCount,
     /// Invalid sample rate
-    InvalidSampleRate =
-        ffi::PaErrorCode_paInvalidSampleRate,
+    InvalidSampleRate = ffi::PaErrorCode_paInvalidSampleRate,
     /// Invalid Device
-    InvalidDevice =
-        ffi::PaErrorCode_paInvalidDevice,
+    InvalidDevice = ffi::PaErrorCode_paInvalidDevice,
     /// Invalid Flag
-    InvalidFlag =
-        ffi::PaErrorCode_paInvalidFlag,
+    InvalidFlag = ffi::PaErrorCode_paInvalidFlag,
     /// The Sample format is not supported
-    SampleFormatNotSupported =
-        ffi::PaErrorCode_paSampleFormatNotSupported,
+    SampleFormatNotSupported = ffi::PaErrorCode_paSampleFormatNotSupported,
     /// Input device not compatible with output device
-    BadIODeviceCombination =
-        ffi::PaErrorCode_paBadIODeviceCombination,
+    BadIODeviceCombination = ffi::PaErrorCode_paBadIODeviceCombination,
     /// Memory insufficient
-    InsufficientMemory =
-        ffi::PaErrorCode_paInsufficientMemory,
+    InsufficientMemory = ffi::PaErrorCode_paInsufficientMemory,
     /// The buffer is too big
-    BufferTooBig =
-        ffi::PaErrorCode_paBufferTooBig,
+    BufferTooBig = ffi::PaErrorCode_paBufferTooBig,
     /// The buffer is too small
-    BufferTooSmall =
-        ffi::PaErrorCode_paBufferTooSmall,
+    BufferTooSmall = ffi::PaErrorCode_paBufferTooSmall,
     /// Invalid callback
-    NullCallback =
-        ffi::PaErrorCode_paNullCallback,
+    NullCallback = ffi::PaErrorCode_paNullCallback,
     /// Invalid Stream
-    BadStreamPtr =
-        ffi::PaErrorCode_paBadStreamPtr,
+    BadStreamPtr = ffi::PaErrorCode_paBadStreamPtr,
     /// Time out
-    TimedOut =
-        ffi::PaErrorCode_paTimedOut,
+    TimedOut = ffi::PaErrorCode_paTimedOut,
     /// Portaudio internal error
-    InternalError =
-        ffi::PaErrorCode_paInternalError,
+    InternalError = ffi::PaErrorCode_paInternalError,
     /// Device unavailable
-    DeviceUnavailable =
-        ffi::PaErrorCode_paDeviceUnavailable,
+    DeviceUnavailable = ffi::PaErrorCode_paDeviceUnavailable,
     /// Stream info not compatible with the host
-    IncompatibleHostApiSpecificStreamInfo =
-        ffi::PaErrorCode_paIncompatibleHostApiSpecificStreamInfo,
+    IncompatibleHostApiSpecificStreamInfo = ffi::PaErrorCode_paIncompatibleHostApiSpecificStreamInfo,
     /// The stream is stopped
-    StreamIsStopped =
-        ffi::PaErrorCode_paStreamIsStopped,
+    StreamIsStopped = ffi::PaErrorCode_paStreamIsStopped,
     /// The stream is not stopped
-    StreamIsNotStopped =
-        ffi::PaErrorCode_paStreamIsNotStopped,
+    StreamIsNotStopped = ffi::PaErrorCode_paStreamIsNotStopped,
     /// The input stream has overflowed
-    InputOverflowed =
-        ffi::PaErrorCode_paInputOverflowed,
+    InputOverflowed = ffi::PaErrorCode_paInputOverflowed,
     /// The output has underflowed
-    OutputUnderflowed =
-        ffi::PaErrorCode_paOutputUnderflowed,
+    OutputUnderflowed = ffi::PaErrorCode_paOutputUnderflowed,
     /// The host API is not found by Portaudio
-    HostApiNotFound =
-        ffi::PaErrorCode_paHostApiNotFound,
+    HostApiNotFound = ffi::PaErrorCode_paHostApiNotFound,
     /// The host API is invalid
-    InvalidHostApi =
-        ffi::PaErrorCode_paInvalidHostApi,
+    InvalidHostApi = ffi::PaErrorCode_paInvalidHostApi,
     /// Portaudio cannot read from the callback stream
-    CanNotReadFromACallbackStream =
-        ffi::PaErrorCode_paCanNotReadFromACallbackStream,
+    CanNotReadFromACallbackStream = ffi::PaErrorCode_paCanNotReadFromACallbackStream,
     /// Portaudio cannot write to the callback stream
-    CanNotWriteToACallbackStream =
-        ffi::PaErrorCode_paCanNotWriteToACallbackStream,
+    CanNotWriteToACallbackStream = ffi::PaErrorCode_paCanNotWriteToACallbackStream,
     /// Portaudio cannot read from an output only stream
-    CanNotReadFromAnOutputOnlyStream =
-        ffi::PaErrorCode_paCanNotReadFromAnOutputOnlyStream,
+    CanNotReadFromAnOutputOnlyStream = ffi::PaErrorCode_paCanNotReadFromAnOutputOnlyStream,
     /// Portaudio cannot write to an input only stream
-    CanNotWriteToAnInputOnlyStream =
-        ffi::PaErrorCode_paCanNotWriteToAnInputOnlyStream,
+    CanNotWriteToAnInputOnlyStream = ffi::PaErrorCode_paCanNotWriteToAnInputOnlyStream,
     /// The stream is not compatible with the host API
-    IncompatibleStreamHostApi =
-        ffi::PaErrorCode_paIncompatibleStreamHostApi,
+    IncompatibleStreamHostApi = ffi::PaErrorCode_paIncompatibleStreamHostApi,
     /// Invalid buffer
-    BadBufferPtr =
-        ffi::PaErrorCode_paBadBufferPtr,
+    BadBufferPtr = ffi::PaErrorCode_paBadBufferPtr,
+    ;
+    /// The input and output of a duplex stream can't run at the same sample rate, e.g. because
+    /// they are different devices with different clocks.
+    ///
+    /// The fields hold the sample rates that each direction supports, which is the requested
+    /// rate if that direction supports it and the device's default sample rate otherwise.
+    DuplexRateMismatch {
+        /// A sample rate supported by the input device.
+        input: f64,
+        /// A sample rate supported by the output device.
+        output: f64,
+    } => ffi::PaErrorCode_paInvalidSampleRate,
+    /// A buffer passed to a blocking read or write doesn't have the size that the stream's sample
+    /// format and channel count call for, e.g. because it ends part way through a frame.
+    ///
+    /// Both sizes are given in bytes, as determined via `Pa_GetSampleSize`.
+    InvalidBuffer {
+        /// The number of bytes that the transfer requires.
+        expected: usize,
+        /// The number of bytes that the buffer holds.
+        actual: usize,
+    } => ffi::PaErrorCode_paBadBufferPtr,
+    /// No device with channels in the requested direction has a name containing the one given to
+    /// **PortAudio::find_device**.
+    DeviceNotFound => ffi::PaErrorCode_paInvalidDevice,
+    /// More than one device matches the name given to **PortAudio::find_device**.
+    ///
+    /// **PortAudio::find_devices** lists the matching devices, e.g. to let the user choose
+    /// between them.
+    AmbiguousDevice {
+        /// The number of devices whose names matched.
+        matches: usize,
+    } => ffi::PaErrorCode_paInvalidDevice,
+    /// The input and output devices given to **PortAudio::default_sample_rate_for** don't share
+    /// any of the sample rates that were probed.
+    NoCommonSampleRate => ffi::PaErrorCode_paInvalidSampleRate,
+    /// The signal played by **PortAudio::measure_roundtrip_latency** didn't come back on the
+    /// input within the measured time, e.g. because no loopback cable is connected.
+    NoLoopbackDetected => ffi::PaErrorCode_paTimedOut,
+    /// The host API, or the host API of the device, was left out of the host APIs that the
+    /// **PortAudio** was built to allow via **PortAudioBuilder::allow_host_apis**.
+    HostApiExcluded => ffi::PaErrorCode_paHostApiNotFound,
+    /// The number of bytes needed to buffer the requested frames and channels doesn't fit within
+    /// a `usize`, e.g. because of an absurd channel count read from a configuration file.
+    BufferTooLarge => ffi::PaErrorCode_paInsufficientMemory,
+    /// A **PortAudio** constructed by **PortAudio::new_with_thread_check** was used from another
+    /// thread than the one that constructed it, with the `strict-threading` feature enabled.
+    WrongThread => ffi::PaErrorCode_paInternalError,
+    /// **PortAudio::rescan_devices** was called while the **PortAudio** is shared, i.e. while a
+    /// clone of it or a **Stream** opened by it is still alive. Re-initialising PortAudio would
+    /// invalidate the streams, along with any **DeviceInfo** borrowed from the clones.
+    PortAudioInUse => ffi::PaErrorCode_paStreamIsNotStopped,
+}
+
+/// A broad classification of **Error**s by how an application may respond to them, as returned
+/// by **Error::category**.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ErrorCategory {
+    /// A glitch in an otherwise working stream, such as an xrun or a timed out read, after which
+    /// the stream may keep being used.
+    Transient,
+    /// The requested settings or use of a stream are invalid, e.g. an unsupported sample rate or
+    /// a read from an output-only stream. The same request will keep failing.
+    Configuration,
+    /// The device doesn't exist or can't be used, e.g. because it was unplugged. The stream
+    /// needs to be torn down and possibly re-opened on another device.
+    Device,
+    /// The host API failed or is not available. **PortAudio::last_host_error_info** may give
+    /// details.
+    Host,
+    /// PortAudio is not usable, e.g. because it is not initialised or ran out of memory.
+    Fatal,
+}
+
+impl Error {
+    /// The **ErrorCategory** of the error.
+    ///
+    /// ```
+    /// use portaudio::{Error, ErrorCategory};
+    ///
+    /// assert_eq!(Error::OutputUnderflowed.category(), ErrorCategory::Transient);
+    /// assert_eq!(Error::InvalidSampleRate.category(), ErrorCategory::Configuration);
+    /// assert_eq!(Error::DeviceUnavailable.category(), ErrorCategory::Device);
+    /// assert_eq!(Error::UnanticipatedHostError.category(), ErrorCategory::Host);
+    /// assert_eq!(Error::NotInitialized.category(), ErrorCategory::Fatal);
+    /// assert_eq!(Error::Unknown(-1).category(), ErrorCategory::Fatal);
+    /// ```
+    pub fn category(&self) -> ErrorCategory {
+        match *self {
+            Error::NoError
+            | Error::TimedOut
+            | Error::InputOverflowed
+            | Error::OutputUnderflowed => ErrorCategory::Transient,
+            Error::InvalidChannelCount
+            | Error::InvalidSampleRate
+            | Error::InvalidFlag
+            | Error::SampleFormatNotSupported
+            | Error::BadIODeviceCombination
+            | Error::BufferTooBig
+            | Error::BufferTooSmall
+            | Error::NullCallback
+            | Error::IncompatibleHostApiSpecificStreamInfo
+            | Error::StreamIsStopped
+            | Error::StreamIsNotStopped
+            | Error::CanNotReadFromACallbackStream
+            | Error::CanNotWriteToACallbackStream
+            | Error::CanNotReadFromAnOutputOnlyStream
+            | Error::CanNotWriteToAnInputOnlyStream
+            | Error::IncompatibleStreamHostApi
+            | Error::BadBufferPtr
+            | Error::DuplexRateMismatch { .. }
+            | Error::InvalidBuffer { .. }
+            | Error::AmbiguousDevice { .. }
+            | Error::NoCommonSampleRate
+            | Error::BufferTooLarge
+            | Error::WrongThread
+            | Error::PortAudioInUse => ErrorCategory::Configuration,
+            Error::NoDevice
+            | Error::InvalidDevice
+            | Error::DeviceUnavailable
+            | Error::DeviceNotFound
+            | Error::NoLoopbackDetected => ErrorCategory::Device,
+            Error::UnanticipatedHostError
+            | Error::HostApiNotFound
+            | Error::InvalidHostApi
+            | Error::HostApiExcluded => ErrorCategory::Host,
+            Error::NotInitialized
+            | Error::InsufficientMemory
+            | Error::BadStreamPtr
+            | Error::InternalError
+            | Error::Unknown(_) => ErrorCategory::Fatal,
+        }
+    }
+
+    /// Whether the error is **ErrorCategory::Transient**, i.e. it may be logged and the stream
+    /// kept running, as opposed to requiring the stream to be torn down.
+    ///
+    /// ```
+    /// use portaudio::Error;
+    ///
+    /// assert!(Error::InputOverflowed.is_recoverable());
+    /// assert!(!Error::InvalidDevice.is_recoverable());
+    /// ```
+    pub fn is_recoverable(&self) -> bool {
+        self.category() == ErrorCategory::Transient
+    }
 }
+
+impl FromPrimitive for Error {
+    fn from_i64(n: i64) -> Option<Error> {
+        if (i64::from(i32::MIN)..=i64::from(i32::MAX)).contains(&n) {
+            Some(Error::from_code(n as i32))
+        } else {
+            None
+        }
+    }
+
+    fn from_u64(n: u64) -> Option<Error> {
+        if n <= i32::MAX as u64 {
+            Some(Error::from_code(n as i32))
+        } else {
+            None
+        }
+    }
 }
 
 impl ::std::fmt::Display for Error {
+    /// Uses the text PortAudio provides for the error code via `Pa_GetErrorText`, falling back to
+    /// the variant name for codes that PortAudio does not describe (such as **NoDevice**). Errors
+    /// detected by this crate are described by the crate.
+    ///
+    /// ```
+    /// extern crate portaudio;
+    /// extern crate portaudio_sys;
+    ///
+    /// use portaudio::Error;
+    /// use std::ffi::CStr;
+    ///
+    /// # fn main() {
+    /// let text = format!("{}", Error::InvalidDevice);
+    /// assert!(!text.is_empty());
+    /// let pa_text = unsafe { portaudio_sys::Pa_GetErrorText(Error::InvalidDevice.to_code()) };
+    /// if !pa_text.is_null() {
+    ///     assert_eq!(text, unsafe { CStr::from_ptr(pa_text) }.to_str().unwrap());
+    /// }
+    ///
+    /// assert_eq!(
+    ///     format!("{}", Error::DeviceNotFound),
+    ///     "No device matches the given name"
+    /// );
+    /// # }
+    /// ```
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        match *self {
+            Error::DuplexRateMismatch { input, output } => {
+                return write!(
+                    f,
+                    "The input and output devices don't share a sample rate \
+                     (input supports {} Hz, output supports {} Hz)",
+                    input, output
+                );
+            }
+            Error::InvalidBuffer { expected, actual } => {
+                return write!(
+                    f,
+                    "The buffer holds {} bytes where {} bytes were expected",
+                    actual, expected
+                );
+            }
+            Error::DeviceNotFound => {
+                return write!(f, "No device matches the given name");
+            }
+            Error::AmbiguousDevice { matches } => {
+                return write!(f, "{} devices match the given name", matches);
+            }
+            Error::NoCommonSampleRate => {
+                return write!(f, "The input and output devices don't share a sample rate");
+            }
+            Error::NoLoopbackDetected => {
+                return write!(f, "The output wasn't captured by the input");
+            }
+            Error::HostApiExcluded => {
+                return write!(f, "The host API is not among the allowed host APIs");
+            }
+            Error::BufferTooLarge => {
+                return write!(f, "The buffer size overflows the address space");
+            }
+            Error::WrongThread => {
+                return write!(
+                    f,
+                    "PortAudio was used from another thread than it was created on"
+                );
+            }
+            Error::PortAudioInUse => {
+                return write!(f, "PortAudio is still in use by a clone or a stream");
+            }
+            _ => (),
+        }
+        let code = self.to_code();
+        let text = unsafe { ffi::Pa_GetErrorText(code) };
+        if !text.is_null() {
+            if let Ok(text) = ffi::c_str_to_str(text) {
+                if !text.starts_with("Invalid error code") {
+                    return write!(f, "{}", text);
+                }
+            }
+        }
         write!(f, "{:?}", self)
     }
 }
@@ -154,6 +441,78 @@ impl ::std::error::Error for Error {
             }
             Error::IncompatibleStreamHostApi => "The stream is not compatible with the host API",
             Error::BadBufferPtr => "Invalid buffer",
+            Error::DuplexRateMismatch { .. } => {
+                "The input and output devices don't share a sample rate"
+            }
+            Error::InvalidBuffer { .. } => "The buffer is not sized for the stream's format",
+            Error::DeviceNotFound => "No device matches the given name",
+            Error::AmbiguousDevice { .. } => "More than one device matches the given name",
+            Error::NoCommonSampleRate => "The devices don't share a sample rate",
+            Error::NoLoopbackDetected => "No loopback detected",
+            Error::HostApiExcluded => "The host API is excluded",
+            Error::BufferTooLarge => "The buffer is too large",
+            Error::WrongThread => "PortAudio was used from the wrong thread",
+            Error::PortAudioInUse => "PortAudio is still in use",
+            Error::Unknown(_) => "Unknown error",
         }
     }
 }
+
+/// A detailed explanation of why a stream format is not supported, as produced by the
+/// **PortAudio::check_*_format** methods.
+///
+/// PortAudio itself only returns a single **Error**, so the cause is found by re-checking the
+/// format with each suspect parameter adjusted to a value that the device reports as supported.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FormatSupportError {
+    /// The error returned by PortAudio for the format as given.
+    pub error: Error,
+    /// The input device's maximum number of input channels, if more were requested.
+    pub max_input_channels: Option<i32>,
+    /// The output device's maximum number of output channels, if more were requested.
+    pub max_output_channels: Option<i32>,
+    /// The device's default sample rate, if the format is supported at that rate (with any excess
+    /// channels removed) but not at the requested one.
+    pub default_sample_rate: Option<f64>,
+}
+
+impl From<Error> for FormatSupportError {
+    /// An error for which the cause wasn't looked into, e.g. because PortAudio is not
+    /// initialised.
+    fn from(err: Error) -> FormatSupportError {
+        FormatSupportError {
+            error: err,
+            max_input_channels: None,
+            max_output_channels: None,
+            default_sample_rate: None,
+        }
+    }
+}
+
+impl From<FormatSupportError> for Error {
+    fn from(err: FormatSupportError) -> Error {
+        err.error
+    }
+}
+
+impl ::std::fmt::Display for FormatSupportError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        write!(f, "{}", self.error)?;
+        if let Some(max) = self.max_input_channels {
+            write!(f, "; the input device supports at most {} channels", max)?;
+        }
+        if let Some(max) = self.max_output_channels {
+            write!(f, "; the output device supports at most {} channels", max)?;
+        }
+        if let Some(rate) = self.default_sample_rate {
+            write!(
+                f,
+                "; the format is supported at the default sample rate of {} Hz",
+                rate
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl ::std::error::Error for FormatSupportError {}
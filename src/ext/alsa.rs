@@ -0,0 +1,109 @@
+//! Stream options specific to the ALSA host API on Linux.
+
+use std::os::raw;
+
+use ffi;
+use stream::HostApiSpecificStreamInfo;
+use types::HostApiTypeId;
+
+/// The maximum length in bytes of the device string of an **AlsaStreamInfo**.
+pub const MAX_DEVICE_STRING_LEN: usize = 63;
+
+/// ALSA-specific options for one direction of a stream, corresponding to PortAudio's
+/// `PaAlsaStreamInfo`.
+///
+/// This opens the stream on an ALSA device string of the caller's choosing, such as `"hw:1,0"`,
+/// rather than on one of the devices enumerated by PortAudio. In particular this allows choosing
+/// between the `hw` device, which only accepts the hardware's native sample rates and formats,
+/// and the `plughw` device, which converts to them in ALSA's plug layer. Bypassing the plug layer
+/// is the only way to be certain that a stream runs at exactly the requested rate.
+///
+/// Attach these to **Parameters** via **Parameters::with_host_api_stream_info**. The device
+/// string replaces the **Parameters**' device if that belongs to ALSA, or is
+/// **DeviceKind::UseHostApiSpecificDeviceSpecification**. The options are ignored for devices of
+/// other host APIs, such as JACK.
+///
+/// The device string is stored inline so that **Parameters** remain `Copy`, hence its length is
+/// limited to **MAX_DEVICE_STRING_LEN**.
+#[derive(Copy, Clone)]
+pub struct AlsaStreamInfo {
+    /// The NUL-terminated device string.
+    device_string: [u8; MAX_DEVICE_STRING_LEN + 1],
+    len: usize,
+}
+
+impl AlsaStreamInfo {
+    /// Construct an **AlsaStreamInfo** that uses the given ALSA device string, e.g. `"hw:1,0"`
+    /// or `"plughw:CARD=USB,DEV=0"`.
+    ///
+    /// Returns `None` if the string is longer than **MAX_DEVICE_STRING_LEN** or contains a NUL
+    /// byte.
+    pub fn new(device_string: &str) -> Option<Self> {
+        let bytes = device_string.as_bytes();
+        if bytes.len() > MAX_DEVICE_STRING_LEN || bytes.contains(&0) {
+            return None;
+        }
+        let mut info = AlsaStreamInfo {
+            device_string: [0; MAX_DEVICE_STRING_LEN + 1],
+            len: bytes.len(),
+        };
+        info.device_string[..bytes.len()].copy_from_slice(bytes);
+        Some(info)
+    }
+
+    /// Construct an **AlsaStreamInfo** for the given device of an ALSA card, where `card` is
+    /// either the card's index or its id (e.g. `"1"` or `"USB"`).
+    ///
+    /// If `allow_resampling` is `true`, the `plughw` device is used so that ALSA converts the
+    /// stream to the rates and formats supported by the hardware. Otherwise the `hw` device is
+    /// used and opening the stream fails if the hardware doesn't support its format exactly.
+    pub fn hardware(card: &str, device: u32, allow_resampling: bool) -> Option<Self> {
+        let prefix = if allow_resampling { "plughw" } else { "hw" };
+        AlsaStreamInfo::new(&format!("{}:{},{}", prefix, card, device))
+    }
+
+    /// The ALSA device string.
+    pub fn device_string(&self) -> &str {
+        // The bytes were copied from a `str` in `new`.
+        ::std::str::from_utf8(&self.device_string[..self.len]).unwrap_or("")
+    }
+
+    /// Whether the device string refers to a device of ALSA's plug layer, which converts between
+    /// sample rates and formats.
+    pub fn allows_resampling(&self) -> bool {
+        self.device_string().starts_with("plug")
+    }
+
+    /// Produce the C struct, whose device string points into `self`.
+    ///
+    /// This borrows rather than copies `self`, as the pointer would otherwise dangle once the
+    /// copy was dropped.
+    pub(crate) fn as_c_info(&self) -> ffi::PaAlsaStreamInfo {
+        ffi::PaAlsaStreamInfo {
+            size: ::std::mem::size_of::<ffi::PaAlsaStreamInfo>() as _,
+            hostApiType: HostApiTypeId::ALSA as ffi::PaHostApiTypeId,
+            version: 1,
+            deviceString: self.device_string.as_ptr() as *const raw::c_char,
+        }
+    }
+}
+
+impl PartialEq for AlsaStreamInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.device_string() == other.device_string()
+    }
+}
+
+impl ::std::fmt::Debug for AlsaStreamInfo {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("AlsaStreamInfo")
+            .field("device_string", &self.device_string())
+            .finish()
+    }
+}
+
+impl From<AlsaStreamInfo> for HostApiSpecificStreamInfo {
+    fn from(info: AlsaStreamInfo) -> Self {
+        HostApiSpecificStreamInfo::Alsa(info)
+    }
+}
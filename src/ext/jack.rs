@@ -0,0 +1,54 @@
+//! Functions specific to the JACK host API on Linux.
+//!
+//! These are only defined by PortAudio builds with JACK support, so they are only available with
+//! the `jack` feature. Linking against a PortAudio built without JACK fails with the feature.
+
+use std::ffi::CString;
+use std::mem;
+use std::ptr;
+
+use error::Error;
+use ffi;
+use PortAudio;
+
+/// Set the name under which PortAudio registers its JACK client, which is what patchbays such as
+/// `qjackctl` list the application's ports under. By default every PortAudio application
+/// registers as "PortAudio", so running several of them makes their clients hard to tell apart.
+///
+/// The client is registered when PortAudio is initialised, so this must be called **before**
+/// **PortAudio::new**, or be left to **PortAudioBuilder::jack_client_name**. While a
+/// **PortAudio** instance (or one of its clones) is alive, a new name only takes effect once all
+/// of them have been dropped or terminated and PortAudio is initialised again.
+///
+/// PortAudio refers to the name rather than copying it, so each accepted name is kept alive for
+/// the rest of the process.
+///
+/// Returns `Err(Error::InvalidFlag)` if the name contains a NUL byte, or if PortAudio rejects it
+/// for being longer than JACK allows.
+pub fn set_client_name(name: &str) -> Result<(), Error> {
+    let name = CString::new(name).map_err(|_| Error::InvalidFlag)?;
+    match Error::from_code(unsafe { ffi::PaJack_SetClientName(name.as_ptr()) }) {
+        Error::NoError => {
+            mem::forget(name);
+            Ok(())
+        }
+        err => Err(err),
+    }
+}
+
+/// The name of the JACK client that PortAudio registered, which may differ from the name passed
+/// to **set_client_name** if JACK had to make it unique, e.g. by appending a number.
+///
+/// Returns `Err(Error::HostApiNotFound)` if PortAudio isn't using JACK, e.g. because the JACK
+/// server wasn't running when PortAudio was initialised.
+pub fn client_name(port_audio: &PortAudio) -> Result<&str, Error> {
+    port_audio.check_initialized()?;
+    let mut name = ptr::null();
+    match Error::from_code(unsafe { ffi::PaJack_GetClientName(&mut name) }) {
+        Error::NoError if !name.is_null() => {
+            ffi::c_str_to_str(name).map_err(|_| Error::InternalError)
+        }
+        Error::NoError => Err(Error::InternalError),
+        err => Err(err),
+    }
+}
@@ -25,3 +25,10 @@
 //pub mod mac_core;
 
 // pub mod asio;
+
+#[cfg(target_os = "linux")]
+pub mod alsa;
+#[cfg(all(target_os = "linux", feature = "jack"))]
+pub mod jack;
+#[cfg(windows)]
+pub mod wasapi;
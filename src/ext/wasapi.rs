@@ -0,0 +1,141 @@
+//! Stream options specific to the WASAPI host API on Windows.
+
+use ffi;
+use stream::HostApiSpecificStreamInfo;
+use types::HostApiTypeId;
+
+/// How a WASAPI stream shares its device with the rest of the system.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum WasapiMode {
+    /// Share the device with other applications via the Windows audio engine. The stream's
+    /// sample rate must match that of the engine.
+    Shared,
+    /// Share the device, letting Windows convert the stream's sample rate and format to that of
+    /// the audio engine where necessary (`paWinWasapiAutoConvert`).
+    AutoConvert,
+    /// Take exclusive control of the device, bypassing the audio engine (`paWinWasapiExclusive`).
+    ///
+    /// This allows for lower latencies and bit-perfect playback, but opening the stream fails if
+    /// another application is using the device.
+    Exclusive,
+}
+
+/// The MMCSS task that the WASAPI processing thread registers as, which determines its
+/// scheduling priority.
+#[repr(u32)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ThreadPriority {
+    /// The "Audio" task.
+    Audio = 1,
+    /// The "Capture" task.
+    Capture = 2,
+    /// The "Distribution" task.
+    Distribution = 3,
+    /// The "Games" task.
+    Games = 4,
+    /// The "Playback" task.
+    Playback = 5,
+    /// The "Pro Audio" task, the highest priority available.
+    ProAudio = 6,
+    /// The "Window Manager" task.
+    WindowManager = 7,
+}
+
+/// WASAPI-specific options for one direction of a stream, corresponding to PortAudio's
+/// `PaWasapiStreamInfo`.
+///
+/// Attach these to the **Parameters** of a WASAPI device via
+/// **Parameters::with_host_api_stream_info**.
+///
+/// PortAudio has no loopback option for WASAPI streams. Instead, it lists a loopback input
+/// device (with "[Loopback]" in its name) for each output device, which may be opened like any
+/// other input device to capture what the output device is playing. These are found by
+/// **PortAudio::open_wasapi_loopback_stream**.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct WasapiStreamInfo {
+    mode: WasapiMode,
+    thread_priority: Option<ThreadPriority>,
+    channel_mask: Option<u32>,
+    polling: bool,
+}
+
+impl WasapiStreamInfo {
+    /// Construct a **WasapiStreamInfo** for a shared mode stream with PortAudio's default
+    /// options.
+    pub fn new() -> Self {
+        WasapiStreamInfo {
+            mode: WasapiMode::Shared,
+            thread_priority: None,
+            channel_mask: None,
+            polling: false,
+        }
+    }
+
+    /// How the stream shares its device. Defaults to **WasapiMode::Shared**.
+    pub fn mode(mut self, mode: WasapiMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The priority of the processing thread. By default PortAudio chooses one based on the
+    /// stream's latency.
+    pub fn thread_priority(mut self, priority: ThreadPriority) -> Self {
+        self.thread_priority = Some(priority);
+        self
+    }
+
+    /// The speaker positions of the stream's channels, as a `WAVEFORMATEXTENSIBLE` channel mask
+    /// (e.g. `0x3` for front left and right). By default the channels are mapped in order.
+    pub fn channel_mask(mut self, mask: u32) -> Self {
+        self.channel_mask = Some(mask);
+        self
+    }
+
+    /// Whether PortAudio polls the device for buffer space rather than waiting to be signalled
+    /// by it, which some drivers require in exclusive mode. Defaults to `false`.
+    pub fn polling(mut self, polling: bool) -> Self {
+        self.polling = polling;
+        self
+    }
+
+    pub(crate) fn to_c_info(self) -> ffi::PaWasapiStreamInfo {
+        let mut flags = match self.mode {
+            WasapiMode::Shared => 0,
+            WasapiMode::AutoConvert => ffi::PA_WIN_WASAPI_AUTO_CONVERT,
+            WasapiMode::Exclusive => ffi::PA_WIN_WASAPI_EXCLUSIVE,
+        };
+        if self.thread_priority.is_some() {
+            flags |= ffi::PA_WIN_WASAPI_THREAD_PRIORITY;
+        }
+        if self.channel_mask.is_some() {
+            flags |= ffi::PA_WIN_WASAPI_USE_CHANNEL_MASK;
+        }
+        if self.polling {
+            flags |= ffi::PA_WIN_WASAPI_POLLING;
+        }
+        ffi::PaWasapiStreamInfo {
+            size: ::std::mem::size_of::<ffi::PaWasapiStreamInfo>() as _,
+            hostApiType: HostApiTypeId::WASAPI as ffi::PaHostApiTypeId,
+            version: 1,
+            flags: flags,
+            channelMask: self.channel_mask.unwrap_or(0) as _,
+            hostProcessorOutput: None,
+            hostProcessorInput: None,
+            threadPriority: self.thread_priority.map(|p| p as u32).unwrap_or(0),
+            streamCategory: 0,
+            streamOption: 0,
+        }
+    }
+}
+
+impl Default for WasapiStreamInfo {
+    fn default() -> Self {
+        WasapiStreamInfo::new()
+    }
+}
+
+impl From<WasapiStreamInfo> for HostApiSpecificStreamInfo {
+    fn from(info: WasapiStreamInfo) -> Self {
+        HostApiSpecificStreamInfo::Wasapi(info)
+    }
+}
@@ -0,0 +1,117 @@
+//!
+//! Measurement of the round-trip latency of a duplex loopback, e.g. an interface's output patched
+//! back into its input with a cable.
+//!
+
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use error::Error;
+use stream::{DuplexSettings, Parameters};
+use types::{frames_to_duration, DeviceIndex};
+use PortAudio;
+
+/// The number of frames in the chirp that is played to measure the latency.
+const PROBE_FRAMES: usize = 1024;
+/// The number of frames written and read at a time.
+const FRAMES_PER_BUFFER: u32 = 256;
+/// The longest round trip that can be measured.
+const MAX_ROUNDTRIP: Duration = Duration::from_secs(1);
+/// The lowest and highest frequencies swept by the chirp, in Hz. The highest is further limited
+/// to a quarter of the sample rate.
+const CHIRP_FREQUENCIES: (f64, f64) = (200.0, 8000.0);
+/// The normalised correlation between the chirp and the captured input below which the chirp is
+/// considered not to have been captured at all.
+const DETECTION_THRESHOLD: f64 = 0.5;
+
+/// A linear chirp with a Hann window, so that it starts and ends without a click.
+fn chirp(sample_rate: f64) -> Vec<f32> {
+    let (start, end) = CHIRP_FREQUENCIES;
+    let end = end.min(sample_rate / 4.0);
+    let length = PROBE_FRAMES as f64 / sample_rate;
+    (0..PROBE_FRAMES)
+        .map(|i| {
+            let t = i as f64 / sample_rate;
+            let phase = 2.0 * PI * (start * t + (end - start) * t * t / (2.0 * length));
+            let window = 0.5 - 0.5 * (2.0 * PI * i as f64 / (PROBE_FRAMES - 1) as f64).cos();
+            (0.5 * window * phase.sin()) as f32
+        })
+        .collect()
+}
+
+/// Find the offset of `probe` within `captured` by cross-correlation.
+///
+/// Returns `None` if no part of `captured` correlates with the `probe` closely enough, e.g.
+/// because it is silent.
+fn find_probe(probe: &[f32], captured: &[f32]) -> Option<usize> {
+    if captured.len() < probe.len() {
+        return None;
+    }
+    let probe_energy: f64 = probe.iter().map(|&s| s as f64 * s as f64).sum();
+    let mut window_energy: f64 = captured[..probe.len()]
+        .iter()
+        .map(|&s| s as f64 * s as f64)
+        .sum();
+    let mut best: Option<(usize, f64, f64)> = None;
+    for offset in 0..=captured.len() - probe.len() {
+        if offset > 0 {
+            let left = captured[offset - 1] as f64;
+            let entered = captured[offset + probe.len() - 1] as f64;
+            window_energy = (window_energy - left * left + entered * entered).max(0.0);
+        }
+        let window = &captured[offset..offset + probe.len()];
+        let dot: f64 = probe
+            .iter()
+            .zip(window)
+            .map(|(&p, &c)| p as f64 * c as f64)
+            .sum();
+        // The loopback may invert the signal, so the correlation is compared by magnitude.
+        let is_better = match best {
+            Some((_, best_dot, _)) => dot.abs() > best_dot.abs(),
+            None => true,
+        };
+        if is_better {
+            best = Some((offset, dot, window_energy));
+        }
+    }
+    let (offset, dot, energy) = best?;
+    if energy <= 0.0 || dot.abs() / (probe_energy * energy).sqrt() < DETECTION_THRESHOLD {
+        return None;
+    }
+    Some(offset)
+}
+
+pub(crate) fn measure_roundtrip_latency(
+    port_audio: &PortAudio,
+    input: DeviceIndex,
+    output: DeviceIndex,
+    sample_rate: f64,
+) -> Result<Duration, Error> {
+    const CHANNELS: i32 = 1;
+    const INTERLEAVED: bool = true;
+    let in_latency = port_audio.device_info(input)?.default_low_input_latency;
+    let out_latency = port_audio.device_info(output)?.default_low_output_latency;
+    let in_params = Parameters::<f32>::new(input, CHANNELS, INTERLEAVED, in_latency);
+    let out_params = Parameters::<f32>::new(output, CHANNELS, INTERLEAVED, out_latency);
+    let settings = DuplexSettings::new(in_params, out_params, sample_rate, FRAMES_PER_BUFFER);
+    let mut stream = port_audio.open_blocking_stream(settings)?;
+
+    // The chirp is played at the very start, followed by enough silence for it to come back.
+    let probe = chirp(sample_rate);
+    let total_frames = (MAX_ROUNDTRIP.as_secs_f64() * sample_rate).ceil() as usize + probe.len();
+    let mut played = probe.clone();
+    played.resize(total_frames, 0.0);
+    let mut captured = vec![0.0; total_frames];
+
+    stream.start()?;
+    let block = FRAMES_PER_BUFFER as usize;
+    for (out, into) in played.chunks(block).zip(captured.chunks_mut(block)) {
+        stream.write_from(out)?;
+        stream.read_into(into)?;
+    }
+    stream.stop()?;
+    stream.close()?;
+
+    let offset = find_probe(&probe, &captured).ok_or(Error::NoLoopbackDetected)?;
+    Ok(frames_to_duration(offset as u64, sample_rate))
+}
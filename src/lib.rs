@@ -54,51 +54,115 @@
 #[macro_use]
 extern crate bitflags;
 extern crate libc;
+#[cfg(feature = "log")]
+extern crate log;
 extern crate num;
 extern crate portaudio_sys as ffi;
+#[cfg(feature = "tokio")]
+extern crate tokio;
 
 use num::FromPrimitive;
 use std::option::Option;
 use std::os::raw;
 
-pub use error::Error;
+#[cfg(feature = "tokio")]
+pub use async_stream::{AsyncInputStream, AsyncOutputStream};
+pub use convert::{Converter, ConvertingStream, Dither};
+pub use error::{Error, ErrorCategory, FormatSupportError};
+#[cfg(all(target_os = "linux", feature = "jack"))]
+pub use ext::jack;
+/// The virtual device that stands in for PortAudio when the `mock-backend` feature is enabled.
+#[cfg(feature = "mock-backend")]
+pub use ffi::mock;
 pub use ffi::{
     PaStreamCallbackResult as StreamCallbackResult, PA_ABORT as Abort, PA_COMPLETE as Complete,
     PA_CONTINUE as Continue,
 };
+pub use output_queue::{OutputQueue, OutputQueueReader};
+pub use recorder::Recorder;
+pub use resample::AdaptiveResampler;
+pub use ring_buffer::{RingBuffer, RingBufferReader, RingBufferWriter};
 pub use stream::{
     callback_flags as stream_callback_flags, flags as stream_flags, Available as StreamAvailable,
-    Blocking, CallbackFlags as StreamCallbackFlags, CallbackTimeInfo as StreamCallbackTimeInfo,
-    Duplex, DuplexCallbackArgs as DuplexStreamCallbackArgs, DuplexSettings as DuplexStreamSettings,
-    Flags as StreamFlags, Flow, Info as StreamInfo, Input,
+    BlockIterator, Blocking, BufferLayout, CallbackFlags as StreamCallbackFlags,
+    CallbackTimeInfo as StreamCallbackTimeInfo, CustomStreamInfo, DefaultDuplex, Duplex,
+    DuplexCallbackArgs as DuplexStreamCallbackArgs, DuplexSettings as DuplexStreamSettings,
+    Flags as StreamFlags, Flow, HostApiSpecificStreamInfo, Info as StreamInfo, Input,
     InputCallbackArgs as InputStreamCallbackArgs, InputSettings as InputStreamSettings,
     NonBlocking, Output, OutputCallbackArgs as OutputStreamCallbackArgs,
     OutputSettings as OutputStreamSettings, Parameters as StreamParameters,
-    Settings as StreamSettings, Stream,
+    ParametersBuilder as StreamParametersBuilder, PlanarBuffer, PlanarBufferMut, Raw, RawDuplex,
+    RawDuplexSettings as RawDuplexStreamSettings, RawInput,
+    RawInputSettings as RawInputStreamSettings, RawOutput,
+    RawOutputSettings as RawOutputStreamSettings, Settings as StreamSettings, State as StreamState,
+    Stream, StreamGuard, MAX_HOST_API_SPECIFIC_INFO_SIZE, TIMEOUT_POLL_INTERVAL,
 };
 pub use types::{
-    DeviceIndex, DeviceInfo, Frames, HostApiIndex, HostApiInfo, HostApiTypeId, HostErrorInfo,
-    SampleFormat, Time, FRAMES_PER_BUFFER_UNSPECIFIED,
+    duration_to_frames, frames_to_duration, DeviceIndex, DeviceInfo, Direction, Frames,
+    HostApiIndex, HostApiInfo, HostApiTypeId, HostErrorInfo, SampleFormat, Time, VersionInfo,
+    FRAMES_PER_BUFFER_UNSPECIFIED, I24, STANDARD_SAMPLE_RATES,
 };
+pub use xrun::{XrunCounter, XrunStats};
 
 use std::ptr;
+use types::DeviceKind;
+
+/// Emits a `log` record with the "portaudio" target, e.g. `log_event!(debug, "started")`.
+///
+/// Without the `log` feature this expands to nothing, so the arguments are never evaluated.
+#[cfg(feature = "log")]
+macro_rules! log_event {
+    ($level:ident, $($arg:tt)+) => {
+        ::log::$level!(target: "portaudio", $($arg)+)
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_event {
+    ($level:ident, $($arg:tt)+) => {};
+}
 
 #[macro_use]
 mod enum_primitive;
+#[cfg(feature = "tokio")]
+pub mod async_stream;
+pub mod convert;
 pub mod error;
 pub mod ext;
+mod latency;
+pub mod meter;
+mod output_queue;
+mod recorder;
+pub mod resample;
+mod ring_buffer;
 pub mod stream;
+pub mod tone;
 mod types;
+#[cfg(feature = "wav")]
+pub mod wav;
+mod xrun;
 
 /// A type-safe wrapper around the PortAudio API.
 ///
 /// We use a type here instead of pure functions in order to ensure correct intialisation and
 /// termination of the underlying PortAudio instance.
-#[derive(Debug)]
+///
+/// A **PortAudio** is a reference counted handle, so it can be cloned cheaply to share it between
+/// threads or components. Each **Stream** also holds a reference, so a stream keeps PortAudio
+/// initialised even after every **PortAudio** handle has been dropped. `Pa_Terminate` is called
+/// once the last handle and the last stream are gone.
+///
+/// **PortAudio** is `Send` and `Sync`, but PortAudio itself doesn't guarantee that its functions
+/// may be called concurrently: apart from calls on an open stream, such as starting, stopping or
+/// reading it, its API should be used from one thread at a time. Querying devices from two
+/// threads at once, or enumerating them while another thread initialises or terminates a
+/// **PortAudio**, may misbehave. **PortAudio::new_with_thread_check** helps to catch this.
+#[derive(Clone, Debug)]
 pub struct PortAudio {
     /// The lifetime of the `PortAudio` API.
     ///
-    /// The lifetime is shared between `PortAudio` and all its spawned `Stream`s.
+    /// The lifetime is shared between all clones of a `PortAudio` and all their spawned
+    /// `Stream`s.
     life: std::sync::Arc<Life>,
 }
 
@@ -110,6 +174,155 @@ pub struct Life {
     /// This is solely used for checking whether or not the PortAudio API has already been
     /// terminated manually (via the `PortAudio::terminate` method) when `Drop::drop` is called.
     is_terminated: std::sync::Mutex<bool>,
+    /// The host APIs given to **PortAudioBuilder::allow_host_apis**, or `None` if all are allowed.
+    allowed_host_apis: Option<Vec<HostApiTypeId>>,
+    /// The thread that built the `PortAudio`, if **PortAudioBuilder::thread_check** was enabled.
+    owner_thread: Option<std::thread::ThreadId>,
+    /// Whether using the `PortAudio` from another thread has already been warned about.
+    warned_thread: std::sync::atomic::AtomicBool,
+}
+
+impl Life {
+    /// Whether the host API at the given index is one of the allowed host APIs.
+    fn allows_host_api(&self, host_api: HostApiIndex) -> bool {
+        let allowed = match self.allowed_host_apis {
+            Some(ref allowed) => allowed,
+            None => return true,
+        };
+        let c_info = unsafe { ffi::Pa_GetHostApiInfo(host_api) };
+        if c_info.is_null() {
+            return false;
+        }
+        match FromPrimitive::from_u32(unsafe { (*c_info).type_ }) {
+            Some(type_id) => allowed.contains(&type_id),
+            None => false,
+        }
+    }
+
+    /// Whether the given device belongs to one of the allowed host APIs.
+    ///
+    /// Indices that don't refer to a device, such as that of
+    /// **DeviceKind::UseHostApiSpecificDeviceSpecification**, are left for PortAudio to check.
+    fn allows_device(&self, device: ffi::PaDeviceIndex) -> bool {
+        if self.allowed_host_apis.is_none() || device < 0 {
+            return true;
+        }
+        let c_info = unsafe { ffi::Pa_GetDeviceInfo(device) };
+        c_info.is_null() || self.allows_host_api(unsafe { (*c_info).hostApi })
+    }
+
+    /// Returns `Err(Error::HostApiExcluded)` unless the devices of the given stream parameters
+    /// belong to allowed host APIs.
+    fn check_stream_params(
+        &self,
+        in_params: Option<ffi::PaStreamParameters>,
+        out_params: Option<ffi::PaStreamParameters>,
+    ) -> Result<(), Error> {
+        let allowed = in_params
+            .iter()
+            .chain(out_params.iter())
+            .all(|params| self.allows_device(params.device));
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::HostApiExcluded)
+        }
+    }
+}
+
+/// Configures the initialisation of a **PortAudio**, as returned by **PortAudio::builder**.
+///
+/// Some options, such as the JACK client name, have to be given to PortAudio before
+/// `Pa_Initialize`, while others are applied on top of the initialised API. **build** takes care
+/// of doing each at the right time.
+///
+/// PortAudio has no options for its ASIO host API that would need to be set before
+/// initialisation. ASIO devices are configured per stream, or by their driver's control panel.
+#[derive(Clone, Debug, Default)]
+pub struct PortAudioBuilder {
+    allowed_host_apis: Option<Vec<HostApiTypeId>>,
+    #[cfg(all(target_os = "linux", feature = "jack"))]
+    jack_client_name: Option<String>,
+    thread_check: bool,
+}
+
+impl PortAudioBuilder {
+    /// Construct a **PortAudioBuilder** with the same options as **PortAudio::new**.
+    pub fn new() -> Self {
+        PortAudioBuilder {
+            allowed_host_apis: None,
+            #[cfg(all(target_os = "linux", feature = "jack"))]
+            jack_client_name: None,
+            thread_check: false,
+        }
+    }
+
+    /// The name under which PortAudio registers its JACK client, see **jack::set_client_name**.
+    ///
+    /// The name is only used if PortAudio isn't already initialised by another **PortAudio**.
+    #[cfg(all(target_os = "linux", feature = "jack"))]
+    pub fn jack_client_name(mut self, name: &str) -> Self {
+        self.jack_client_name = Some(name.to_string());
+        self
+    }
+
+    /// Only expose the host APIs of the given types, e.g. to keep DirectSound and MME out of a
+    /// device picker on Windows.
+    ///
+    /// PortAudio itself still initialises every host API it was built with, so this is a filter
+    /// on top of it:
+    ///
+    /// - **PortAudio::host_apis** and **PortAudio::devices** (along with the methods that search
+    ///   the devices, such as **PortAudio::find_device**) skip the other host APIs and their
+    ///   devices.
+    /// - **PortAudio::default_host_api** returns the first of the given host APIs that is
+    ///   available when PortAudio's own default is excluded, and **default_input_device** and
+    ///   **default_output_device** return that host API's default devices.
+    /// - Looking up or listing the devices of an excluded host API, and opening a stream on one
+    ///   of its devices, returns `Err(Error::HostApiExcluded)`.
+    ///
+    /// **device_count** and **host_api_count** are left unfiltered, as they describe the range
+    /// of valid indices.
+    pub fn allow_host_apis(mut self, host_apis: &[HostApiTypeId]) -> Self {
+        self.allowed_host_apis = Some(host_apis.to_vec());
+        self
+    }
+
+    /// Check that the **PortAudio** is only used from the thread that calls **build**, see
+    /// **PortAudio::new_with_thread_check**.
+    pub fn thread_check(mut self) -> Self {
+        self.thread_check = true;
+        self
+    }
+
+    /// Initialise PortAudio with the configured options, as described for **PortAudio::new**.
+    ///
+    /// Returns `Err(Error::InvalidFlag)` if the JACK client name is rejected, without
+    /// initialising PortAudio.
+    pub fn build(self) -> Result<PortAudio, Error> {
+        #[cfg(all(target_os = "linux", feature = "jack"))]
+        {
+            if let Some(ref name) = self.jack_client_name {
+                jack::set_client_name(name)?;
+            }
+        }
+        match Error::from_code(unsafe { ffi::Pa_Initialize() }) {
+            Error::NoError => {
+                let life = std::sync::Arc::new(Life {
+                    is_terminated: std::sync::Mutex::new(false),
+                    allowed_host_apis: self.allowed_host_apis,
+                    owner_thread: if self.thread_check {
+                        Some(std::thread::current().id())
+                    } else {
+                        None
+                    },
+                    warned_thread: std::sync::atomic::AtomicBool::new(false),
+                });
+                Ok(PortAudio { life: life })
+            }
+            err => Err(err),
+        }
+    }
 }
 
 impl PortAudio {
@@ -124,19 +337,41 @@ impl PortAudio {
     ///
     /// It is safe to simultaneously construct more than one **PortAudio** instance, however this
     /// is rarely necessary.
+    ///
+    /// **PortAudio::builder** allows configuring the initialisation.
     pub fn new() -> Result<Self, Error> {
-        unsafe {
-            let error = FromPrimitive::from_i32(ffi::Pa_Initialize()).unwrap();
-            match error {
-                Error::NoError => {
-                    let life = std::sync::Arc::new(Life {
-                        is_terminated: std::sync::Mutex::new(false),
-                    });
-                    Ok(PortAudio { life: life })
-                }
-                err => Err(err),
-            }
-        }
+        PortAudioBuilder::new().build()
+    }
+
+    /// Construct a **PortAudio** that checks it is only used from the calling thread.
+    ///
+    /// Each method of this **PortAudio**, and of its clones, that calls into PortAudio, such as
+    /// those enumerating devices and host APIs or opening streams, first checks the current
+    /// thread. This catches devices being queried from several threads, which PortAudio doesn't
+    /// support (see **PortAudio**). The methods of open **Stream**s aren't checked, as streams
+    /// may be used from any thread.
+    ///
+    /// In debug builds, the first use from another thread is logged as a warning (with the `log`
+    /// feature). In release builds nothing is checked, unless the `strict-threading` feature is
+    /// enabled, in which case every use from another thread returns `Err(Error::WrongThread)`
+    /// in any build.
+    ///
+    /// This is a shorthand for `PortAudio::builder().thread_check().build()`.
+    pub fn new_with_thread_check() -> Result<Self, Error> {
+        PortAudio::builder().thread_check().build()
+    }
+
+    /// Returns a **PortAudioBuilder** for constructing a **PortAudio** with non-default options.
+    pub fn builder() -> PortAudioBuilder {
+        PortAudioBuilder::new()
+    }
+
+    /// Construct a **PortAudio** that only exposes the host APIs of the given types.
+    ///
+    /// This is a shorthand for `PortAudio::builder().allow_host_apis(host_apis).build()`, see
+    /// **PortAudioBuilder::allow_host_apis**.
+    pub fn initialize_with_host_apis(host_apis: &[HostApiTypeId]) -> Result<Self, Error> {
+        PortAudio::builder().allow_host_apis(host_apis).build()
     }
 
     /// Takes ownership of `self` and terminates the PortAudio API using `Pa_Terminate`.
@@ -144,13 +379,111 @@ impl PortAudio {
     /// This function deallocates all resources allocated by PortAudio since it was constructed.
     ///
     /// **Calling this method is optional**. It is only necessary if you require handling any
-    /// PortAudio termination errors. Otherwise, `Pa_Terminate` will be called and all necessary
-    /// cleanup will occur automatically when this **PortAudio** instance is **Drop**ped.
+    /// PortAudio termination errors, or control over when termination happens, e.g. before
+    /// deliberately re-initialising. Otherwise, `Pa_Terminate` will be called and all necessary
+    /// cleanup will occur automatically when this **PortAudio** instance is **Drop**ped, in which
+    /// case any error is discarded.
+    ///
+    /// If other clones of this **PortAudio** or any of its **Stream**s are still alive, PortAudio
+    /// must stay initialised for them, so this only drops `self` and returns `Ok(())`. The API is
+    /// then terminated once the last of them is dropped.
     pub fn terminate(self) -> Result<(), Error> {
-        *self.life.is_terminated.lock().unwrap() = true;
+        let life = match std::sync::Arc::try_unwrap(self.life) {
+            Ok(life) => life,
+            Err(_) => return Ok(()),
+        };
+        let mut is_terminated = life.is_terminated.lock().unwrap();
+        if *is_terminated {
+            return Ok(());
+        }
+        *is_terminated = true;
         terminate()
     }
 
+    /// Refresh PortAudio's list of devices by terminating and re-initialising the API.
+    ///
+    /// PortAudio only enumerates the available devices during `Pa_Initialize`, so this is
+    /// necessary to detect devices that have been plugged in or removed since this **PortAudio**
+    /// was constructed.
+    ///
+    /// **All previously retrieved DeviceIndex and HostApiIndex values become stale**, as devices
+    /// may be numbered differently after the rescan. **DeviceInfo::signature** may be used to find
+    /// a device again.
+    ///
+    /// Terminating PortAudio would invalidate any open streams, and any **DeviceInfo** borrowed
+    /// from other clones, so this returns `Err(Error::PortAudioInUse)` while any clone of this
+    /// **PortAudio**, or any **Stream** opened by it or its clones, is still alive. PortAudio only
+    /// truly terminates once every initialisation has been matched by a termination, so the
+    /// device list is not refreshed while other independently constructed **PortAudio** instances
+    /// exist.
+    ///
+    /// If re-initialisation fails, the error is returned and this **PortAudio** is left
+    /// uninitialised, in which case the other methods return `Err(Error::NotInitialized)` until a
+    /// later **rescan_devices** succeeds.
+    pub fn rescan_devices(&mut self) -> Result<(), Error> {
+        self.check_thread()?;
+        if std::sync::Arc::strong_count(&self.life) > 1 {
+            return Err(Error::PortAudioInUse);
+        }
+        if self.check_initialized().is_ok() {
+            terminate()?;
+        }
+        match Error::from_code(unsafe { ffi::Pa_Initialize() }) {
+            Error::NoError => {
+                *self.life.is_terminated.lock().unwrap() = false;
+                Ok(())
+            }
+            err => {
+                *self.life.is_terminated.lock().unwrap() = true;
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns `Err(Error::NotInitialized)` if PortAudio has been terminated from under this
+    /// **PortAudio**, i.e. by a failed **PortAudio::rescan_devices**, so that nothing is asked of
+    /// the uninitialised library.
+    ///
+    /// The calling thread is checked first, see **PortAudio::check_thread**.
+    fn check_initialized(&self) -> Result<(), Error> {
+        self.check_thread()?;
+        if *self.life.is_terminated.lock().unwrap() {
+            Err(Error::NotInitialized)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that a **PortAudio** constructed with **PortAudioBuilder::thread_check** is used
+    /// from the thread that constructed it, as described for **PortAudio::new_with_thread_check**.
+    ///
+    /// Without `debug_assertions` or the `strict-threading` feature this compiles to nothing.
+    fn check_thread(&self) -> Result<(), Error> {
+        if !cfg!(any(debug_assertions, feature = "strict-threading")) {
+            return Ok(());
+        }
+        let owner = match self.life.owner_thread {
+            Some(owner) => owner,
+            None => return Ok(()),
+        };
+        if owner == std::thread::current().id() {
+            return Ok(());
+        }
+        if cfg!(feature = "strict-threading") {
+            return Err(Error::WrongThread);
+        }
+        let ordering = std::sync::atomic::Ordering::Relaxed;
+        if !self.life.warned_thread.swap(true, ordering) {
+            log_event!(
+                warn,
+                "PortAudio is used from thread {:?}, but was created on thread {:?}",
+                std::thread::current().id(),
+                owner
+            );
+        }
+        Ok(())
+    }
+
     /// Retrieve the release number of the currently running PortAudio build.
     pub fn version(&self) -> i32 {
         version()
@@ -163,6 +496,12 @@ impl PortAudio {
         version_text()
     }
 
+    /// Retrieve the major, minor and sub-minor version numbers along with the version control
+    /// revision of the currently running PortAudio build, see **version_info**.
+    pub fn version_info(&self) -> Option<VersionInfo> {
+        version_info()
+    }
+
     /// Produces an iterator yielding the **DeviceIndex** for each device along with their
     /// respective **DeviceInfo**s.
     pub fn devices(&self) -> Result<Devices, Error> {
@@ -183,13 +522,37 @@ impl PortAudio {
     /// caused by PortAudio not being initialised, however this should not be possible with our
     /// type-safe **PortAudio** API.
     pub fn device_count(&self) -> Result<u32, Error> {
+        self.check_initialized()?;
         match unsafe { ffi::Pa_GetDeviceCount() } {
             n if n >= 0 => Ok(n as u32),
             // NOTE: The docs for this error (NO_DEVICE) specify that this simply indicates that
             // there are no devices available or that no available devices should be used. Thus, we
             // will simply translate this to a count of `0`.
             -1 => Ok(0),
-            err => Err(::num::FromPrimitive::from_i32(err).unwrap()),
+            err => Err(Error::from_code(err)),
+        }
+    }
+
+    /// Retrieve the index of the default device for the given **Direction**, as by
+    /// **PortAudio::default_input_device** or **PortAudio::default_output_device**.
+    ///
+    /// Returns `Error` if no default device is available or an error was encountered.
+    pub fn default_device(&self, direction: Direction) -> Result<DeviceIndex, Error> {
+        self.check_initialized()?;
+        let idx = match direction {
+            Direction::Input => unsafe { ffi::Pa_GetDefaultInputDevice() },
+            Direction::Output => unsafe { ffi::Pa_GetDefaultOutputDevice() },
+        };
+        match idx {
+            idx if idx >= 0 && self.life.allows_device(idx) => Ok(DeviceIndex(idx as u32)),
+            // The default device belongs to an excluded host API, so fall back to that of the
+            // default among the allowed host APIs.
+            idx if idx >= 0 => {
+                let host_api = self.default_host_api()?;
+                let info = self.host_api_info(host_api).ok_or(Error::HostApiNotFound)?;
+                info.default_device(direction).ok_or(Error::NoDevice)
+            }
+            err => Err(Error::from_code(err)),
         }
     }
 
@@ -202,10 +565,7 @@ impl PortAudio {
     ///
     /// **TODO:** Investigate exactly what errors may occur as the PA docs aren't clear on this.
     pub fn default_input_device(&self) -> Result<DeviceIndex, Error> {
-        match unsafe { ffi::Pa_GetDefaultInputDevice() } {
-            idx if idx >= 0 => Ok(DeviceIndex(idx as u32)),
-            err => Err(::num::FromPrimitive::from_i32(err).unwrap()),
-        }
+        self.default_device(Direction::Input)
     }
 
     /// Retrieve the index of the default output device. The result can be used in the
@@ -217,10 +577,7 @@ impl PortAudio {
     ///
     /// **TODO:** Investigate exactly what errors may occur as the PA docs aren't clear on this.
     pub fn default_output_device(&self) -> Result<DeviceIndex, Error> {
-        match unsafe { ffi::Pa_GetDefaultOutputDevice() } {
-            idx if idx >= 0 => Ok(DeviceIndex(idx as u32)),
-            err => Err(::num::FromPrimitive::from_i32(err).unwrap()),
-        }
+        self.default_device(Direction::Output)
     }
 
     /// Retrieve a **DeviceInfo** structure containing information about the specified device.
@@ -233,6 +590,7 @@ impl PortAudio {
     ///
     /// - device - A valid device index in the range 0 to (port_audio.device_count()-1).
     pub fn device_info(&self, device: DeviceIndex) -> Result<DeviceInfo, Error> {
+        self.check_initialized()?;
         let c_info = unsafe { ffi::Pa_GetDeviceInfo(device.into()) };
         if c_info.is_null() {
             Err(Error::InvalidDevice)
@@ -241,6 +599,95 @@ impl PortAudio {
         }
     }
 
+    /// Retrieve the **DeviceInfo** of the given device, or `None` if there is no such device.
+    ///
+    /// Unlike **device_info**, the index is checked against **device_count** first, which suits
+    /// restoring a previously saved **DeviceIndex** that may have gone out of range since, e.g.
+    /// by falling back to the default device whenever this returns `None`.
+    pub fn device(&self, device: DeviceIndex) -> Option<DeviceInfo<'_>> {
+        let count = self.device_count().ok()?;
+        if device.0 < count {
+            self.device_info(device).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Find the device whose **DeviceInfo::signature** matches the given `signature`.
+    ///
+    /// **DeviceIndex**es may refer to a different device once devices are added or removed, so a
+    /// signature should be persisted instead and resolved to an index with this method.
+    ///
+    /// PortAudio only enumerates devices during initialisation, so to observe hot-plugged devices
+    /// this **PortAudio** instance must be **terminate**d and a new one constructed first.
+    ///
+    /// Returns `Ok(None)` if no device matches. If more than one device shares the signature, the
+    /// first is returned.
+    pub fn find_device_by_signature(&self, signature: &str) -> Result<Option<DeviceIndex>, Error> {
+        for device in self.devices()? {
+            let (idx, info) = device?;
+            if info.signature() == signature {
+                return Ok(Some(idx));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the device with channels in the given **Direction** whose name contains
+    /// `name_substring`, ignoring case.
+    ///
+    /// This suits user-facing device selection, e.g. a `--device "Scarlett"` command line option,
+    /// as names stay the same when devices are reconnected whereas **DeviceIndex**es may not.
+    ///
+    /// If several devices contain `name_substring` but exactly one of them has that name in full,
+    /// that device is returned.
+    ///
+    /// Returns `Err(Error::DeviceNotFound)` if no device matches and
+    /// `Err(Error::AmbiguousDevice { .. })` if more than one does, in which case
+    /// **find_devices** lists the candidates.
+    pub fn find_device(
+        &self,
+        name_substring: &str,
+        direction: Direction,
+    ) -> Result<DeviceIndex, Error> {
+        let matches = self.find_devices(name_substring, direction)?;
+        match matches.len() {
+            0 => Err(Error::DeviceNotFound),
+            1 => Ok(matches[0]),
+            n => {
+                let needle = name_substring.to_lowercase();
+                let mut exact_matches = Vec::new();
+                for &idx in &matches {
+                    if self.device_info(idx)?.name.to_lowercase() == needle {
+                        exact_matches.push(idx);
+                    }
+                }
+                match exact_matches[..] {
+                    [idx] => Ok(idx),
+                    _ => Err(Error::AmbiguousDevice { matches: n }),
+                }
+            }
+        }
+    }
+
+    /// List the devices with channels in the given **Direction** whose names contain
+    /// `name_substring`, ignoring case, in the order that PortAudio enumerates them.
+    pub fn find_devices(
+        &self,
+        name_substring: &str,
+        direction: Direction,
+    ) -> Result<Vec<DeviceIndex>, Error> {
+        let needle = name_substring.to_lowercase();
+        let mut matches = Vec::new();
+        for device in self.devices()? {
+            let (idx, info) = device?;
+            if info.max_channels(direction) > 0 && info.name.to_lowercase().contains(&needle) {
+                matches.push(idx);
+            }
+        }
+        Ok(matches)
+    }
+
     /// Produces an iterator yielding the **HostApiIndex** of each available API along with their
     /// respective **HostApiInfo**s.
     pub fn host_apis(&self) -> HostApis {
@@ -260,6 +707,7 @@ impl PortAudio {
     ///
     /// TODO: Determine exactly what errors might occur (PA docs aren't clear on this).
     pub fn host_api_count(&self) -> Result<HostApiIndex, Error> {
+        self.check_initialized()?;
         unsafe { result_from_host_api_index(ffi::Pa_GetHostApiCount()) }
     }
 
@@ -272,8 +720,33 @@ impl PortAudio {
     /// default host API index or an `Error` if an error is encountered.
     ///
     /// TODO: Determine exactly what errors might occur (PA docs aren't clear on this).
+    ///
+    /// If PortAudio's default host API was excluded via **PortAudioBuilder::allow_host_apis**,
+    /// the first of the allowed host APIs that is available is returned instead, or
+    /// `Err(Error::HostApiNotFound)` if none of them is.
     pub fn default_host_api(&self) -> Result<HostApiIndex, Error> {
-        unsafe { result_from_host_api_index(ffi::Pa_GetDefaultHostApi()) }
+        self.check_initialized()?;
+        let host_api = unsafe { result_from_host_api_index(ffi::Pa_GetDefaultHostApi())? };
+        let allowed = match self.life.allowed_host_apis {
+            Some(ref allowed) if !self.life.allows_host_api(host_api) => allowed,
+            _ => return Ok(host_api),
+        };
+        allowed
+            .iter()
+            .filter_map(|&type_id| self.host_api_type_id_to_host_api_index(type_id).ok())
+            .next()
+            .ok_or(Error::HostApiNotFound)
+    }
+
+    /// Retrieve the index of the default host API along with its **HostApiInfo**, e.g. to report
+    /// which backend is in use.
+    ///
+    /// This is the same as calling **PortAudio::default_host_api** followed by
+    /// **PortAudio::host_api_info**.
+    pub fn default_host_api_info<'a>(&'a self) -> Result<(HostApiIndex, HostApiInfo<'a>), Error> {
+        let host_api = self.default_host_api()?;
+        let info = self.host_api_info(host_api).ok_or(Error::InvalidHostApi)?;
+        Ok((host_api, info))
     }
 
     /// Retrieve a pointer to a structure containing information about a specific host Api.
@@ -286,6 +759,9 @@ impl PortAudio {
     ///
     /// Returns `None` if the `host_api` parameter is out of range or an error is encountered.
     pub fn host_api_info<'a>(&'a self, host_api: HostApiIndex) -> Option<HostApiInfo<'a>> {
+        if self.check_initialized().is_err() {
+            return None;
+        }
         let c_host_info = unsafe { ffi::Pa_GetHostApiInfo(host_api as HostApiIndex) };
         if c_host_info.is_null() {
             None
@@ -294,6 +770,122 @@ impl PortAudio {
         }
     }
 
+    /// Retrieve the **HostApiInfo** of the given host API, or `None` if there is no such host API.
+    ///
+    /// The index is checked against **host_api_count** first, as with **PortAudio::device**.
+    pub fn host_api<'a>(&'a self, host_api: HostApiIndex) -> Option<HostApiInfo<'a>> {
+        let count = self.host_api_count().ok()?;
+        if host_api >= 0 && host_api < count {
+            self.host_api_info(host_api)
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve the host API that the given device belongs to along with its index, e.g. to group
+    /// devices by their backend within a device picker.
+    ///
+    /// Returns `Err(Error::InvalidDevice)` if there is no such device, and
+    /// `Err(Error::HostApiNotFound)` if the device's host API can't be described.
+    pub fn device_host_api(
+        &self,
+        device: DeviceIndex,
+    ) -> Result<(HostApiIndex, HostApiInfo<'_>), Error> {
+        let host_api = self.device_info(device)?.host_api;
+        let info = self.host_api_info(host_api).ok_or(Error::HostApiNotFound)?;
+        Ok((host_api, info))
+    }
+
+    /// The names of the device's channels in the given **Direction**, e.g. `"Mic 1"` or
+    /// `"SPDIF L"`, such as for labelling the channels within a routing matrix.
+    ///
+    /// PortAudio only provides channel names through host-API-specific functions, so this is
+    /// best-effort and limited to:
+    ///
+    /// - CoreAudio devices on macOS, via `PaMacCore_GetChannelName`.
+    /// - ASIO devices on Windows when built with the `asio` feature, via
+    ///   `PaAsio_GetInputChannelName` and `PaAsio_GetOutputChannelName`.
+    ///
+    /// Returns `None` for the devices of all other host APIs, or if there is no such device or
+    /// any of its channels can't be named. A device without channels in the direction has no
+    /// names.
+    pub fn channel_names(&self, device: DeviceIndex, direction: Direction) -> Option<Vec<String>> {
+        let info = self.device_info(device).ok()?;
+        let host_api = self.host_api_info(info.host_api)?.host_type;
+        if !has_channel_names(host_api) {
+            return None;
+        }
+        (0..info.max_channels(direction))
+            .map(|channel| channel_name(device, channel, direction))
+            .collect()
+    }
+
+    /// Produces an iterator yielding the **DeviceIndex** for each device belonging to the given host
+    /// API along with their respective **DeviceInfo**s.
+    ///
+    /// The host-API-specific device indices are translated to global **DeviceIndex**es via
+    /// `Pa_HostApiDeviceIndexToDeviceIndex`. The iterator's length is the host API's
+    /// `device_count`.
+    ///
+    /// Returns `Err(Error::InvalidHostApi)` if the `host_api` parameter is out of range, and
+    /// `Err(Error::HostApiExcluded)` if it was excluded via
+    /// **PortAudioBuilder::allow_host_apis**.
+    pub fn host_api_devices<'a>(
+        &'a self,
+        host_api: HostApiIndex,
+    ) -> Result<HostApiDevices<'a>, Error> {
+        let info = self.host_api_info(host_api).ok_or(Error::InvalidHostApi)?;
+        if !self.life.allows_host_api(host_api) {
+            return Err(Error::HostApiExcluded);
+        }
+        Ok(HostApiDevices {
+            host_api: host_api,
+            total: info.device_count,
+            next: 0,
+            port_audio: self,
+        })
+    }
+
+    /// Retrieve the default device of the given host API for the given **Direction** along with
+    /// its **DeviceInfo**.
+    ///
+    /// Returns `Err(Error::InvalidHostApi)` if the `host_api` parameter is out of range.
+    ///
+    /// Returns `Err(Error::NoDevice)` if the host API has no default device for the direction.
+    pub fn default_device_for_host_api<'a>(
+        &'a self,
+        host_api: HostApiIndex,
+        direction: Direction,
+    ) -> Result<(DeviceIndex, DeviceInfo<'a>), Error> {
+        let info = self.host_api_info(host_api).ok_or(Error::InvalidHostApi)?;
+        let device = info.default_device(direction).ok_or(Error::NoDevice)?;
+        Ok((device, self.device_info(device)?))
+    }
+
+    /// Retrieve the default input device of the given host API along with its **DeviceInfo**.
+    ///
+    /// Returns `Err(Error::InvalidHostApi)` if the `host_api` parameter is out of range.
+    ///
+    /// Returns `Err(Error::NoDevice)` if the host API has no default input device.
+    pub fn default_input_device_for_host_api<'a>(
+        &'a self,
+        host_api: HostApiIndex,
+    ) -> Result<(DeviceIndex, DeviceInfo<'a>), Error> {
+        self.default_device_for_host_api(host_api, Direction::Input)
+    }
+
+    /// Retrieve the default output device of the given host API along with its **DeviceInfo**.
+    ///
+    /// Returns `Err(Error::InvalidHostApi)` if the `host_api` parameter is out of range.
+    ///
+    /// Returns `Err(Error::NoDevice)` if the host API has no default output device.
+    pub fn default_output_device_for_host_api<'a>(
+        &'a self,
+        host_api: HostApiIndex,
+    ) -> Result<(DeviceIndex, DeviceInfo<'a>), Error> {
+        self.default_device_for_host_api(host_api, Direction::Output)
+    }
+
     /// Convert a static host API unique identifier, into a runtime host API index.
     ///
     /// # Arguments
@@ -303,15 +895,36 @@ impl PortAudio {
     /// Return a valid `HostApiIndex` ranging from 0 to (get_host_api_count()-1) or an `Error` if
     /// an error is encountered.
     ///
+    /// Returns `Err(Error::HostApiExcluded)` if the host API was excluded via
+    /// **PortAudioBuilder::allow_host_apis**.
+    ///
     /// TODO: Determine exactly what errors might occur (PA docs aren't clear on this).
     pub fn host_api_type_id_to_host_api_index(
         &self,
         type_id: HostApiTypeId,
     ) -> Result<HostApiIndex, Error> {
-        let id = FromPrimitive::from_i32(type_id as i32).unwrap();
+        self.check_initialized()?;
+        if let Some(ref allowed) = self.life.allowed_host_apis {
+            if !allowed.contains(&type_id) {
+                return Err(Error::HostApiExcluded);
+            }
+        }
+        let id = type_id as ffi::PaHostApiTypeId;
         unsafe { result_from_host_api_index(ffi::Pa_HostApiTypeIdToHostApiIndex(id)) }
     }
 
+    /// Find the runtime **HostApiIndex** of the host API with the given type, e.g.
+    /// `HostApiTypeId::ASIO`.
+    ///
+    /// Host API indices depend on which host APIs PortAudio was built with, whereas the
+    /// **HostApiTypeId** of each is fixed, so this is the portable way of selecting a particular
+    /// host API. The reverse lookup is provided by **HostApiInfo::type_id**.
+    ///
+    /// Returns `Err(Error::HostApiNotFound)` if PortAudio was built without the host API.
+    pub fn host_api_type_id_to_index(&self, type_id: HostApiTypeId) -> Result<HostApiIndex, Error> {
+        self.host_api_type_id_to_host_api_index(type_id)
+    }
+
     /// Convert a host-API-specific device index to standard PortAudio device index.
     ///
     /// This function may be used in conjunction with the `device_count` field of `HostApiInfo` to
@@ -332,14 +945,32 @@ impl PortAudio {
         host_api: HostApiIndex,
         host_api_device_index: i32,
     ) -> Result<DeviceIndex, Error> {
+        self.check_initialized()?;
         let result =
             unsafe { ffi::Pa_HostApiDeviceIndexToDeviceIndex(host_api, host_api_device_index) };
         match result {
             idx if idx >= 0 => Ok(DeviceIndex(idx as u32)),
-            err => Err(::num::FromPrimitive::from_i32(err).unwrap()),
+            err => Err(Error::from_code(err)),
         }
     }
 
+    /// Determine whether it would be possible to open a stream in the given **Direction** only,
+    /// with the specified parameters, as by **PortAudio::is_input_format_supported** or
+    /// **PortAudio::is_output_format_supported**.
+    pub fn is_format_supported<S>(
+        &self,
+        direction: Direction,
+        params: StreamParameters<S>,
+        sample_rate: f64,
+    ) -> Result<(), Error>
+    where
+        S: Sample,
+    {
+        self.check_initialized()?;
+        let (input, output) = one_direction(direction, params.into());
+        is_format_supported(input, output, sample_rate)
+    }
+
     /// Determine whether it would be possible to open an input-only stream with the specified
     /// parameters.
     ///
@@ -355,7 +986,7 @@ impl PortAudio {
     where
         I: Sample,
     {
-        is_format_supported(Some(params.into()), None, sample_rate)
+        self.is_format_supported(Direction::Input, params, sample_rate)
     }
 
     /// Determine whether it would be possible to open an output-only stream with the specified
@@ -373,7 +1004,7 @@ impl PortAudio {
     where
         O: Sample,
     {
-        is_format_supported(None, Some(params.into()), sample_rate)
+        self.is_format_supported(Direction::Output, params, sample_rate)
     }
 
     /// Determine whether it would be possible to open a duplex stream with the specified
@@ -393,9 +1024,193 @@ impl PortAudio {
         I: Sample,
         O: Sample,
     {
+        self.check_initialized()?;
         is_format_supported(Some(in_params.into()), Some(out_params.into()), sample_rate)
     }
 
+    /// The same as **PortAudio::is_format_supported**, but in the case that the format is not
+    /// supported the returned **FormatSupportError** also describes which of the parameters is
+    /// responsible, where that can be determined.
+    pub fn check_format<S>(
+        &self,
+        direction: Direction,
+        params: StreamParameters<S>,
+        sample_rate: f64,
+    ) -> Result<(), FormatSupportError>
+    where
+        S: Sample,
+    {
+        self.check_initialized()?;
+        let (input, output) = one_direction(direction, params.into());
+        check_format(input, output, sample_rate)
+    }
+
+    /// The same as **PortAudio::is_input_format_supported**, but in the case that the format is
+    /// not supported the returned **FormatSupportError** also describes which of the parameters
+    /// is responsible, where that can be determined.
+    pub fn check_input_format<I>(
+        &self,
+        params: StreamParameters<I>,
+        sample_rate: f64,
+    ) -> Result<(), FormatSupportError>
+    where
+        I: Sample,
+    {
+        self.check_format(Direction::Input, params, sample_rate)
+    }
+
+    /// The same as **PortAudio::is_output_format_supported**, but in the case that the format is
+    /// not supported the returned **FormatSupportError** also describes which of the parameters
+    /// is responsible, where that can be determined.
+    pub fn check_output_format<O>(
+        &self,
+        params: StreamParameters<O>,
+        sample_rate: f64,
+    ) -> Result<(), FormatSupportError>
+    where
+        O: Sample,
+    {
+        self.check_format(Direction::Output, params, sample_rate)
+    }
+
+    /// The same as **PortAudio::is_duplex_format_supported**, but in the case that the format is
+    /// not supported the returned **FormatSupportError** also describes which of the parameters
+    /// is responsible, where that can be determined.
+    pub fn check_duplex_format<I, O>(
+        &self,
+        in_params: StreamParameters<I>,
+        out_params: StreamParameters<O>,
+        sample_rate: f64,
+    ) -> Result<(), FormatSupportError>
+    where
+        I: Sample,
+        O: Sample,
+    {
+        self.check_initialized()?;
+        check_format(Some(in_params.into()), Some(out_params.into()), sample_rate)
+    }
+
+    /// The **STANDARD_SAMPLE_RATES** at which the given device can run with the given number of
+    /// channels and **SampleFormat**, e.g. to offer a choice of sample rates to the user.
+    ///
+    /// PortAudio has no way of listing the sample rates that a device supports, so each rate is
+    /// probed via `Pa_IsFormatSupported`. A rate is included if the device supports it for either
+    /// input or output. An invalid device produces an empty list.
+    pub fn supported_sample_rates(
+        &self,
+        device: DeviceIndex,
+        channels: i32,
+        format: SampleFormat,
+    ) -> Vec<f64> {
+        self.supported_sample_rates_of(device, channels, format, &STANDARD_SAMPLE_RATES)
+    }
+
+    /// The same as **PortAudio::supported_sample_rates**, but probes the given `sample_rates`
+    /// instead of the **STANDARD_SAMPLE_RATES**.
+    pub fn supported_sample_rates_of(
+        &self,
+        device: DeviceIndex,
+        channels: i32,
+        format: SampleFormat,
+        sample_rates: &[f64],
+    ) -> Vec<f64> {
+        let info = match self.device_info(device) {
+            Ok(info) => info,
+            Err(_) => return Vec::new(),
+        };
+        let params: ffi::PaStreamParameters =
+            StreamParameters::raw(device, channels, format, true, 0.0).into();
+        let input = if channels <= info.max_input_channels {
+            Some(params)
+        } else {
+            None
+        };
+        let output = if channels <= info.max_output_channels {
+            Some(params)
+        } else {
+            None
+        };
+        sample_rates
+            .iter()
+            .cloned()
+            .filter(|&rate| {
+                input.is_some_and(|p| is_format_supported(Some(p), None, rate).is_ok())
+                    || output.is_some_and(|p| is_format_supported(None, Some(p), rate).is_ok())
+            })
+            .collect()
+    }
+
+    /// Find a sample rate at which both the `input` device can record and the `output` device
+    /// can play the given number of channels, e.g. to configure a duplex stream across two
+    /// devices.
+    ///
+    /// The **STANDARD_SAMPLE_RATES** and the two devices' default sample rates are probed for
+    /// each direction, as by **PortAudio::supported_sample_rates**, using `f32` samples. Of the
+    /// rates that both directions support, 48 kHz is chosen when available and the highest one
+    /// otherwise.
+    ///
+    /// Returns `Err(Error::NoCommonSampleRate)` if the devices don't share any of the probed
+    /// rates, and `Err(Error::InvalidDevice)` if either device doesn't exist.
+    pub fn default_sample_rate_for(
+        &self,
+        input: DeviceIndex,
+        output: DeviceIndex,
+        channels: i32,
+    ) -> Result<f64, Error> {
+        const PREFERRED_SAMPLE_RATE: f64 = 48_000.0;
+        const INTERLEAVED: bool = true;
+        let input_default = self.device_info(input)?.default_sample_rate;
+        let output_default = self.device_info(output)?.default_sample_rate;
+        let mut candidates = STANDARD_SAMPLE_RATES.to_vec();
+        for &rate in &[input_default, output_default] {
+            if !candidates.contains(&rate) {
+                candidates.push(rate);
+            }
+        }
+
+        let in_params =
+            StreamParameters::raw(input, channels, SampleFormat::F32, INTERLEAVED, 0.0).into();
+        let out_params =
+            StreamParameters::raw(output, channels, SampleFormat::F32, INTERLEAVED, 0.0).into();
+        let common: Vec<f64> = candidates
+            .into_iter()
+            .filter(|&rate| {
+                is_format_supported(Some(in_params), None, rate).is_ok()
+                    && is_format_supported(None, Some(out_params), rate).is_ok()
+            })
+            .collect();
+        if common.contains(&PREFERRED_SAMPLE_RATE) {
+            return Ok(PREFERRED_SAMPLE_RATE);
+        }
+        common
+            .into_iter()
+            .fold(None, |max: Option<f64>, rate| {
+                Some(max.map_or(rate, |max| max.max(rate)))
+            })
+            .ok_or(Error::NoCommonSampleRate)
+    }
+
+    /// Measure the round-trip latency from the `output` device to the `input` device, e.g. of an
+    /// audio interface whose output is patched back into its input with a loopback cable.
+    ///
+    /// A short chirp is played on the first output channel through a blocking duplex stream at
+    /// the devices' default low latencies, while the first input channel is recorded for about a
+    /// second. The delay at which the recording best correlates with the chirp is the round-trip
+    /// latency, which includes the buffering of PortAudio and the host API in both directions as
+    /// well as the converters of the interface itself.
+    ///
+    /// Returns `Err(Error::NoLoopbackDetected)` if the chirp isn't found in the recording, e.g.
+    /// because nothing is connected or the input is muted. Any xrun during the measurement is
+    /// returned as an error, as it would skew the result, so the measurement may be retried.
+    pub fn measure_roundtrip_latency(
+        &self,
+        input: DeviceIndex,
+        output: DeviceIndex,
+        sample_rate: f64,
+    ) -> Result<::std::time::Duration, Error> {
+        latency::measure_roundtrip_latency(self, input, output, sample_rate)
+    }
+
     /// Open a new blocking [**Stream**](./stream/struct.Stream.html) with the given settings.
     ///
     /// The **Stream** will be opened in **Blocking** "read/write" mode.
@@ -415,6 +1230,7 @@ impl PortAudio {
         S: StreamSettings,
         S::Flow: Flow,
     {
+        self.check_initialized()?;
         Stream::<Blocking<<S::Flow as Flow>::Buffer>, S::Flow>::open(self.life.clone(), settings)
     }
 
@@ -451,6 +1267,11 @@ impl PortAudio {
     /// stop the **Stream**. **Output** stream `callback`s must always fill the entire buffer
     /// irrespective of its return value.
     ///
+    /// The `callback` may own any state that it needs between calls, such as a phase
+    /// accumulator or the writing half of a **RingBuffer**. It is stored within the returned
+    /// **Stream** and dropped along with it. As it is called from PortAudio's audio thread, it
+    /// must be `Send`.
+    ///
     /// The returned **Stream** is inactive (stopped).
     pub fn open_non_blocking_stream<S, C>(
         &self,
@@ -460,23 +1281,215 @@ impl PortAudio {
     where
         S: StreamSettings,
         S::Flow: Flow,
-        C: FnMut(<S::Flow as Flow>::CallbackArgs) -> ffi::PaStreamCallbackResult + 'static,
+        C: FnMut(<S::Flow as Flow>::CallbackArgs) -> ffi::PaStreamCallbackResult + Send + 'static,
     {
+        self.check_initialized()?;
         Stream::<NonBlocking, S::Flow>::open(self.life.clone(), settings, callback)
     }
 
-    /// Produce the default **StreamParameters** for an **Input** **Stream**.
+    /// Open a non-blocking **Duplex** **Stream**, first checking that its input and output can run
+    /// at the requested sample rate together.
+    ///
+    /// This is the same as **PortAudio::open_non_blocking_stream** but, where PortAudio would
+    /// return a generic error, returns `Err(Error::DuplexRateMismatch)` describing the rates
+    /// supported by each direction if either doesn't support the requested rate.
+    ///
+    /// **Note:** if the input and output are different devices, such as a USB microphone and the
+    /// built-in speakers, each runs on its own clock. PortAudio does not compensate for the
+    /// difference, so the two gradually drift apart even when their nominal sample rates match,
+    /// which eventually causes input overflows or output underflows. Use a single duplex-capable
+    /// device wherever timing matters, or else open a stream for each device and pass the audio
+    /// between them through an **AdaptiveResampler**.
+    pub fn open_duplex_stream<I, O, C>(
+        &self,
+        settings: DuplexStreamSettings<I, O>,
+        callback: C,
+    ) -> Result<Stream<NonBlocking, Duplex<I, O>>, Error>
+    where
+        I: Sample + 'static,
+        O: Sample + 'static,
+        C: FnMut(DuplexStreamCallbackArgs<'static, I, O>) -> ffi::PaStreamCallbackResult
+            + Send
+            + 'static,
+    {
+        let rate = settings.sample_rate;
+        let (in_params, out_params) = (settings.in_params, settings.out_params);
+        if let Err(err) = self.is_duplex_format_supported(in_params, out_params, rate) {
+            let in_rate = match self.is_input_format_supported(in_params, rate) {
+                Ok(()) => Some(rate),
+                Err(_) => self.params_default_sample_rate(&in_params),
+            };
+            let out_rate = match self.is_output_format_supported(out_params, rate) {
+                Ok(()) => Some(rate),
+                Err(_) => self.params_default_sample_rate(&out_params),
+            };
+            return match (in_rate, out_rate) {
+                (Some(input), Some(output)) if input != rate || output != rate => {
+                    Err(Error::DuplexRateMismatch {
+                        input: input,
+                        output: output,
+                    })
+                }
+                _ => Err(err),
+            };
+        }
+        self.open_non_blocking_stream(settings, callback)
+    }
+
+    /// The default sample rate of the device of the given parameters, if it has one.
+    fn params_default_sample_rate<S>(&self, params: &StreamParameters<S>) -> Option<f64> {
+        match params.device {
+            DeviceKind::Index(device) => self
+                .device_info(device)
+                .ok()
+                .map(|info| info.default_sample_rate),
+            DeviceKind::UseHostApiSpecificDeviceSpecification => None,
+        }
+    }
+
+    /// Open a non-blocking **Input** **Stream** that captures the audio being played by the given
+    /// WASAPI output device, e.g. to record "what's playing" on Windows.
     ///
-    /// The device used will be the default input device for the default Host API.
+    /// PortAudio's WASAPI host API lists a loopback input device for each output device, named
+    /// after the output device with a " [Loopback]" suffix. The loopback device of
+    /// `output_device` is found and used in place of the device in the `settings`' params. Its
+    /// channel count and sample rate generally match those of the output device's shared mode
+    /// format. The `callback` is as described for **PortAudio::open_non_blocking_stream**.
+    ///
+    /// Returns `Err(Error::HostApiNotFound)` if PortAudio was built without WASAPI, which is
+    /// always the case on platforms other than Windows.
+    ///
+    /// Returns `Err(Error::InvalidDevice)` if `output_device` is not a WASAPI output device.
+    ///
+    /// Returns `Err(Error::DeviceUnavailable)` if no loopback device exists for `output_device`,
+    /// e.g. because the PortAudio release predates loopback support.
+    pub fn open_wasapi_loopback_stream<I, C>(
+        &self,
+        output_device: DeviceIndex,
+        settings: InputStreamSettings<I>,
+        callback: C,
+    ) -> Result<Stream<NonBlocking, Input<I>>, Error>
+    where
+        I: Sample + 'static,
+        C: FnMut(InputStreamCallbackArgs<'static, I>) -> ffi::PaStreamCallbackResult
+            + Send
+            + 'static,
+    {
+        let wasapi = self.host_api_type_id_to_index(HostApiTypeId::WASAPI)?;
+        let output_info = self.device_info(output_device)?;
+        if output_info.host_api != wasapi || output_info.max_output_channels <= 0 {
+            return Err(Error::InvalidDevice);
+        }
+        let loopback_name = format!("{} [Loopback]", output_info.name);
+        let mut loopback_device = None;
+        for device in self.host_api_devices(wasapi)? {
+            let (idx, info) = device?;
+            if info.max_input_channels > 0 && info.name == loopback_name {
+                loopback_device = Some(idx);
+                break;
+            }
+        }
+        let loopback_device = loopback_device.ok_or(Error::DeviceUnavailable)?;
+        let mut settings = settings;
+        settings.params.device = loopback_device.into();
+        self.open_non_blocking_stream(settings, callback)
+    }
+
+    /// Record the input of the given device to a WAV file at `path` for the given `duration`,
+    /// replacing any existing file.
+    ///
+    /// A blocking input stream is opened at the device's default sample rate and high input
+    /// latency, with the device's channels up to a maximum of two. The file's bit depth follows
+    /// the sample format of `S`, e.g. `record_to_wav::<i16, _>` writes a 16-bit file and
+    /// `record_to_wav::<f32, _>` a 32-bit float file.
+    ///
+    /// If the input overflows, the affected block is dropped and recording continues. The
+    /// dropped frames count towards the `duration` and are reported in the returned
+    /// **wav::Recording**.
+    ///
+    /// Only available with the `wav` feature.
+    #[cfg(feature = "wav")]
+    pub fn record_to_wav<S, P>(
+        &self,
+        device: DeviceIndex,
+        duration: ::std::time::Duration,
+        path: P,
+    ) -> Result<wav::Recording, wav::WavError>
+    where
+        S: Sample + 'static,
+        P: AsRef<::std::path::Path>,
+    {
+        wav::record_to_wav::<S>(self, device, duration, path.as_ref())
+    }
+
+    /// Play the WAV file at `path` on the given output device, returning once it has played to
+    /// the end.
+    ///
+    /// The file is streamed from a buffered reader by a non-blocking stream callback. The stream
+    /// is opened with the file's channels, sample rate and sample format, at the device's high
+    /// output latency. 8, 16, 24 and 32-bit integer and 32-bit float files are supported.
+    ///
+    /// Files aren't resampled, so if the device doesn't support the file's sample rate (or
+    /// channel count) this returns **wav::WavError::Format** describing which it rejected.
+    ///
+    /// Only available with the `wav` feature.
+    #[cfg(feature = "wav")]
+    pub fn play_wav<P>(&self, path: P, device: DeviceIndex) -> Result<(), wav::WavError>
+    where
+        P: AsRef<::std::path::Path>,
+    {
+        wav::play_wav(self, path.as_ref(), device)
+    }
+
+    /// Start playing the WAV file at `path` on the given output device in the background,
+    /// returning a **wav::PlaybackHandle** to follow its progress, seek or stop it.
+    ///
+    /// The file is played as by **PortAudio::play_wav**, which is the same as calling
+    /// **wav::PlaybackHandle::wait** on the returned handle straight away.
+    ///
+    /// Only available with the `wav` feature.
+    #[cfg(feature = "wav")]
+    pub fn start_wav_playback<P>(
+        &self,
+        path: P,
+        device: DeviceIndex,
+    ) -> Result<wav::PlaybackHandle, wav::WavError>
+    where
+        P: AsRef<::std::path::Path>,
+    {
+        wav::start_wav_playback(self, path.as_ref(), device)
+    }
+
+    /// The same as **PortAudio::play_wav**, but writing the file to a blocking stream from the
+    /// calling thread rather than reading it from the stream callback.
+    ///
+    /// Only available with the `wav` feature.
+    #[cfg(feature = "wav")]
+    pub fn play_wav_blocking<P>(&self, path: P, device: DeviceIndex) -> Result<(), wav::WavError>
+    where
+        P: AsRef<::std::path::Path>,
+    {
+        wav::play_wav_blocking(self, path.as_ref(), device)
+    }
+
+    /// Produce the default **StreamParameters** for a **Stream** in the given **Direction**.
+    ///
+    /// The device used will be the default device of that direction for the default Host API,
+    /// along with its default low latency.
     ///
     /// The produced **Parameters** will assume interleaved buffered audio data.
-    pub fn default_input_stream_params<I>(
+    pub fn default_stream_params<S>(
         &self,
+        direction: Direction,
         channels: i32,
-    ) -> Result<StreamParameters<I>, Error> {
+    ) -> Result<StreamParameters<S>, Error> {
         const INTERLEAVED: bool = true;
-        let device = self.default_input_device()?;
-        let latency = self.device_info(device)?.default_low_input_latency;
+        let device = self.default_device(direction)?;
+        let info = self.device_info(device)?;
+        let latency = match direction {
+            Direction::Input => info.default_low_input_latency,
+            Direction::Output => info.default_low_output_latency,
+        };
         Ok(StreamParameters::new(
             device,
             channels,
@@ -485,6 +1498,18 @@ impl PortAudio {
         ))
     }
 
+    /// Produce the default **StreamParameters** for an **Input** **Stream**.
+    ///
+    /// The device used will be the default input device for the default Host API.
+    ///
+    /// The produced **Parameters** will assume interleaved buffered audio data.
+    pub fn default_input_stream_params<I>(
+        &self,
+        channels: i32,
+    ) -> Result<StreamParameters<I>, Error> {
+        self.default_stream_params(Direction::Input, channels)
+    }
+
     /// Produce the default **StreamParameters** for an **Output** **Stream**.
     ///
     /// The device used will be the default output device for the default Host API.
@@ -494,15 +1519,7 @@ impl PortAudio {
         &self,
         channels: i32,
     ) -> Result<StreamParameters<O>, Error> {
-        const INTERLEAVED: bool = true;
-        let device = self.default_output_device()?;
-        let latency = self.device_info(device)?.default_low_output_latency;
-        Ok(StreamParameters::new(
-            device,
-            channels,
-            INTERLEAVED,
-            latency,
-        ))
+        self.default_stream_params(Direction::Output, channels)
     }
 
     /// Produce the default **InputStreamSettings** with the given number of channels, sample_rate
@@ -545,6 +1562,51 @@ impl PortAudio {
         ))
     }
 
+    /// Produce **OutputStreamSettings** for the given device that aim for the lowest latency at
+    /// which the device is still expected to play without underflowing.
+    ///
+    /// The frames per buffer are derived from the device's **default_low_output_latency**: the
+    /// number of frames spanning that latency at `sample_rate` is rounded up to the next power of
+    /// two, within the range of 16 to 8192 frames. The same latency is suggested for the stream.
+    ///
+    /// This is a starting point rather than a guarantee, as the latencies that a device reports
+    /// are only estimates. If the stream still underflows, fall back to
+    /// **suggest_high_latency_settings**.
+    pub fn suggest_low_latency_settings<O>(
+        &self,
+        device: DeviceIndex,
+        channels: i32,
+        sample_rate: f64,
+    ) -> Result<OutputStreamSettings<O>, Error> {
+        let latency = self.device_info(device)?.default_low_output_latency;
+        Ok(latency_output_settings(
+            device,
+            channels,
+            sample_rate,
+            latency,
+        ))
+    }
+
+    /// Produce **OutputStreamSettings** for the given device that favour robustness over latency,
+    /// e.g. for playing back files.
+    ///
+    /// Like **suggest_low_latency_settings**, but derived from the device's
+    /// **default_high_output_latency**.
+    pub fn suggest_high_latency_settings<O>(
+        &self,
+        device: DeviceIndex,
+        channels: i32,
+        sample_rate: f64,
+    ) -> Result<OutputStreamSettings<O>, Error> {
+        let latency = self.device_info(device)?.default_high_output_latency;
+        Ok(latency_output_settings(
+            device,
+            channels,
+            sample_rate,
+            latency,
+        ))
+    }
+
     /// Produce the default **DuplexStreamSettings** with the given number of channels, sample_rate
     /// and frames per buffer.
     ///
@@ -568,6 +1630,98 @@ impl PortAudio {
         ))
     }
 
+    /// Open a non-blocking `f32` output **Stream** on the default output device without choosing
+    /// any settings, much like PortAudio's `Pa_OpenDefaultStream`.
+    ///
+    /// The stream has two interleaved channels, or one if the device is mono, and runs at the
+    /// device's default sample rate and low latency. The frames per buffer are left to the host
+    /// API via **FRAMES_PER_BUFFER_UNSPECIFIED**, so the number of `frames` passed to the
+    /// `callback` may vary. The `callback` is as described for
+    /// **PortAudio::open_non_blocking_stream**.
+    ///
+    /// The channel count is `buffer.len() / frames` within the callback, and the sample rate is
+    /// given by **Stream::info**. The returned **Stream** is inactive (stopped).
+    pub fn open_default_output_stream<C>(
+        &self,
+        callback: C,
+    ) -> Result<Stream<NonBlocking, Output<f32>>, Error>
+    where
+        C: FnMut(OutputStreamCallbackArgs<'static, f32>) -> ffi::PaStreamCallbackResult
+            + Send
+            + 'static,
+    {
+        let device = self.default_output_device()?;
+        let info = self.device_info(device)?;
+        let channels = std::cmp::min(DEFAULT_STREAM_CHANNELS, info.max_output_channels);
+        let latency = info.default_low_output_latency;
+        let params = StreamParameters::new(device, channels, true, latency);
+        let sample_rate = info.default_sample_rate;
+        let settings =
+            OutputStreamSettings::new(params, sample_rate, FRAMES_PER_BUFFER_UNSPECIFIED);
+        self.open_non_blocking_stream(settings, callback)
+    }
+
+    /// Open a non-blocking `f32` input **Stream** on the default input device without choosing
+    /// any settings, with the same choices as **PortAudio::open_default_output_stream**.
+    pub fn open_default_input_stream<C>(
+        &self,
+        callback: C,
+    ) -> Result<Stream<NonBlocking, Input<f32>>, Error>
+    where
+        C: FnMut(InputStreamCallbackArgs<'static, f32>) -> ffi::PaStreamCallbackResult
+            + Send
+            + 'static,
+    {
+        let device = self.default_input_device()?;
+        let info = self.device_info(device)?;
+        let channels = std::cmp::min(DEFAULT_STREAM_CHANNELS, info.max_input_channels);
+        let latency = info.default_low_input_latency;
+        let params = StreamParameters::new(device, channels, true, latency);
+        let sample_rate = info.default_sample_rate;
+        let settings = InputStreamSettings::new(params, sample_rate, FRAMES_PER_BUFFER_UNSPECIFIED);
+        self.open_non_blocking_stream(settings, callback)
+    }
+
+    /// Open a non-blocking **Stream** on the default input and/or output devices by calling
+    /// PortAudio's `Pa_OpenDefaultStream` directly.
+    ///
+    /// Either channel count may be `0` for an output-only or input-only stream, in which case
+    /// the matching buffers of the **DuplexStreamCallbackArgs** given to the `callback` are
+    /// empty. The samples are interleaved and the `callback` is as described for
+    /// **PortAudio::open_non_blocking_stream**.
+    ///
+    /// Unlike the settings-based API, e.g. **PortAudio::open_default_output_stream**, no
+    /// **DeviceInfo** is looked up to choose the parameters. PortAudio picks the default devices
+    /// and their default low latencies itself, leaving any default device edge cases to the host
+    /// API implementation. The channel counts and sample rate are passed through unchecked, so an
+    /// unsupported value is only reported by PortAudio's error.
+    ///
+    /// The returned **Stream** is inactive (stopped).
+    pub fn open_default_stream_raw<S, C>(
+        &self,
+        num_input_channels: i32,
+        num_output_channels: i32,
+        sample_rate: f64,
+        frames_per_buffer: u32,
+        callback: C,
+    ) -> Result<Stream<NonBlocking, DefaultDuplex<S>>, Error>
+    where
+        S: Sample + 'static,
+        C: FnMut(DuplexStreamCallbackArgs<'static, S, S>) -> ffi::PaStreamCallbackResult
+            + Send
+            + 'static,
+    {
+        self.check_initialized()?;
+        Stream::<NonBlocking, DefaultDuplex<S>>::open_default(
+            self.life.clone(),
+            num_input_channels,
+            num_output_channels,
+            sample_rate,
+            frames_per_buffer,
+            callback,
+        )
+    }
+
     /// Put the caller to sleep for at least 'msec' milliseconds.
     ///
     /// In the original API this function is provided only as a convenience for authors of portable
@@ -577,7 +1731,7 @@ impl PortAudio {
     /// The function may sleep longer than requested so don't rely on this for accurate musical
     /// timing.
     pub fn sleep(&self, m_sec: i32) -> () {
-        unsafe { ffi::Pa_Sleep(m_sec as raw::c_long) }
+        sleep(m_sec)
     }
 
     /// Return information about the last host error encountered.
@@ -589,23 +1743,39 @@ impl PortAudio {
     /// This method is provided as a last resort, primarily to enhance debugging by providing
     /// clients with access to all available error information.
     ///
-    /// Return a pointer to an immutable structure constraining information about the host error.
-    /// The values in this structure will only be valid if a PortAudio function or method has
-    /// previously returned the UnanticipatedHostError error code.
-    pub fn last_host_error_info<'a>(&'a self) -> HostErrorInfo<'a> {
+    /// Returns a copy of the information about the last host error. The values will only be
+    /// meaningful if a PortAudio function or method has previously returned the
+    /// UnanticipatedHostError error code.
+    pub fn last_host_error_info(&self) -> HostErrorInfo {
         let c_error = unsafe { ffi::Pa_GetLastHostErrorInfo() };
         HostErrorInfo::from_c_error_info(unsafe { *c_error })
     }
 }
 
+/// The number of channels opened by **PortAudio::open_default_output_stream** and
+/// **PortAudio::open_default_input_stream**, when the device has that many.
+const DEFAULT_STREAM_CHANNELS: i32 = 2;
+
 impl Drop for Life {
+    /// A best-effort fallback for when **PortAudio::terminate** wasn't called. There's no way to
+    /// return an error from here, so it's only logged (with the `log` feature).
     fn drop(&mut self) {
         if !*self.is_terminated.lock().unwrap() {
-            terminate().ok();
+            if let Err(_err) = terminate() {
+                log_event!(warn, "failed to terminate PortAudio on drop: {}", _err);
+            }
         }
     }
 }
 
+/// Put the caller to sleep for at least 'msec' milliseconds.
+///
+/// This is the same as **PortAudio::sleep**, but may be called without an instance as PortAudio
+/// doesn't need to be initialised to sleep.
+pub fn sleep(m_sec: i32) {
+    unsafe { ffi::Pa_Sleep(m_sec as raw::c_long) }
+}
+
 /// Retrieve the release number of the currently running PortAudio build.
 pub fn version() -> i32 {
     unsafe { ffi::Pa_GetVersion() }
@@ -616,6 +1786,20 @@ pub fn version_text() -> Result<&'static str, ::std::str::Utf8Error> {
     unsafe { ffi::c_str_to_str(ffi::Pa_GetVersionText()) }
 }
 
+/// Retrieve the major, minor and sub-minor version numbers along with the version control revision
+/// of the currently running PortAudio build.
+///
+/// `Pa_GetVersionInfo` is linked like any other PortAudio function, so this crate needs a release
+/// that provides it (19.5.0 or later) regardless. Should it return null, the version is parsed
+/// from the version text instead, and `None` is returned if that fails too.
+pub fn version_info() -> Option<VersionInfo> {
+    let c_info = unsafe { ffi::Pa_GetVersionInfo() };
+    if !c_info.is_null() {
+        return Some(VersionInfo::from_c_info(unsafe { *c_info }));
+    }
+    version_text().ok().and_then(VersionInfo::from_version_text)
+}
+
 /// This is used by the **PortAudio::terminate** method.
 ///
 /// Library termination function - call this when finished using PortAudio.
@@ -636,7 +1820,7 @@ pub fn version_text() -> Result<&'static str, ::std::str::Utf8Error> {
 /// Return NoError if successful, otherwise an error code indicating the cause of failure.
 fn terminate() -> Result<(), Error> {
     unsafe {
-        let error = FromPrimitive::from_i32(ffi::Pa_Terminate()).unwrap();
+        let error = Error::from_code(ffi::Pa_Terminate());
         match error {
             Error::NoError => Ok(()),
             err => Err(err),
@@ -644,6 +1828,66 @@ fn terminate() -> Result<(), Error> {
     }
 }
 
+/// Whether **channel_name** supports the devices of the given host API.
+fn has_channel_names(host_api: HostApiTypeId) -> bool {
+    match host_api {
+        #[cfg(target_os = "macos")]
+        HostApiTypeId::CoreAudio => true,
+        #[cfg(all(windows, feature = "asio"))]
+        HostApiTypeId::ASIO => true,
+        _ => false,
+    }
+}
+
+/// The name of a channel of a CoreAudio device.
+#[cfg(target_os = "macos")]
+fn channel_name(device: DeviceIndex, channel: i32, direction: Direction) -> Option<String> {
+    let input = direction == Direction::Input;
+    let name = unsafe { ffi::PaMacCore_GetChannelName(device.0 as raw::c_int, channel, input) };
+    if name.is_null() {
+        return None;
+    }
+    ffi::c_str_to_str(name).ok().map(|name| name.to_string())
+}
+
+/// The name of a channel of an ASIO device.
+#[cfg(all(windows, feature = "asio"))]
+fn channel_name(device: DeviceIndex, channel: i32, direction: Direction) -> Option<String> {
+    let mut name = ptr::null();
+    let code = unsafe {
+        match direction {
+            Direction::Input => ffi::PaAsio_GetInputChannelName(device.into(), channel, &mut name),
+            Direction::Output => {
+                ffi::PaAsio_GetOutputChannelName(device.into(), channel, &mut name)
+            }
+        }
+    };
+    if Error::from_code(code) != Error::NoError || name.is_null() {
+        return None;
+    }
+    ffi::c_str_to_str(name).ok().map(|name| name.to_string())
+}
+
+/// No other platform's host APIs name their channels.
+#[cfg(not(any(target_os = "macos", all(windows, feature = "asio"))))]
+fn channel_name(_device: DeviceIndex, _channel: i32, _direction: Direction) -> Option<String> {
+    None
+}
+
+/// Place the parameters of a single-direction stream on the side of the given **Direction**.
+fn one_direction(
+    direction: Direction,
+    params: ffi::PaStreamParameters,
+) -> (
+    Option<ffi::PaStreamParameters>,
+    Option<ffi::PaStreamParameters>,
+) {
+    match direction {
+        Direction::Input => (Some(params), None),
+        Direction::Output => (None, Some(params)),
+    }
+}
+
 /// This is used by the **PortAudio::is_*_format_supported** methods.
 ///
 /// Determine whether it would be possible to open a stream with the specified parameters.
@@ -681,7 +1925,7 @@ fn is_format_supported(
                 c_output.unwrap_or(ptr::null()),
                 sample_rate as raw::c_double,
             );
-            let error = FromPrimitive::from_i32(error_code).unwrap();
+            let error = Error::from_code(error_code);
             match error {
                 Error::NoError => Ok(()),
                 err => Err(err),
@@ -690,8 +1934,92 @@ fn is_format_supported(
     }
 }
 
+/// This is used by the **PortAudio::check_*_format** methods.
+///
+/// Checks the format via **is_format_supported** and, if it is not supported, probes it again
+/// with the channel counts clamped to each device's maximum and at the devices' default sample
+/// rates in order to find the cause.
+fn check_format(
+    input: Option<ffi::PaStreamParameters>,
+    output: Option<ffi::PaStreamParameters>,
+    sample_rate: f64,
+) -> Result<(), FormatSupportError> {
+    let error = match is_format_supported(input, output, sample_rate) {
+        Ok(()) => return Ok(()),
+        Err(err) => err,
+    };
+
+    let device_info = |params: &ffi::PaStreamParameters| unsafe {
+        if params.device < 0 {
+            None
+        } else {
+            ffi::Pa_GetDeviceInfo(params.device).as_ref().cloned()
+        }
+    };
+    let input_info = input.as_ref().and_then(&device_info);
+    let output_info = output.as_ref().and_then(&device_info);
+
+    // Clamp the requested channel counts to those supported by the devices.
+    let mut clamped_input = input;
+    let mut max_input_channels = None;
+    if let (Some(params), Some(info)) = (clamped_input.as_mut(), input_info.as_ref()) {
+        if params.channelCount > info.maxInputChannels && info.maxInputChannels > 0 {
+            params.channelCount = info.maxInputChannels;
+            max_input_channels = Some(info.maxInputChannels);
+        }
+    }
+    let mut clamped_output = output;
+    let mut max_output_channels = None;
+    if let (Some(params), Some(info)) = (clamped_output.as_mut(), output_info.as_ref()) {
+        if params.channelCount > info.maxOutputChannels && info.maxOutputChannels > 0 {
+            params.channelCount = info.maxOutputChannels;
+            max_output_channels = Some(info.maxOutputChannels);
+        }
+    }
+
+    // Check whether the default sample rate of either device would have been supported.
+    let default_sample_rate = output_info
+        .iter()
+        .chain(input_info.iter())
+        .map(|info| info.defaultSampleRate)
+        .filter(|&rate| rate != sample_rate)
+        .find(|&rate| is_format_supported(clamped_input, clamped_output, rate).is_ok());
+
+    Err(FormatSupportError {
+        error: error,
+        max_input_channels: max_input_channels,
+        max_output_channels: max_output_channels,
+        default_sample_rate: default_sample_rate,
+    })
+}
+
+/// The interleaved **OutputStreamSettings** with a power of two frames per buffer that spans at
+/// least the given latency, as produced by the **PortAudio::suggest_*_latency_settings** methods.
+fn latency_output_settings<O>(
+    device: DeviceIndex,
+    channels: i32,
+    sample_rate: f64,
+    latency: Time,
+) -> OutputStreamSettings<O> {
+    const INTERLEAVED: bool = true;
+    const MIN_FRAMES: u32 = 16;
+    const MAX_FRAMES: u32 = 8192;
+    let frames = (latency * sample_rate).ceil();
+    let frames = if frames.is_finite() && frames > 0.0 {
+        (frames.min(MAX_FRAMES as f64) as u32).next_power_of_two()
+    } else {
+        MIN_FRAMES
+    };
+    // MAX_FRAMES is a power of two itself, so rounding up can't exceed it.
+    let frames_per_buffer = frames.max(MIN_FRAMES);
+    let params = StreamParameters::new(device, channels, INTERLEAVED, latency);
+    OutputStreamSettings::new(params, sample_rate, frames_per_buffer)
+}
+
 /// An iterator yielding the **DeviceIndex** for each available device along with their respective
 /// **DeviceInfo**s.
+///
+/// The devices of host APIs excluded via **PortAudioBuilder::allow_host_apis** are skipped.
 pub struct Devices<'a> {
     total: u32,
     next: u32,
@@ -707,24 +2035,66 @@ pub struct HostApis<'a> {
     port_audio: &'a PortAudio,
 }
 
+/// An iterator yielding the **DeviceIndex** for each device of a single host API along with their
+/// respective **DeviceInfo**s.
+pub struct HostApiDevices<'a> {
+    host_api: HostApiIndex,
+    total: u32,
+    next: u32,
+    port_audio: &'a PortAudio,
+}
+
 impl<'a> Iterator for Devices<'a> {
     type Item = Result<(DeviceIndex, DeviceInfo<'a>), Error>;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.next < self.total {
+        while self.next < self.total {
             let idx = DeviceIndex(self.next);
             self.next += 1;
+            if !self
+                .port_audio
+                .life
+                .allows_device(idx.0 as ffi::PaDeviceIndex)
+            {
+                continue;
+            }
             return Some(self.port_audio.device_info(idx).map(|info| (idx, info)));
         }
         None
     }
 }
 
+impl<'a> Iterator for HostApiDevices<'a> {
+    type Item = Result<(DeviceIndex, DeviceInfo<'a>), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next < self.total {
+            let host_api_device_index = self.next as i32;
+            self.next += 1;
+            let port_audio = self.port_audio;
+            let result = port_audio
+                .api_device_index_to_device_index(self.host_api, host_api_device_index)
+                .and_then(|idx| port_audio.device_info(idx).map(|info| (idx, info)));
+            return Some(result);
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.total - self.next) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for HostApiDevices<'a> {}
+
 impl<'a> Iterator for HostApis<'a> {
     type Item = (HostApiIndex, HostApiInfo<'a>);
     fn next(&mut self) -> Option<Self::Item> {
         while self.next < self.total {
             let idx = self.next;
             self.next += 1;
+            if !self.port_audio.life.allows_host_api(idx) {
+                continue;
+            }
             if let Some(info) = self.port_audio.host_api_info(idx) {
                 return Some((idx, info));
             }
@@ -736,7 +2106,7 @@ impl<'a> Iterator for HostApis<'a> {
 fn result_from_host_api_index(idx: ffi::PaHostApiIndex) -> Result<HostApiIndex, Error> {
     match idx {
         idx if idx >= 0 => Ok(idx),
-        err => Err(::num::FromPrimitive::from_i32(err).unwrap()),
+        err => Err(Error::from_code(err)),
     }
 }
 
@@ -744,17 +2114,19 @@ fn result_from_host_api_index(idx: ffi::PaHostApiIndex) -> Result<HostApiIndex,
 ///
 /// Return the size in bytes of a single sample in the specified format,
 /// or SampleFormatNotSupported if the format is not supported.
+///
+/// This is the size that PortAudio itself assumes when transferring samples, as reported by
+/// `Pa_GetSampleSize`, whereas **SampleFormat::size_in_bytes** gives the same sizes without
+/// asking PortAudio, e.g. 3 bytes for the packed **I24** format.
 pub fn get_sample_size(format: SampleFormat) -> Result<u8, Error> {
-    let result = unsafe { ffi::Pa_GetSampleSize(format as ffi::PaSampleFormat) };
-    if result < 0 {
-        Err(::num::FromPrimitive::from_i32(result).unwrap())
-    } else {
-        Ok(result as u8)
+    match unsafe { ffi::Pa_GetSampleSize(format.flags().bits()) } {
+        size if size > 0 => Ok(size as u8),
+        0 => Err(Error::SampleFormatNotSupported),
+        err => Err(Error::from_code(err)),
     }
 }
 
 mod private {
-    use super::types::SampleFormat;
     use num::{FromPrimitive, ToPrimitive};
     use std::ops::{Add, Div, Mul, Sub};
 
@@ -775,51 +2147,150 @@ mod private {
         fn size<S: SamplePrivate>() -> usize {
             ::std::mem::size_of::<S>()
         }
-        /// get the sample format
-        fn to_sample_format() -> SampleFormat;
+        /// convert the sample to an `f32` in the range `-1.0..=1.0`
+        fn sample_to_f32(self) -> f32;
+        /// convert an `f32` in the range `-1.0..=1.0` to a sample, clipping it if out of range
+        fn sample_from_f32(value: f32) -> Self;
+        /// the number of significant bits in a sample
+        fn resolution_bits() -> u32;
+    }
+
+    /// Clip the value to the range `-1.0..=1.0`, mapping NaN to silence.
+    pub fn clip(value: f32) -> f32 {
+        if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(-1.0, 1.0)
+        }
     }
 }
 
 impl private::SamplePrivate for f32 {
-    fn to_sample_format() -> SampleFormat {
-        SampleFormat::F32
+    fn sample_to_f32(self) -> f32 {
+        self
+    }
+    fn sample_from_f32(value: f32) -> f32 {
+        value
+    }
+    fn resolution_bits() -> u32 {
+        24
     }
 }
 
 impl private::SamplePrivate for i32 {
-    fn to_sample_format() -> SampleFormat {
-        SampleFormat::I32
+    fn sample_to_f32(self) -> f32 {
+        (self as f64 / i32::MAX as f64).max(-1.0) as f32
+    }
+    fn sample_from_f32(value: f32) -> i32 {
+        (private::clip(value) as f64 * i32::MAX as f64).round() as i32
+    }
+    fn resolution_bits() -> u32 {
+        32
+    }
+}
+
+impl private::SamplePrivate for I24 {
+    fn sample_to_f32(self) -> f32 {
+        (self.to_i32() as f32 / I24::MAX as f32).max(-1.0)
+    }
+    fn sample_from_f32(value: f32) -> I24 {
+        I24::wrapping_new((private::clip(value) * I24::MAX as f32).round() as i32)
+    }
+    fn resolution_bits() -> u32 {
+        24
     }
 }
 
 impl private::SamplePrivate for i16 {
-    fn to_sample_format() -> SampleFormat {
-        SampleFormat::I16
+    fn sample_to_f32(self) -> f32 {
+        (self as f32 / i16::MAX as f32).max(-1.0)
+    }
+    fn sample_from_f32(value: f32) -> i16 {
+        (private::clip(value) * i16::MAX as f32).round() as i16
+    }
+    fn resolution_bits() -> u32 {
+        16
     }
 }
 
 impl private::SamplePrivate for i8 {
-    fn to_sample_format() -> SampleFormat {
-        SampleFormat::I8
+    fn sample_to_f32(self) -> f32 {
+        (self as f32 / i8::MAX as f32).max(-1.0)
+    }
+    fn sample_from_f32(value: f32) -> i8 {
+        (private::clip(value) * i8::MAX as f32).round() as i8
+    }
+    fn resolution_bits() -> u32 {
+        8
     }
 }
 
 impl private::SamplePrivate for u8 {
-    fn to_sample_format() -> SampleFormat {
-        SampleFormat::U8
+    fn sample_to_f32(self) -> f32 {
+        ((self as f32 - 128.0) / 127.0).max(-1.0)
+    }
+    fn sample_from_f32(value: f32) -> u8 {
+        ((private::clip(value) * 127.0).round() + 128.0) as u8
+    }
+    fn resolution_bits() -> u32 {
+        8
     }
 }
 
 /// public trait to constraint pa::Stream for specific types
 pub trait Sample: private::SamplePrivate {
-    /// Retrieve the SampleFormat variant associated with the type.
+    /// The **SampleFormat** in which PortAudio transfers samples of this type.
+    ///
+    /// ```
+    /// # extern crate portaudio;
+    /// use portaudio::{Sample, SampleFormat};
+    /// # fn main() {
+    /// assert_eq!(<i16 as Sample>::FORMAT, SampleFormat::I16);
+    /// assert_eq!(u8::sample_format().name(), "u8");
+    /// # }
+    /// ```
+    const FORMAT: SampleFormat;
+
+    /// Retrieve the SampleFormat variant associated with the type, i.e. **Sample::FORMAT**.
     fn sample_format() -> SampleFormat {
-        Self::to_sample_format()
+        Self::FORMAT
+    }
+
+    /// Convert the sample to an `f32` in the range `-1.0..=1.0`.
+    ///
+    /// Integer samples are scaled so that their largest value (e.g. `32767` for `i16`) maps to
+    /// `1.0`. The most negative value of a signed type lies just beyond full scale and is clipped
+    /// to `-1.0`. `u8` samples are offset by `128`.
+    ///
+    fn to_f32_sample(self) -> f32 {
+        self.sample_to_f32()
+    }
+
+    /// Convert an `f32` in the range `-1.0..=1.0` to a sample, the inverse of
+    /// **Sample::to_f32_sample**.
+    ///
+    /// Values outside of that range are clipped when converting to an integer sample, and NaN is
+    /// converted to silence. Conversion to `f32` is the identity.
+    fn from_f32_sample(value: f32) -> Self {
+        Self::sample_from_f32(value)
     }
 }
 
-impl Sample for f32 {}
-impl Sample for i32 {}
-impl Sample for i16 {}
-impl Sample for i8 {}
-impl Sample for u8 {}
+impl Sample for f32 {
+    const FORMAT: SampleFormat = SampleFormat::F32;
+}
+impl Sample for i32 {
+    const FORMAT: SampleFormat = SampleFormat::I32;
+}
+impl Sample for I24 {
+    const FORMAT: SampleFormat = SampleFormat::I24;
+}
+impl Sample for i16 {
+    const FORMAT: SampleFormat = SampleFormat::I16;
+}
+impl Sample for i8 {
+    const FORMAT: SampleFormat = SampleFormat::I8;
+}
+impl Sample for u8 {
+    const FORMAT: SampleFormat = SampleFormat::U8;
+}
@@ -0,0 +1,152 @@
+//!
+//! Level metering of buffers of `f32` samples, e.g. to drive a level meter in a UI.
+//!
+//! The functions neither allocate nor lock, so they may be called from within a stream callback.
+//! A level is an `f32` and can be handed to a display thread through an `AtomicU32` via
+//! `f32::to_bits` and `f32::from_bits`.
+//!
+//! ```
+//! use portaudio::meter;
+//! use portaudio::tone::SineGenerator;
+//!
+//! // A full scale sine peaks at 1.0, with an RMS level of 1/√2.
+//! let mut sine = [0.0; 48_000];
+//! SineGenerator::new(1_000.0, 48_000.0, 1.0, 1).fill(&mut sine);
+//! assert!((meter::peak(&sine) - 1.0).abs() < 0.001);
+//! assert!((meter::rms(&sine) - 0.5f32.sqrt()).abs() < 0.001);
+//!
+//! assert_eq!(meter::peak(&[0.25, -0.5, 0.125]), 0.5);
+//! assert_eq!(meter::rms(&[0.5, -0.5]), 0.5);
+//! assert_eq!(meter::rms(&[]), 0.0);
+//! ```
+
+use std::time::Duration;
+
+/// The largest absolute value among the `samples`, or `0.0` if there are none.
+pub fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0, |peak, s| peak.max(s.abs()))
+}
+
+/// The root mean square of the `samples`, or `0.0` if there are none.
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = samples.iter().map(|&s| f64::from(s) * f64::from(s)).sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
+
+/// Meters the peak level of each channel of interleaved buffers, holding each peak for a while
+/// and then letting it fall smoothly, as a level meter display expects.
+///
+/// ```
+/// use portaudio::meter::PeakMeter;
+/// use std::time::Duration;
+///
+/// // Stereo at 1 kHz, holding peaks for 100 ms and then falling by 20 dB per second.
+/// let mut meter = PeakMeter::new(2, 1_000.0, Duration::from_millis(100), 20.0);
+/// meter.process(&[0.5, -1.0, 0.25, 0.0]);
+/// assert_eq!(meter.levels(), &[0.5, 1.0]);
+///
+/// // Silence leaves the peaks in place while they are held...
+/// let silence = [0.0; 200];
+/// meter.process(&silence[..196]);
+/// assert_eq!(meter.levels(), &[0.5, 1.0]);
+///
+/// // ...and then lets them fall, here by 20 dB over the next second.
+/// for _ in 0..10 {
+///     meter.process(&silence);
+/// }
+/// assert!((meter.level(1) - 0.1).abs() < 0.001);
+///
+/// // A louder buffer raises the level immediately.
+/// meter.process(&[0.0, 0.75]);
+/// assert_eq!(meter.level(1), 0.75);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeakMeter {
+    /// The number of frames for which a peak is held before it falls.
+    hold_frames: u64,
+    /// The factor by which a level falls per frame once its hold has expired.
+    decay_per_frame: f64,
+    levels: Vec<f32>,
+    /// The number of frames for which each channel's peak is still held.
+    holds: Vec<u64>,
+}
+
+impl PeakMeter {
+    /// Construct a meter of the given number of interleaved `channels` of audio at the given
+    /// sample rate, holding each peak for the `hold` duration and then letting it fall by
+    /// `decay_db_per_second` decibels per second.
+    pub fn new(
+        channels: usize,
+        sample_rate: f64,
+        hold: Duration,
+        decay_db_per_second: f32,
+    ) -> Self {
+        let channels = channels.max(1);
+        let decay_db_per_frame = f64::from(decay_db_per_second) / sample_rate;
+        PeakMeter {
+            hold_frames: (hold.as_secs_f64() * sample_rate).round() as u64,
+            decay_per_frame: 10f64.powf(-decay_db_per_frame / 20.0),
+            levels: vec![0.0; channels],
+            holds: vec![0; channels],
+        }
+    }
+
+    /// Meter the interleaved `buffer`, e.g. from within a stream callback.
+    ///
+    /// Any trailing samples that don't make up a whole frame are ignored.
+    pub fn process(&mut self, buffer: &[f32]) {
+        let channels = self.levels.len();
+        let frames = (buffer.len() / channels) as u64;
+        if frames == 0 {
+            return;
+        }
+        for channel in 0..channels {
+            let peak = buffer[..frames as usize * channels]
+                .iter()
+                .skip(channel)
+                .step_by(channels)
+                .fold(0.0f32, |peak, s| peak.max(s.abs()));
+            if peak >= self.levels[channel] {
+                self.levels[channel] = peak;
+                self.holds[channel] = self.hold_frames;
+            } else {
+                let decaying = frames.saturating_sub(self.holds[channel]);
+                self.holds[channel] = self.holds[channel].saturating_sub(frames);
+                if decaying > 0 {
+                    let decay = self.decay_per_frame.powf(decaying as f64) as f32;
+                    self.levels[channel] = (self.levels[channel] * decay).max(peak);
+                }
+            }
+        }
+    }
+
+    /// The current level of each channel, where `1.0` is full scale.
+    pub fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+
+    /// The current level of the given channel, where `1.0` is full scale.
+    ///
+    /// **Panics** if `channel` is not less than **PeakMeter::channels**.
+    pub fn level(&self, channel: usize) -> f32 {
+        self.levels[channel]
+    }
+
+    /// The number of interleaved channels that are metered.
+    pub fn channels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Drop every channel's level to silence, e.g. when the stream is stopped.
+    pub fn reset(&mut self) {
+        for level in &mut self.levels {
+            *level = 0.0;
+        }
+        for hold in &mut self.holds {
+            *hold = 0;
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//!
+//! A queue of output buffers that a stream callback plays back to back, e.g. for gapless playback
+//! of decoded audio.
+//!
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use super::ring_buffer::{RingBuffer, RingBufferReader, RingBufferWriter};
+use super::Sample;
+use ffi;
+
+/// How long **OutputQueue::enqueue** sleeps while it waits for the callback to make room.
+const ENQUEUE_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The control thread's end of a queue of interleaved output buffers, produced by
+/// **OutputQueue::new** along with the **OutputQueueReader** that the stream callback drains.
+///
+/// The samples of consecutive buffers are passed through a **RingBuffer**, so a callback buffer
+/// that spans the end of one queued buffer is continued with the start of the next rather than
+/// padded with silence. Each queued buffer should hold whole frames, otherwise the channels of
+/// the buffers that follow it are shifted.
+///
+/// Closing or dropping the **OutputQueue** marks the end of the audio. Once everything queued
+/// before then has been played, the reader has the callback return **Complete**.
+pub struct OutputQueue<T> {
+    writer: RingBufferWriter<T>,
+    is_closed: Arc<AtomicBool>,
+}
+
+/// The stream callback's end of an **OutputQueue**.
+///
+/// Neither reading nor checking for the end of the queue allocates or locks.
+pub struct OutputQueueReader<T> {
+    reader: RingBufferReader<T>,
+    is_closed: Arc<AtomicBool>,
+}
+
+impl<T: Sample> OutputQueue<T> {
+    /// Construct a queue that can hold at least `capacity` samples that are yet to be played,
+    /// along with the reader for the stream callback.
+    ///
+    /// As with **RingBuffer::new**, the capacity is rounded up to the next power of two.
+    pub fn new(capacity: usize) -> (OutputQueue<T>, OutputQueueReader<T>) {
+        let (writer, reader) = RingBuffer::new(capacity).split();
+        let is_closed = Arc::new(AtomicBool::new(false));
+        let queue = OutputQueue {
+            writer: writer,
+            is_closed: is_closed.clone(),
+        };
+        let reader = OutputQueueReader {
+            reader: reader,
+            is_closed: is_closed,
+        };
+        (queue, reader)
+    }
+
+    /// The number of samples that can be queued without waiting.
+    pub fn write_available(&self) -> usize {
+        self.writer.write_available()
+    }
+
+    /// Append all of the samples in `buffer` to the queue, waiting for the callback to make room
+    /// for them while the queue is full.
+    pub fn enqueue(&mut self, buffer: Vec<T>) {
+        let mut written = self.writer.write(&buffer);
+        while written < buffer.len() {
+            thread::sleep(ENQUEUE_POLL_INTERVAL);
+            written += self.writer.write(&buffer[written..]);
+        }
+    }
+
+    /// Append as many of the given samples as there is room for without waiting.
+    ///
+    /// Returns the number of samples that were queued.
+    pub fn try_enqueue(&mut self, samples: &[T]) -> usize {
+        self.writer.write(samples)
+    }
+
+    /// Mark the end of the audio, so that the reader completes once it has played the samples
+    /// that remain in the queue. This is equivalent to dropping the **OutputQueue**.
+    pub fn close(self) {}
+}
+
+impl<T> Drop for OutputQueue<T> {
+    fn drop(&mut self) {
+        self.is_closed.store(true, Ordering::Release);
+    }
+}
+
+impl<T: Sample> OutputQueueReader<T> {
+    /// Fill `buffer` with the next queued samples, padding it with silence if the queue runs dry
+    /// before the **OutputQueue** is closed.
+    ///
+    /// Returns **Complete** once the queue has been closed and all of its samples have been
+    /// played, and **Continue** otherwise, so the result can be returned straight from the
+    /// callback.
+    pub fn fill(&mut self, buffer: &mut [T]) -> ffi::PaStreamCallbackResult {
+        // Check for the end before reading, so that samples queued just before the queue was
+        // closed are still played.
+        let is_closed = self.is_closed.load(Ordering::Acquire);
+        let read = self.reader.read(buffer);
+        let silence = <T as Sample>::from_f32_sample(0.0);
+        for sample in &mut buffer[read..] {
+            *sample = silence;
+        }
+        if is_closed && self.reader.read_available() == 0 {
+            ffi::PA_COMPLETE
+        } else {
+            ffi::PA_CONTINUE
+        }
+    }
+
+    /// The number of queued samples that are yet to be played.
+    pub fn read_available(&self) -> usize {
+        self.reader.read_available()
+    }
+}
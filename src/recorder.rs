@@ -0,0 +1,183 @@
+//!
+//! A recorder that captures an input device into memory, for when the samples are only needed
+//! once recording has stopped.
+//!
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use super::ring_buffer::{RingBuffer, RingBufferReader};
+use super::Sample;
+use error::Error;
+use ffi;
+use stream::{Input, InputSettings, NonBlocking, Parameters, Stream};
+use types::{DeviceIndex, FRAMES_PER_BUFFER_UNSPECIFIED};
+use PortAudio;
+
+/// How much audio, in seconds, the ring buffer between the callback and the worker thread holds.
+const RING_BUFFER_SECONDS: f64 = 0.5;
+/// How long the worker thread sleeps while the ring buffer is empty.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Records an input device into a growable buffer of interleaved samples, between calls to
+/// **Recorder::start** and **Recorder::stop**.
+///
+/// The stream callback only copies each buffer into a **RingBuffer**, which a worker thread
+/// drains into the recording, so the callback neither allocates nor locks however long the
+/// recording grows. Should the worker thread fall behind, whole frames are discarded instead of
+/// blocking the callback and are counted by **Recorder::dropped_frames**.
+pub struct Recorder<T> {
+    stream: Stream<NonBlocking, Input<T>>,
+    channels: usize,
+    dropped_frames: Arc<AtomicU64>,
+    /// The reader is moved into the worker thread while recording, and handed back by `stop`.
+    reader: Option<RingBufferReader<T>>,
+    worker: Option<Worker<T>>,
+}
+
+/// The thread that drains the ring buffer while recording.
+struct Worker<T> {
+    is_stopping: Arc<AtomicBool>,
+    handle: thread::JoinHandle<(RingBufferReader<T>, Vec<T>)>,
+}
+
+impl<T: Sample + Send + 'static> Recorder<T> {
+    /// Open a stopped recorder for the given number of interleaved `channels` of the `device`, at
+    /// the given sample rate.
+    ///
+    /// The stream uses the device's default high input latency, as a recording tolerates latency
+    /// far better than lost frames.
+    pub fn new(
+        port_audio: &PortAudio,
+        device: DeviceIndex,
+        channels: i32,
+        sample_rate: f64,
+    ) -> Result<Self, Error> {
+        const INTERLEAVED: bool = true;
+        if channels <= 0 {
+            return Err(Error::InvalidChannelCount);
+        }
+        let latency = port_audio.device_info(device)?.default_high_input_latency;
+        let params = Parameters::<T>::new(device, channels, INTERLEAVED, latency);
+        let settings = InputSettings::new(params, sample_rate, FRAMES_PER_BUFFER_UNSPECIFIED);
+
+        let channels = channels as usize;
+        let capacity = (RING_BUFFER_SECONDS * sample_rate).ceil() as usize * channels;
+        let (mut writer, reader) = RingBuffer::new(capacity).split();
+        let dropped_frames = Arc::new(AtomicU64::new(0));
+        let dropped = dropped_frames.clone();
+        let stream = port_audio.open_non_blocking_stream(settings, move |args| {
+            // Only whole frames are written, so that the channels stay in place.
+            let frames = args.frames.min(writer.write_available() / channels);
+            writer.write(&args.buffer[..frames * channels]);
+            if frames < args.frames {
+                dropped.fetch_add((args.frames - frames) as u64, Ordering::Relaxed);
+            }
+            ffi::PA_CONTINUE
+        })?;
+
+        Ok(Recorder {
+            stream: stream,
+            channels: channels,
+            dropped_frames: dropped_frames,
+            reader: Some(reader),
+            worker: None,
+        })
+    }
+
+    /// Start recording into an empty buffer.
+    ///
+    /// Returns `Err(Error::StreamIsNotStopped)` if the recorder is already recording.
+    pub fn start(&mut self) -> Result<(), Error> {
+        let mut reader = match self.reader.take() {
+            Some(reader) => reader,
+            None => return Err(Error::StreamIsNotStopped),
+        };
+        let is_stopping = Arc::new(AtomicBool::new(false));
+        let stopping = is_stopping.clone();
+        let handle = thread::spawn(move || {
+            let mut samples = Vec::new();
+            loop {
+                // The flag is checked before draining, so that everything written before the
+                // stream was stopped is drained once more after it is set.
+                let is_last = stopping.load(Ordering::Acquire);
+                let available = reader.read_available();
+                if available > 0 {
+                    let len = samples.len();
+                    samples.resize(len + available, T::default());
+                    reader.read(&mut samples[len..]);
+                } else if is_last {
+                    break;
+                } else {
+                    thread::sleep(DRAIN_POLL_INTERVAL);
+                }
+            }
+            (reader, samples)
+        });
+        self.dropped_frames.store(0, Ordering::Relaxed);
+        self.worker = Some(Worker {
+            is_stopping: is_stopping,
+            handle: handle,
+        });
+        if let Err(err) = self.stream.start() {
+            self.finish_worker().ok();
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Stop recording, returning the interleaved samples that were captured since the last call
+    /// to **Recorder::start**.
+    ///
+    /// The recorder may be started again afterwards.
+    ///
+    /// Returns `Err(Error::StreamIsStopped)` if the recorder isn't recording.
+    pub fn stop(&mut self) -> Result<Vec<T>, Error> {
+        if self.worker.is_none() {
+            return Err(Error::StreamIsStopped);
+        }
+        self.stream.stop()?;
+        self.finish_worker()
+    }
+
+    /// Have the worker thread drain what remains of the ring buffer and end, taking back the
+    /// reader along with the recorded samples.
+    fn finish_worker(&mut self) -> Result<Vec<T>, Error> {
+        let worker = match self.worker.take() {
+            Some(worker) => worker,
+            None => return Err(Error::StreamIsStopped),
+        };
+        worker.is_stopping.store(true, Ordering::Release);
+        let (reader, samples) = worker.handle.join().map_err(|_| Error::InternalError)?;
+        self.reader = Some(reader);
+        Ok(samples)
+    }
+
+    /// Whether the recorder is between calls to **Recorder::start** and **Recorder::stop**.
+    pub fn is_recording(&self) -> bool {
+        self.worker.is_some()
+    }
+
+    /// The number of interleaved channels of the recording.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The number of frames that were discarded since the recording started because the worker
+    /// thread fell behind the stream callback. They are missing from the recording.
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Drop for Recorder<T> {
+    /// Ends the worker thread of a recording that was never stopped, discarding its samples.
+    fn drop(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            worker.is_stopping.store(true, Ordering::Release);
+            worker.handle.join().ok();
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//!
+//! An adaptive resampler for passing audio between two devices whose clocks drift apart, e.g.
+//! from the input stream of one device to the output stream of another.
+//!
+
+/// How quickly the smoothed fill level follows the reported one, per report.
+const FILL_SMOOTHING: f64 = 0.05;
+/// The correction applied per unit of relative fill level error.
+const PROPORTIONAL_GAIN: f64 = 0.01;
+/// The correction accumulated per report, per unit of relative fill level error.
+const INTEGRAL_GAIN: f64 = 0.000_025;
+/// The largest relative correction of the ratio, far beyond the drift of any working clock.
+const MAX_CORRECTION: f64 = 0.005;
+
+/// Resamples interleaved `f32` audio by a ratio that is continuously nudged to keep the buffer
+/// it fills centered on a target fill level.
+///
+/// Two devices with independent clocks never run at exactly the same rate, even when their
+/// nominal sample rates match, so a buffer between the input of one and the output of the other
+/// slowly fills up or runs dry until the audio glitches. The fix is to place the resampler in
+/// front of the buffer, e.g. in the input callback before writing into a **RingBuffer** that the
+/// output callback reads from, and to report the buffer's fill level via
+/// **AdaptiveResampler::update_fill_level** before each call to **AdaptiveResampler::process**.
+/// When the buffer holds more than the target, slightly fewer frames are produced than were
+/// given, and when it holds less, slightly more.
+///
+/// ```
+/// use portaudio::resample::AdaptiveResampler;
+/// use portaudio::RingBuffer;
+///
+/// // The input device's clock runs 0.1% faster than the output device's, which would overflow
+/// // the buffer within a couple of minutes.
+/// const BLOCK: usize = 256;
+/// const TARGET: usize = 1024;
+/// let drift = 1.001;
+/// let (mut writer, mut reader) = RingBuffer::new(4 * TARGET).split();
+/// writer.write(&[0.0; TARGET]);
+/// let mut resampler = AdaptiveResampler::new(1, 48_000.0, 48_000.0, TARGET);
+///
+/// let mut captured = 0.0;
+/// let mut played = [0.0; BLOCK];
+/// for _ in 0..40_000 {
+///     // While the output plays a block, the input captures slightly more than a block.
+///     captured += BLOCK as f64 * drift;
+///     let frames = captured as usize;
+///     captured -= frames as f64;
+///
+///     resampler.update_fill_level(writer.capacity() - writer.write_available());
+///     let resampled = resampler.process(&vec![0.5; frames]);
+///     assert_eq!(writer.write(&resampled), resampled.len(), "the buffer overflowed");
+///     assert_eq!(reader.read(&mut played), BLOCK, "the buffer ran dry");
+/// }
+///
+/// let fill = writer.capacity() - writer.write_available();
+/// assert!((fill as f64 - TARGET as f64).abs() < BLOCK as f64);
+/// assert!((resampler.ratio() - drift).abs() < 0.0001);
+/// ```
+#[derive(Clone, Debug)]
+pub struct AdaptiveResampler {
+    channels: usize,
+    /// The number of input frames consumed per output frame, at the nominal sample rates.
+    nominal_ratio: f64,
+    /// The fill level, in frames, that the ratio is adjusted to keep the buffer at.
+    target_fill: usize,
+    /// The exponentially smoothed fill level, in frames.
+    fill: f64,
+    /// The integral term of the correction.
+    integral: f64,
+    /// The number of input frames consumed per output frame, including the correction.
+    ratio: f64,
+    /// The position of the next output frame relative to the last frame of the previous input,
+    /// in frames.
+    position: f64,
+    /// The last frame of the previous input, which the first output frames are interpolated from.
+    last_frame: Vec<f32>,
+}
+
+impl AdaptiveResampler {
+    /// Construct a resampler of the given number of interleaved `channels`, from audio at the
+    /// nominal `input_rate` to audio at the nominal `output_rate`, that keeps the buffer it fills
+    /// at `target_fill` frames.
+    ///
+    /// A target of a few callback buffers' worth of frames leaves room for the jitter of both
+    /// streams' callbacks, and the buffer should hold at least twice the target.
+    pub fn new(channels: usize, input_rate: f64, output_rate: f64, target_fill: usize) -> Self {
+        let channels = channels.max(1);
+        let nominal_ratio = input_rate / output_rate;
+        AdaptiveResampler {
+            channels: channels,
+            nominal_ratio: nominal_ratio,
+            target_fill: target_fill.max(1),
+            fill: target_fill as f64,
+            integral: 0.0,
+            ratio: nominal_ratio,
+            position: 0.0,
+            last_frame: vec![0.0; channels],
+        }
+    }
+
+    /// Report the number of frames that the buffer being filled currently holds, e.g.
+    /// `(writer.capacity() - writer.write_available()) / channels` for a **RingBufferWriter**.
+    ///
+    /// The ratio is adjusted based on the fill level averaged over many reports, as the level
+    /// jumps by a callback buffer whenever either stream's callback runs.
+    pub fn update_fill_level(&mut self, frames: usize) {
+        self.fill += (frames as f64 - self.fill) * FILL_SMOOTHING;
+        let target = self.target_fill as f64;
+        let error = (self.fill - target) / target;
+        self.integral = clamp(self.integral + error * INTEGRAL_GAIN, MAX_CORRECTION);
+        let correction = clamp(error * PROPORTIONAL_GAIN + self.integral, MAX_CORRECTION);
+        self.ratio = self.nominal_ratio * (1.0 + correction);
+    }
+
+    /// Resample the interleaved `input`, continuing from the previous call, and return the
+    /// interleaved output.
+    ///
+    /// This allocates the returned buffer. Within a stream callback, use
+    /// **AdaptiveResampler::process_into** with a buffer of sufficient capacity instead.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::new();
+        self.process_into(input, &mut output);
+        output
+    }
+
+    /// Resample the interleaved `input`, continuing from the previous call, and append the
+    /// interleaved output to `output`.
+    ///
+    /// The output holds about `input.len() / ratio` samples, so reserving some more than that
+    /// beforehand avoids reallocating. Any trailing samples of `input` that don't make up a whole
+    /// frame are ignored.
+    pub fn process_into(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        let channels = self.channels;
+        let frames = input.len() / channels;
+        if frames == 0 {
+            return;
+        }
+        // Frame `-1` is the last frame of the previous input.
+        let frame = |index: isize| -> &[f32] {
+            if index < 0 {
+                &self.last_frame
+            } else {
+                let start = index as usize * channels;
+                &input[start..start + channels]
+            }
+        };
+
+        let last = frames as isize - 1;
+        let mut position = self.position - 1.0;
+        while position < last as f64 {
+            let index = position.floor();
+            let fraction = (position - index) as f32;
+            let (a, b) = (frame(index as isize), frame(index as isize + 1));
+            output.extend(a.iter().zip(b).map(|(&a, &b)| a + (b - a) * fraction));
+            position += self.ratio;
+        }
+        self.position = position - last as f64;
+        let start = last as usize * channels;
+        self.last_frame
+            .copy_from_slice(&input[start..start + channels]);
+    }
+
+    /// The number of input frames currently consumed per output frame, i.e. the ratio of the
+    /// nominal sample rates including the correction for the drift.
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    /// The number of interleaved channels that are resampled.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+}
+
+/// Clamp `value` to `-limit..=limit`.
+fn clamp(value: f64, limit: f64) -> f64 {
+    value.max(-limit).min(limit)
+}
@@ -0,0 +1,196 @@
+//!
+//! A single-producer, single-consumer lock-free ring buffer for passing audio between a stream
+//! callback and other threads.
+//!
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A single-producer, single-consumer lock-free ring buffer.
+///
+/// This is a port of the `PaUtil_RingBuffer` that ships with PortAudio (`pa_ringbuffer.c`).
+/// PortAudio's own implementation is not part of its public API and is not exported by every
+/// build of the library, so it is reimplemented here using the same scheme: the capacity is
+/// rounded up to a power of two and the read and write indices run over twice the capacity, so
+/// that a full buffer can be told apart from an empty one without wasting an element.
+///
+/// Neither reading nor writing allocates or locks, so it is safe to use from within a stream
+/// callback. To use the two ends from different threads, **RingBuffer::split** it into a
+/// **RingBufferWriter** and a **RingBufferReader**.
+pub struct RingBuffer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// The writing half of a **RingBuffer**, produced by **RingBuffer::split**.
+pub struct RingBufferWriter<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// The reading half of a **RingBuffer**, produced by **RingBuffer::split**.
+pub struct RingBufferReader<T> {
+    inner: Arc<Inner<T>>,
+}
+
+struct Inner<T> {
+    data: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    /// Used to wrap the indices, which run from `0` to twice the capacity.
+    big_mask: usize,
+    /// Used to map an index to a position within `data`.
+    small_mask: usize,
+    write_index: AtomicUsize,
+    read_index: AtomicUsize,
+}
+
+// Only the writer touches the elements between the write index and the read index (wrapping
+// around), and only the reader touches those between the read index and the write index. The
+// indices themselves are atomics.
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T: Copy> RingBuffer<T> {
+    /// Construct a new **RingBuffer** that can hold at least `capacity` elements.
+    ///
+    /// The capacity is rounded up to the next power of two.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1).next_power_of_two();
+        let data = (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        let inner = Inner {
+            data: data,
+            big_mask: capacity * 2 - 1,
+            small_mask: capacity - 1,
+            write_index: AtomicUsize::new(0),
+            read_index: AtomicUsize::new(0),
+        };
+        RingBuffer {
+            inner: Arc::new(inner),
+        }
+    }
+
+    /// The number of elements that the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.inner.data.len()
+    }
+
+    /// The number of elements that can currently be written.
+    pub fn write_available(&self) -> usize {
+        self.inner.write_available()
+    }
+
+    /// The number of elements that can currently be read.
+    pub fn read_available(&self) -> usize {
+        self.inner.read_available()
+    }
+
+    /// Write as many elements of `data` as there is room for.
+    ///
+    /// Returns the number of elements written.
+    pub fn write(&mut self, data: &[T]) -> usize {
+        unsafe { self.inner.write(data) }
+    }
+
+    /// Read as many elements as are available into `data`.
+    ///
+    /// Returns the number of elements read.
+    pub fn read(&mut self, data: &mut [T]) -> usize {
+        unsafe { self.inner.read(data) }
+    }
+
+    /// Split the **RingBuffer** into its writing and reading halves, which may be sent to
+    /// separate threads.
+    pub fn split(self) -> (RingBufferWriter<T>, RingBufferReader<T>) {
+        let writer = RingBufferWriter {
+            inner: self.inner.clone(),
+        };
+        let reader = RingBufferReader { inner: self.inner };
+        (writer, reader)
+    }
+}
+
+impl<T: Copy> RingBufferWriter<T> {
+    /// The number of elements that the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.inner.data.len()
+    }
+
+    /// The number of elements that can currently be written.
+    pub fn write_available(&self) -> usize {
+        self.inner.write_available()
+    }
+
+    /// Write as many elements of `data` as there is room for.
+    ///
+    /// Returns the number of elements written.
+    pub fn write(&mut self, data: &[T]) -> usize {
+        unsafe { self.inner.write(data) }
+    }
+}
+
+impl<T: Copy> RingBufferReader<T> {
+    /// The number of elements that the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.inner.data.len()
+    }
+
+    /// The number of elements that can currently be read.
+    pub fn read_available(&self) -> usize {
+        self.inner.read_available()
+    }
+
+    /// Read as many elements as are available into `data`.
+    ///
+    /// Returns the number of elements read.
+    pub fn read(&mut self, data: &mut [T]) -> usize {
+        unsafe { self.inner.read(data) }
+    }
+}
+
+impl<T: Copy> Inner<T> {
+    fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    fn read_available(&self) -> usize {
+        let write_index = self.write_index.load(Ordering::Acquire);
+        let read_index = self.read_index.load(Ordering::Acquire);
+        write_index.wrapping_sub(read_index) & self.big_mask
+    }
+
+    fn write_available(&self) -> usize {
+        self.capacity() - self.read_available()
+    }
+
+    /// Must only be called by the single writer.
+    unsafe fn write(&self, data: &[T]) -> usize {
+        let count = ::std::cmp::min(data.len(), self.write_available());
+        let write_index = self.write_index.load(Ordering::Relaxed);
+        let start = write_index & self.small_mask;
+        for (i, &element) in data[..count].iter().enumerate() {
+            let slot = &self.data[(start + i) & self.small_mask];
+            *slot.get() = MaybeUninit::new(element);
+        }
+        // Publish the written elements to the reader.
+        let write_index = (write_index + count) & self.big_mask;
+        self.write_index.store(write_index, Ordering::Release);
+        count
+    }
+
+    /// Must only be called by the single reader.
+    unsafe fn read(&self, data: &mut [T]) -> usize {
+        let count = ::std::cmp::min(data.len(), self.read_available());
+        let read_index = self.read_index.load(Ordering::Relaxed);
+        let start = read_index & self.small_mask;
+        for (i, element) in data[..count].iter_mut().enumerate() {
+            let slot = &self.data[(start + i) & self.small_mask];
+            *element = (*slot.get()).assume_init();
+        }
+        // Hand the read slots back to the writer.
+        let read_index = (read_index + count) & self.big_mask;
+        self.read_index.store(read_index, Ordering::Release);
+        count
+    }
+}
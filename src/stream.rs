@@ -5,13 +5,21 @@
 
 use ffi;
 use libc;
-use num::FromPrimitive;
+use std::any::Any;
 use std::os::raw;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::time::Duration;
 use std::{self, ptr};
 
 use super::error::Error;
+#[cfg(target_os = "linux")]
+use super::ext::alsa::AlsaStreamInfo;
+#[cfg(windows)]
+use super::ext::wasapi::WasapiStreamInfo;
 use super::types::{
-    sample_format_flags, DeviceIndex, DeviceKind, SampleFormat, SampleFormatFlags, Time,
+    self, sample_format_flags, time_to_duration, DeviceIndex, DeviceKind, HostApiTypeId,
+    SampleFormat, SampleFormatFlags, Time,
 };
 use super::Sample;
 
@@ -24,8 +32,51 @@ pub trait Mode {}
 
 /// Types used to open a **Stream** via the
 /// [**PortAudio::open_blocking_stream**](../struct.PortAudio.html#method.open_blocking_stream) and
-/// [**PortAudio::open_non_blocking_stream**](../struct.PortAudio.html#method.open_blocking_stream)
+/// [**PortAudio::open_non_blocking_stream**](../struct.PortAudio.html#method.open_non_blocking_stream)
 /// methods.
+///
+/// Each settings type determines the **Flow** of the opened **Stream**, and hence the type of its
+/// blocking buffers and of the arguments passed to its callback:
+///
+/// - **InputSettings** -> **Input**, whose callback is given **InputCallbackArgs**
+/// - **OutputSettings** -> **Output**, whose callback is given **OutputCallbackArgs**
+/// - **DuplexSettings** -> **Duplex**, whose callback is given **DuplexCallbackArgs**
+/// - **RawInputSettings**, **RawOutputSettings** and **RawDuplexSettings** -> the **Raw** flows
+///
+/// This allows helpers to be written once for streams of any direction, e.g. one that runs a
+/// callback stream until the callback completes:
+///
+/// ```no_run
+/// extern crate portaudio;
+/// use portaudio as pa;
+///
+/// fn run_until_complete<S, C>(
+///     pa: &pa::PortAudio,
+///     settings: S,
+///     callback: C,
+/// ) -> Result<(), pa::Error>
+/// where
+///     S: pa::StreamSettings,
+///     S::Flow: pa::stream::Flow,
+///     C: FnMut(<S::Flow as pa::stream::Flow>::CallbackArgs) -> pa::StreamCallbackResult
+///         + Send
+///         + 'static,
+/// {
+///     let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+///     stream.start()?;
+///     while stream.is_active()? {
+///         pa.sleep(10);
+///     }
+///     stream.stop()?;
+///     stream.close()
+/// }
+///
+/// fn main() {
+///     let pa = pa::PortAudio::new().unwrap();
+///     let settings = pa.default_output_stream_settings::<f32>(2, 44_100.0, 256).unwrap();
+///     run_until_complete(&pa, settings, |_| pa::Complete).unwrap();
+/// }
+/// ```
 pub trait Settings {
     /// The **Flow** of the **Stream** (**Input**, **Output** or **Duplex**).
     type Flow;
@@ -51,19 +102,67 @@ pub trait Flow {
         Option<ffi::PaStreamParameters>,
         Option<ffi::PaStreamParameters>,
     );
+    /// The **HostApiSpecificStreamInfo** attached to the parameters of each direction, which is
+    /// passed to Pa_OpenStream alongside the **params_both_directions**.
+    fn host_api_stream_info_both_directions(
+        &self,
+    ) -> (
+        Option<HostApiSpecificStreamInfo>,
+        Option<HostApiSpecificStreamInfo>,
+    ) {
+        (None, None)
+    }
     /// Constructs the **Flow**'s associated **CallbackArgs** from the non-blocking C API stream
     /// parameters.
-    fn new_callback_args(
+    ///
+    /// # Safety
+    ///
+    /// `time_info` must point to valid time info, and `input` and `output` must point to buffers
+    /// of `frame_count` frames laid out as described by `in_layout` and `out_layout`, as they are
+    /// when passed to the stream callback by PortAudio.
+    unsafe fn new_callback_args(
         input: *const raw::c_void,
         output: *mut raw::c_void,
         frame_count: raw::c_ulong,
         time_info: *const ffi::PaStreamCallbackTimeInfo,
         flags: ffi::PaStreamCallbackFlags,
-        in_channels: i32,
-        out_channels: i32,
+        in_layout: BufferLayout,
+        out_layout: BufferLayout,
     ) -> Self::CallbackArgs;
 }
 
+/// Describes the layout of the audio buffer(s) given to the stream callback for one direction of
+/// a **Stream**, as passed to **Flow::new_callback_args**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BufferLayout {
+    /// The number of channels, or `0` if the **Stream** has no such direction.
+    pub channels: i32,
+    /// Whether the channels are interleaved within a single buffer.
+    pub interleaved: bool,
+    /// The size of a single sample in bytes.
+    pub sample_size: usize,
+}
+
+impl BufferLayout {
+    fn from_c_params(c_params: Option<ffi::PaStreamParameters>) -> Self {
+        match c_params {
+            Some(p) => {
+                let flags: SampleFormatFlags = p.sampleFormat.into();
+                BufferLayout {
+                    channels: p.channelCount,
+                    interleaved: !flags.contains(sample_format_flags::NON_INTERLEAVED),
+                    sample_size: SampleFormat::from_flags(flags).size_in_bytes() as usize,
+                }
+            }
+            None => BufferLayout {
+                channels: 0,
+                interleaved: true,
+                sample_size: 0,
+            },
+        }
+    }
+}
+
 /// **Streams** that can be read by the user.
 pub trait Reader: Flow {
     /// The sample format for the readable buffer.
@@ -85,17 +184,35 @@ pub trait Writer: Flow {
 }
 
 /// An alias for the boxed Callback function type.
-type CallbackFn = FnMut(
-    *const raw::c_void,
-    *mut raw::c_void,
-    raw::c_ulong,
-    *const ffi::PaStreamCallbackTimeInfo,
-    ffi::PaStreamCallbackFlags,
-) -> ffi::PaStreamCallbackResult;
+///
+/// The function is called from PortAudio's audio thread, hence it must be `Send`.
+type CallbackFn = dyn FnMut(
+        *const raw::c_void,
+        *mut raw::c_void,
+        raw::c_ulong,
+        *const ffi::PaStreamCallbackTimeInfo,
+        ffi::PaStreamCallbackFlags,
+    ) -> ffi::PaStreamCallbackResult
+    + Send;
 
 /// A wrapper around a user-given **CallbackFn** that can be sent to PortAudio.
 struct CallbackFnWrapper {
     f: Box<CallbackFn>,
+    /// Called by PortAudio once the stream becomes inactive. See `Stream::set_finished_callback`.
+    finished: Option<Box<dyn FnMut() + Send>>,
+    /// The payload of the first panic caught in either callback. See `Stream::take_callback_panic`.
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+impl CallbackFnWrapper {
+    /// Store the payload of a panic caught on PortAudio's thread, unless an earlier one is
+    /// already waiting to be taken.
+    fn store_panic(&self, payload: Box<dyn Any + Send>) {
+        let mut panic = self.panic.lock().unwrap_or_else(|err| err.into_inner());
+        if panic.is_none() {
+            *panic = Some(payload);
+        }
+    }
 }
 
 /// Timing information for the buffer passed to the input stream callback.
@@ -136,11 +253,110 @@ pub struct DuplexCallbackTimeInfo {
     pub out_buffer_dac: Time,
 }
 
+/// The separate per-channel buffers read from a non-interleaved **Stream**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PlanarBuffer<'a, S: 'a> {
+    channels: &'a [*const S],
+    frames: usize,
+}
+
+/// The separate per-channel buffers to be written to a non-interleaved **Stream**.
+#[derive(Debug, PartialEq)]
+pub struct PlanarBufferMut<'a, S: 'a> {
+    channels: &'a [*mut S],
+    frames: usize,
+}
+
+impl<'a, S> PlanarBuffer<'a, S> {
+    /// Wrap the array of `channel_count` channel buffers given by PortAudio.
+    unsafe fn from_raw(ptr: *const *const S, channel_count: i32, frames: usize) -> Self {
+        PlanarBuffer {
+            channels: std::slice::from_raw_parts(ptr, channel_count as usize),
+            frames: frames,
+        }
+    }
+
+    /// The number of channels.
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// The number of frames, i.e. the length of each channel's buffer.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The samples of the channel at the given index.
+    ///
+    /// Panics if `channel` is out of range.
+    pub fn channel(&self, channel: usize) -> &'a [S] {
+        unsafe { std::slice::from_raw_parts(self.channels[channel], self.frames) }
+    }
+
+    /// An iterator yielding the samples of each channel in turn.
+    pub fn channels(&self) -> impl Iterator<Item = &'a [S]> {
+        let frames = self.frames;
+        self.channels
+            .iter()
+            .map(move |&ptr| unsafe { std::slice::from_raw_parts(ptr, frames) })
+    }
+}
+
+impl<'a, S> PlanarBufferMut<'a, S> {
+    /// Wrap the array of `channel_count` channel buffers given by PortAudio.
+    unsafe fn from_raw(ptr: *const *mut S, channel_count: i32, frames: usize) -> Self {
+        PlanarBufferMut {
+            channels: std::slice::from_raw_parts(ptr, channel_count as usize),
+            frames: frames,
+        }
+    }
+
+    /// The number of channels.
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// The number of frames, i.e. the length of each channel's buffer.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The samples of the channel at the given index.
+    ///
+    /// Panics if `channel` is out of range.
+    pub fn channel(&self, channel: usize) -> &[S] {
+        unsafe { std::slice::from_raw_parts(self.channels[channel], self.frames) }
+    }
+
+    /// The samples of the channel at the given index, to be written to.
+    ///
+    /// Panics if `channel` is out of range.
+    pub fn channel_mut(&mut self, channel: usize) -> &mut [S] {
+        unsafe { std::slice::from_raw_parts_mut(self.channels[channel], self.frames) }
+    }
+
+    /// An iterator yielding the samples of each channel in turn, to be written to.
+    pub fn channels_mut(&mut self) -> impl Iterator<Item = &mut [S]> {
+        let frames = self.frames;
+        // Each pointer refers to a distinct channel buffer, so the slices never alias.
+        self.channels
+            .iter()
+            .map(move |&ptr| unsafe { std::slice::from_raw_parts_mut(ptr, frames) })
+    }
+}
+
 /// Arguments given to a **NonBlocking** **Input** **Stream**'s **CallbackFn**.
+///
+/// Only the recorded buffer is given, as an **Input** **Stream** has no output to fill.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct InputCallbackArgs<'a, I: 'a> {
     /// The buffer of interleaved samples read from the **Input** **Stream**'s ADC.
+    ///
+    /// This is empty if the stream is non-interleaved, in which case `planar` is used instead.
     pub buffer: &'a [I],
+    /// The per-channel buffers read from a non-interleaved **Input** **Stream**, or `None` if
+    /// the stream is interleaved.
+    pub planar: Option<PlanarBuffer<'a, I>>,
     /// The number of frames of audio data stored within the `buffer`.
     pub frames: usize,
     /// Flags indicating the current state of the stream and whether or not any special edge cases
@@ -150,11 +366,18 @@ pub struct InputCallbackArgs<'a, I: 'a> {
     pub time: InputCallbackTimeInfo,
 }
 
-/// Arguments given to a **NonBlocking** **Input** **Stream**'s **CallbackFn**.
+/// Arguments given to a **NonBlocking** **Output** **Stream**'s **CallbackFn**.
+///
+/// Only the output buffer is given, as an **Output** **Stream** doesn't record.
 #[derive(Debug, PartialEq)]
 pub struct OutputCallbackArgs<'a, O: 'a> {
     /// The **Output** **Stream**'s buffer, to which we will write our interleaved audio data.
+    ///
+    /// This is empty if the stream is non-interleaved, in which case `planar` is used instead.
     pub buffer: &'a mut [O],
+    /// The per-channel buffers of a non-interleaved **Output** **Stream**, or `None` if the
+    /// stream is interleaved.
+    pub planar: Option<PlanarBufferMut<'a, O>>,
     /// The number of frames of audio data stored within the `buffer`.
     pub frames: usize,
     /// Flags indicating the current state of the stream and whether or not any special edge cases
@@ -164,13 +387,22 @@ pub struct OutputCallbackArgs<'a, O: 'a> {
     pub time: OutputCallbackTimeInfo,
 }
 
-/// Arguments given to a **NonBlocking** **Input** **Stream**'s **CallbackFn**.
+/// Arguments given to a **NonBlocking** **Duplex** **Stream**'s **CallbackFn**.
 #[derive(Debug, PartialEq)]
 pub struct DuplexCallbackArgs<'a, I: 'a, O: 'a> {
     /// The buffer of interleaved samples read from the **Stream**'s ADC.
+    ///
+    /// This is empty if the input is non-interleaved, in which case `in_planar` is used instead.
     pub in_buffer: &'a [I],
     /// The **Stream**'s output buffer, to which we will write interleaved audio data.
+    ///
+    /// This is empty if the output is non-interleaved, in which case `out_planar` is used
+    /// instead.
     pub out_buffer: &'a mut [O],
+    /// The per-channel input buffers if the input is non-interleaved, otherwise `None`.
+    pub in_planar: Option<PlanarBuffer<'a, I>>,
+    /// The per-channel output buffers if the output is non-interleaved, otherwise `None`.
+    pub out_planar: Option<PlanarBufferMut<'a, O>>,
     /// The number of frames of audio data stored within the `buffer`.
     pub frames: usize,
     /// Flags indicating the current state of the stream and whether or not any special edge cases
@@ -190,6 +422,9 @@ pub struct Blocking<B> {
 
 /// A **Stream** **Mode** representing a non-blocking stream.
 pub struct NonBlocking {
+    /// PortAudio holds a pointer to the wrapper as the stream's `user_data`. It is boxed so that
+    /// the pointer remains valid when the **Stream** is moved, and is only dropped once the
+    /// stream is closed, after which PortAudio no longer calls the callback.
     callback: Box<CallbackFnWrapper>,
 }
 
@@ -253,9 +488,23 @@ pub struct Stream<M, F> {
     pa_stream: *mut ffi::PaStream,
     mode: M,
     flow: F,
+    sample_rate: f64,
+    frames_per_buffer: u32,
+    flags: Flags,
     port_audio_life: std::sync::Arc<super::Life>,
 }
 
+/// Keeps a **Stream** running for as long as it is alive, as returned by **Stream::scoped_start**.
+///
+/// Dropping the guard stops the stream, waiting for pending buffers to be played, unless it has
+/// already been stopped. Errors encountered while stopping on drop are only logged, so use
+/// **StreamGuard::stop** where they need to be handled.
+///
+/// The guard dereferences to the **Stream**, e.g. to read or write from within the scope.
+pub struct StreamGuard<'a, M: 'a, F: 'a> {
+    stream: &'a mut Stream<M, F>,
+}
+
 /// Parameters for one direction (input or output) of a stream.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Parameters<S> {
@@ -274,16 +523,83 @@ pub struct Parameters<S> {
     pub is_interleaved: bool,
     /// Sample format of the audio data provided to/by the device.
     sample_format: std::marker::PhantomData<S>,
+    /// The sample format chosen at runtime for **Raw** parameters, `None` otherwise.
+    raw_format: Option<SampleFormat>,
+    /// Options specific to the device's host API.
+    host_api_stream_info: Option<HostApiSpecificStreamInfo>,
+}
+
+/// Options specific to a particular host API, attached to **Parameters** via
+/// **Parameters::with_host_api_stream_info**.
+///
+/// The options are passed to PortAudio as the `hostApiSpecificStreamInfo` of the stream's
+/// parameters when it is opened. They are ignored if the **Parameters**' device belongs to a
+/// different host API.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HostApiSpecificStreamInfo {
+    /// Options for a WASAPI device.
+    #[cfg(windows)]
+    Wasapi(WasapiStreamInfo),
+    /// Options for an ALSA device.
+    #[cfg(target_os = "linux")]
+    Alsa(AlsaStreamInfo),
+    /// A host API's C struct, attached via **Parameters::with_host_api_specific_info**.
+    Custom(CustomStreamInfo),
+}
+
+/// The largest C struct that may be attached via **Parameters::with_host_api_specific_info**.
+pub const MAX_HOST_API_SPECIFIC_INFO_SIZE: usize = 128;
+
+/// A copy of a host API's `hostApiSpecificStreamInfo` C struct, for host APIs that this crate
+/// has no typed options for.
+///
+/// The struct is stored inline so that **Parameters** remain `Copy`. It is copied into a box
+/// when the stream is opened, which PortAudio is then pointed at for the duration of the call.
+#[derive(Copy, Clone)]
+pub struct CustomStreamInfo {
+    host_api: HostApiTypeId,
+    data: [u64; MAX_HOST_API_SPECIFIC_INFO_SIZE / 8],
+    size: usize,
+}
+
+/// Used in place of a **Sample** type for streams whose **SampleFormat** is only known at
+/// runtime, e.g. a player that switches between `f32` and `i16` files without opening a
+/// differently typed **Stream** for each.
+///
+/// The audio data of such streams is exchanged as bytes, hence the callback arguments and
+/// blocking reads and writes deal in `u8` buffers. Each frame of an interleaved buffer is
+/// `Parameters::bytes_per_frame` bytes long. **Raw** parameters are constructed via
+/// **Parameters::raw** and used to open a stream via the **RawInputSettings**,
+/// **RawOutputSettings** or **RawDuplexSettings**.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Raw {}
+
+/// A builder for **Parameters** that checks them against the device they are for, so that
+/// mistakes such as an invalid channel count are caught before the **Stream** is opened.
+///
+/// Created via **Parameters::builder**.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ParametersBuilder<S> {
+    device: DeviceIndex,
+    channel_count: Option<i32>,
+    is_interleaved: bool,
+    suggested_latency: Option<Duration>,
+    sample_format: std::marker::PhantomData<S>,
 }
 
 /// Settings used to construct an **Input** **Stream**.
+///
+/// These only describe the input direction, so a capture-only stream needs no output
+/// parameters. Opening the stream with them produces an **Input** **Stream**, whose callback is
+/// given **InputCallbackArgs**.
 #[derive(Copy, Clone, Debug)]
 pub struct InputSettings<I> {
     /// The set of Parameters necessary for constructing the **Stream**.
     pub params: Parameters<I>,
     /// The number of audio frames read per second.
     pub sample_rate: f64,
-    /// The number of audio frames that are read per buffer.
+    /// The number of audio frames that are read per buffer, or
+    /// **FRAMES_PER_BUFFER_UNSPECIFIED** to leave it to the host API.
     pub frames_per_buffer: u32,
     /// Any special **Stream** behaviour we require given as a set of flags.
     pub flags: Flags,
@@ -296,7 +612,8 @@ pub struct OutputSettings<O> {
     pub params: Parameters<O>,
     /// The number of audio frames written per second.
     pub sample_rate: f64,
-    /// The number of audio frames requested per buffer.
+    /// The number of audio frames requested per buffer, or
+    /// **FRAMES_PER_BUFFER_UNSPECIFIED** to leave it to the host API.
     pub frames_per_buffer: u32,
     /// Any special **Stream** behaviour we require given as a set of flags.
     pub flags: Flags,
@@ -311,7 +628,52 @@ pub struct DuplexSettings<I, O> {
     pub out_params: Parameters<O>,
     /// The number of audio frames written per second.
     pub sample_rate: f64,
-    /// The number of audio frames requested per buffer.
+    /// The number of audio frames requested per buffer, or
+    /// **FRAMES_PER_BUFFER_UNSPECIFIED** to leave it to the host API.
+    pub frames_per_buffer: u32,
+    /// Any special **Stream** behaviour we require given as a set of flags.
+    pub flags: Flags,
+}
+
+/// Settings used to construct a **RawInput** **Stream**.
+#[derive(Copy, Clone, Debug)]
+pub struct RawInputSettings {
+    /// The set of Parameters necessary for constructing the **Stream**.
+    pub params: Parameters<Raw>,
+    /// The number of audio frames read per second.
+    pub sample_rate: f64,
+    /// The number of audio frames that are read per buffer, or
+    /// **FRAMES_PER_BUFFER_UNSPECIFIED** to leave it to the host API.
+    pub frames_per_buffer: u32,
+    /// Any special **Stream** behaviour we require given as a set of flags.
+    pub flags: Flags,
+}
+
+/// Settings used to construct a **RawOutput** **Stream**.
+#[derive(Copy, Clone, Debug)]
+pub struct RawOutputSettings {
+    /// The set of Parameters necessary for constructing the **Stream**.
+    pub params: Parameters<Raw>,
+    /// The number of audio frames written per second.
+    pub sample_rate: f64,
+    /// The number of audio frames requested per buffer, or
+    /// **FRAMES_PER_BUFFER_UNSPECIFIED** to leave it to the host API.
+    pub frames_per_buffer: u32,
+    /// Any special **Stream** behaviour we require given as a set of flags.
+    pub flags: Flags,
+}
+
+/// Settings used to construct a **RawDuplex** **Stream**.
+#[derive(Copy, Clone, Debug)]
+pub struct RawDuplexSettings {
+    /// The set of Parameters necessary for constructing the input **Stream**.
+    pub in_params: Parameters<Raw>,
+    /// The set of Parameters necessary for constructing the output **Stream**.
+    pub out_params: Parameters<Raw>,
+    /// The number of audio frames written per second.
+    pub sample_rate: f64,
+    /// The number of audio frames requested per buffer, or
+    /// **FRAMES_PER_BUFFER_UNSPECIFIED** to leave it to the host API.
     pub frames_per_buffer: u32,
     /// Any special **Stream** behaviour we require given as a set of flags.
     pub flags: Flags,
@@ -333,7 +695,32 @@ pub struct Duplex<I, O> {
     out_params: Parameters<O>,
 }
 
-unsafe impl Send for NonBlocking {}
+/// A type of **Flow** that describes a **Stream** opened on the default devices via
+/// **PortAudio::open_default_stream_raw**, either direction of which may be absent.
+///
+/// Its callback is given **DuplexCallbackArgs**, the buffers of any absent direction being empty.
+pub struct DefaultDuplex<S> {
+    in_params: Option<Parameters<S>>,
+    out_params: Option<Parameters<S>>,
+}
+
+/// A type of **Flow** that describes an input-only **Stream** with a runtime **SampleFormat**.
+pub struct RawInput {
+    params: Parameters<Raw>,
+}
+
+/// A type of **Flow** that describes an output-only **Stream** with a runtime **SampleFormat**.
+pub struct RawOutput {
+    params: Parameters<Raw>,
+}
+
+/// A type of **Flow** that describes a bi-directional **Stream** with a runtime
+/// **SampleFormat**.
+pub struct RawDuplex {
+    in_params: Parameters<Raw>,
+    out_params: Parameters<Raw>,
+}
+
 unsafe impl<M, F> Send for Stream<M, F>
 where
     M: Send,
@@ -357,6 +744,20 @@ impl<S> Parameters<S> {
         )
     }
 
+    /// Begin building **Parameters** for the given device.
+    ///
+    /// Unlike **Parameters::new**, the built parameters are validated against the device's
+    /// **DeviceInfo**.
+    pub fn builder(device: DeviceIndex) -> ParametersBuilder<S> {
+        ParametersBuilder {
+            device: device,
+            channel_count: None,
+            is_interleaved: true,
+            suggested_latency: None,
+            sample_format: std::marker::PhantomData,
+        }
+    }
+
     /// The same as **Parameters::new**, but the device(s) to be used are specified in the host
     /// api specific stream info structure.
     ///
@@ -370,6 +771,74 @@ impl<S> Parameters<S> {
         Self::new_internal(kind, channel_count, is_interleaved, suggested_latency)
     }
 
+    /// Attach options specific to the device's host API, such as a
+    /// [**WasapiStreamInfo**](../ext/wasapi/struct.WasapiStreamInfo.html) on Windows or an
+    /// [**AlsaStreamInfo**](../ext/alsa/struct.AlsaStreamInfo.html) on Linux.
+    ///
+    /// The options are ignored when opening a stream if the device belongs to a different host
+    /// API, so they may be attached regardless of which host API the user selected.
+    pub fn with_host_api_stream_info<T>(mut self, info: T) -> Self
+    where
+        T: Into<HostApiSpecificStreamInfo>,
+    {
+        self.host_api_stream_info = Some(info.into());
+        self
+    }
+
+    /// Attach a host API's `hostApiSpecificStreamInfo` C struct, e.g. a `PaAsioStreamInfo`, for
+    /// host APIs that have no typed options in this crate.
+    ///
+    /// The struct is copied, so `info` need not outlive the **Parameters**. As with
+    /// **Parameters::with_host_api_stream_info**, it is ignored when opening a stream on a
+    /// device of a host API other than `host_api`.
+    ///
+    /// # Safety
+    ///
+    /// PortAudio reads the struct through a `void` pointer, so:
+    ///
+    /// - `T` must be `#[repr(C)]` with the layout that `host_api`'s PortAudio header declares,
+    ///   beginning with the `size`, `hostApiType` and `version` fields, which must be filled in
+    ///   accordingly.
+    /// - Any pointers within `info`, such as a channel selector array, must remain valid until
+    ///   every **Stream** opened with these **Parameters** has been opened, including by
+    ///   `Stream::reopen`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is larger than **MAX_HOST_API_SPECIFIC_INFO_SIZE** bytes or is aligned to
+    /// more than 8 bytes.
+    pub unsafe fn with_host_api_specific_info<T>(
+        mut self,
+        host_api: HostApiTypeId,
+        info: &T,
+    ) -> Self
+    where
+        T: Copy,
+    {
+        let size = std::mem::size_of::<T>();
+        assert!(
+            size <= MAX_HOST_API_SPECIFIC_INFO_SIZE && std::mem::align_of::<T>() <= 8,
+            "host API specific stream info is too large"
+        );
+        let mut custom = CustomStreamInfo {
+            host_api: host_api,
+            data: [0; MAX_HOST_API_SPECIFIC_INFO_SIZE / 8],
+            size: size,
+        };
+        ptr::copy_nonoverlapping(
+            info as *const T as *const u8,
+            custom.data.as_mut_ptr() as *mut u8,
+            size,
+        );
+        self.host_api_stream_info = Some(HostApiSpecificStreamInfo::Custom(custom));
+        self
+    }
+
+    /// The host-API-specific options attached via **Parameters::with_host_api_stream_info**.
+    pub fn host_api_stream_info(&self) -> Option<HostApiSpecificStreamInfo> {
+        self.host_api_stream_info
+    }
+
     fn new_internal(
         device_kind: DeviceKind,
         channel_count: i32,
@@ -382,41 +851,220 @@ impl<S> Parameters<S> {
             is_interleaved: is_interleaved,
             suggested_latency: suggested_latency,
             sample_format: std::marker::PhantomData,
+            raw_format: None,
+            host_api_stream_info: None,
+        }
+    }
+}
+
+impl Parameters<Raw> {
+    /// Construct a new **Parameters** for a stream with the given runtime **SampleFormat**.
+    pub fn raw(
+        device: DeviceIndex,
+        channel_count: i32,
+        sample_format: SampleFormat,
+        is_interleaved: bool,
+        suggested_latency: Time,
+    ) -> Self {
+        let mut params = Self::new(device, channel_count, is_interleaved, suggested_latency);
+        params.raw_format = Some(sample_format);
+        params
+    }
+
+    /// The **SampleFormat** of the stream.
+    ///
+    /// This is **SampleFormat::Unknown** if the parameters were not constructed via
+    /// **Parameters::raw**.
+    pub fn sample_format(&self) -> SampleFormat {
+        self.raw_format.unwrap_or(SampleFormat::Unknown)
+    }
+
+    /// The number of bytes in a single frame of an interleaved buffer, i.e. the size of a sample
+    /// multiplied by the channel count.
+    pub fn bytes_per_frame(&self) -> usize {
+        self.sample_format().size_in_bytes() as usize * self.channel_count as usize
+    }
+}
+
+impl<S> ParametersBuilder<S> {
+    /// The number of channels to use.
+    ///
+    /// Defaults to all of the device's channels in the direction that is built.
+    pub fn channels(mut self, channel_count: i32) -> Self {
+        self.channel_count = Some(channel_count);
+        self
+    }
+
+    /// Whether the audio data is interleaved. Defaults to `true`.
+    pub fn interleaved(mut self, is_interleaved: bool) -> Self {
+        self.is_interleaved = is_interleaved;
+        self
+    }
+
+    /// The latency to suggest to PortAudio.
+    ///
+    /// Defaults to the device's default low latency in the direction that is built.
+    pub fn suggested_latency(mut self, latency: Duration) -> Self {
+        self.suggested_latency = Some(latency);
+        self
+    }
+
+    /// Build **Parameters** for the input of a **Stream**.
+    ///
+    /// Returns `Err(Error::InvalidDevice)` if the device does not exist and
+    /// `Err(Error::InvalidChannelCount)` if the channel count is zero or exceeds the device's
+    /// `max_input_channels`.
+    pub fn build_input(&self, port_audio: &super::PortAudio) -> Result<Parameters<S>, Error> {
+        let info = port_audio.device_info(self.device)?;
+        self.build(info.max_input_channels, info.default_low_input_latency)
+    }
+
+    /// Build **Parameters** for the output of a **Stream**.
+    ///
+    /// Returns `Err(Error::InvalidDevice)` if the device does not exist and
+    /// `Err(Error::InvalidChannelCount)` if the channel count is zero or exceeds the device's
+    /// `max_output_channels`.
+    pub fn build_output(&self, port_audio: &super::PortAudio) -> Result<Parameters<S>, Error> {
+        let info = port_audio.device_info(self.device)?;
+        self.build(info.max_output_channels, info.default_low_output_latency)
+    }
+
+    fn build(&self, max_channels: i32, default_latency: Time) -> Result<Parameters<S>, Error> {
+        let channel_count = self.channel_count.unwrap_or(max_channels);
+        if channel_count <= 0 || channel_count > max_channels {
+            return Err(Error::InvalidChannelCount);
         }
+        let suggested_latency = self
+            .suggested_latency
+            .map(|latency| latency.as_secs_f64())
+            .unwrap_or(default_latency);
+        Ok(Parameters::new(
+            self.device,
+            channel_count,
+            self.is_interleaved,
+            suggested_latency,
+        ))
     }
 }
 
+/// Implements a builder method for each of the **StreamFlags** on a Settings type.
+macro_rules! impl_settings_flag_methods {
+    () => {
+        /// Disable PortAudio's default clipping of out of range samples, e.g. to overdrive the
+        /// output deliberately (`paClipOff`).
+        pub fn clip_off(mut self) -> Self {
+            self.flags.insert(flags::CLIP_OFF);
+            self
+        }
+
+        /// Disable PortAudio's default dithering, e.g. for measurement work (`paDitherOff`).
+        pub fn dither_off(mut self) -> Self {
+            self.flags.insert(flags::DITHER_OFF);
+            self
+        }
+
+        /// Request that, where possible, a full duplex stream never discards overflowed input
+        /// samples without calling the stream callback (`paNeverDropInput`).
+        ///
+        /// PortAudio only accepts this flag for full duplex streams with an unspecified
+        /// `frames_per_buffer`.
+        pub fn never_drop_input(mut self) -> Self {
+            self.flags.insert(flags::NEVER_DROP_INPUT);
+            self
+        }
+
+        /// Call the stream callback to fill the initial output buffers, rather than priming them
+        /// with silence (`paPrimeOutputBuffersUsingStreamCallback`).
+        pub fn prime_output_buffers_using_stream_callback(mut self) -> Self {
+            self.flags
+                .insert(flags::PA_PRIME_OUTPUT_BUFFERS_USING_STREAM_CALLBACK);
+            self
+        }
+    };
+}
+
 /// Simplify implementation of one-way-Stream Settings types.
 macro_rules! impl_half_duplex_settings {
     ($name:ident) => {
         impl<S> $name<S> {
-            /// Construct the settings from the given `params`, `sample_rate` and
-            /// `frames_per_buffer` with an empty set of **StreamFlags**.
-            pub fn new(params: Parameters<S>, sample_rate: f64, frames_per_buffer: u32) -> Self {
-                Self::with_flags(params, sample_rate, frames_per_buffer, Flags::empty())
-            }
+            impl_half_duplex_settings!(@methods $name, S);
+        }
+    };
+    ($name:ident, $sample:ty) => {
+        impl $name {
+            impl_half_duplex_settings!(@methods $name, $sample);
+        }
+    };
+    (@methods $name:ident, $sample:ty) => {
+        /// Construct the settings from the given `params`, `sample_rate` and
+        /// `frames_per_buffer` with an empty set of **StreamFlags**.
+        pub fn new(params: Parameters<$sample>, sample_rate: f64, frames_per_buffer: u32) -> Self {
+            Self::with_flags(params, sample_rate, frames_per_buffer, Flags::empty())
+        }
 
-            /// Construct the settings with the given **Parameters**, `sample_rate`,
-            /// `frames_per_buffer` and **StreamFlags**.
-            pub fn with_flags(
-                params: Parameters<S>,
-                sample_rate: f64,
-                frames_per_buffer: u32,
-                flags: Flags,
-            ) -> Self {
-                $name {
-                    params: params,
-                    sample_rate: sample_rate,
-                    frames_per_buffer: frames_per_buffer,
-                    flags: flags,
-                }
+        /// Construct the settings with the given **Parameters**, `sample_rate`,
+        /// `frames_per_buffer` and **StreamFlags**.
+        pub fn with_flags(
+            params: Parameters<$sample>,
+            sample_rate: f64,
+            frames_per_buffer: u32,
+            flags: Flags,
+        ) -> Self {
+            $name {
+                params: params,
+                sample_rate: sample_rate,
+                frames_per_buffer: frames_per_buffer,
+                flags: flags,
             }
         }
+
+        impl_settings_flag_methods!();
     };
 }
 
 impl_half_duplex_settings!(OutputSettings);
 impl_half_duplex_settings!(InputSettings);
+impl_half_duplex_settings!(RawOutputSettings, Raw);
+impl_half_duplex_settings!(RawInputSettings, Raw);
+
+impl RawDuplexSettings {
+    /// Construct the settings from the given `params`, `sample_rate` and
+    /// `frames_per_buffer` with an empty set of **StreamFlags**.
+    pub fn new(
+        in_params: Parameters<Raw>,
+        out_params: Parameters<Raw>,
+        sample_rate: f64,
+        frames_per_buffer: u32,
+    ) -> Self {
+        Self::with_flags(
+            in_params,
+            out_params,
+            sample_rate,
+            frames_per_buffer,
+            Flags::empty(),
+        )
+    }
+
+    /// Construct the settings with the given **Parameters**, `sample_rate`,
+    /// `frames_per_buffer` and **StreamFlags**.
+    pub fn with_flags(
+        in_params: Parameters<Raw>,
+        out_params: Parameters<Raw>,
+        sample_rate: f64,
+        frames_per_buffer: u32,
+        flags: Flags,
+    ) -> Self {
+        RawDuplexSettings {
+            in_params: in_params,
+            out_params: out_params,
+            sample_rate: sample_rate,
+            frames_per_buffer: frames_per_buffer,
+            flags: flags,
+        }
+    }
+
+    impl_settings_flag_methods!();
+}
 
 impl<I, O> DuplexSettings<I, O> {
     /// Construct the settings from the given `params`, `sample_rate` and
@@ -453,6 +1101,8 @@ impl<I, O> DuplexSettings<I, O> {
             flags: flags,
         }
     }
+
+    impl_settings_flag_methods!();
 }
 
 impl<I> Flow for Input<I>
@@ -477,36 +1127,25 @@ where
         (Some(self.params.into()), None)
     }
 
-    fn new_callback_args(
+    fn host_api_stream_info_both_directions(
+        &self,
+    ) -> (
+        Option<HostApiSpecificStreamInfo>,
+        Option<HostApiSpecificStreamInfo>,
+    ) {
+        (self.params.host_api_stream_info, None)
+    }
+
+    unsafe fn new_callback_args(
         input: *const raw::c_void,
         _output: *mut raw::c_void,
         frame_count: raw::c_ulong,
         time_info: *const ffi::PaStreamCallbackTimeInfo,
         flags: ffi::PaStreamCallbackFlags,
-        in_channels: i32,
-        _out_channels: i32,
+        in_layout: BufferLayout,
+        _out_layout: BufferLayout,
     ) -> Self::CallbackArgs {
-        let flags = CallbackFlags::from_bits(flags).unwrap_or_else(|| CallbackFlags::empty());
-        let time = unsafe {
-            InputCallbackTimeInfo {
-                current: (*time_info).currentTime,
-                buffer_adc: (*time_info).inputBufferAdcTime,
-            }
-        };
-        // TODO: At the moment, we assume the buffer is interleaved. We need to check whether or
-        // not buffer is interleaved here. This should probably an extra type parameter (along-side
-        // the Sample type param).
-        let buffer: &[I] = {
-            let buffer_len = in_channels as usize * frame_count as usize;
-            let buffer_ptr = input as *const I;
-            unsafe { std::slice::from_raw_parts(buffer_ptr, buffer_len) }
-        };
-        InputCallbackArgs {
-            buffer: buffer,
-            frames: frame_count as usize,
-            flags: flags,
-            time: time,
-        }
+        input_callback_args(input, frame_count, time_info, flags, in_layout, 1)
     }
 }
 
@@ -527,41 +1166,30 @@ where
         (None, Some(self.params.into()))
     }
 
-    fn new_buffer(&self, frames_per_buffer: u32) -> Self::Buffer {
-        let channel_count = self.params.channel_count;
-        Buffer::new::<O>(frames_per_buffer, channel_count)
-    }
+    fn host_api_stream_info_both_directions(
+        &self,
+    ) -> (
+        Option<HostApiSpecificStreamInfo>,
+        Option<HostApiSpecificStreamInfo>,
+    ) {
+        (None, self.params.host_api_stream_info)
+    }
+
+    fn new_buffer(&self, frames_per_buffer: u32) -> Self::Buffer {
+        let channel_count = self.params.channel_count;
+        Buffer::new::<O>(frames_per_buffer, channel_count)
+    }
 
-    fn new_callback_args(
+    unsafe fn new_callback_args(
         _input: *const raw::c_void,
         output: *mut raw::c_void,
         frame_count: raw::c_ulong,
         time_info: *const ffi::PaStreamCallbackTimeInfo,
         flags: ffi::PaStreamCallbackFlags,
-        _in_channels: i32,
-        out_channels: i32,
+        _in_layout: BufferLayout,
+        out_layout: BufferLayout,
     ) -> Self::CallbackArgs {
-        let flags = CallbackFlags::from_bits(flags).unwrap_or_else(|| CallbackFlags::empty());
-        let time = unsafe {
-            OutputCallbackTimeInfo {
-                current: (*time_info).currentTime,
-                buffer_dac: (*time_info).outputBufferDacTime,
-            }
-        };
-        // TODO: At the moment, we assume the buffer is interleaved. We need to check whether or
-        // not buffer is interleaved here. This should probably an extra type parameter (along-side
-        // the Sample type param).
-        let buffer: &mut [O] = {
-            let buffer_len = out_channels as usize * frame_count as usize;
-            let buffer_ptr = output as *mut O;
-            unsafe { std::slice::from_raw_parts_mut(buffer_ptr, buffer_len) }
-        };
-        OutputCallbackArgs {
-            buffer: buffer,
-            frames: frame_count as usize,
-            flags: flags,
-            time: time,
-        }
+        output_callback_args(output, frame_count, time_info, flags, out_layout, 1)
     }
 }
 
@@ -583,6 +1211,18 @@ where
         (Some(self.in_params.into()), Some(self.out_params.into()))
     }
 
+    fn host_api_stream_info_both_directions(
+        &self,
+    ) -> (
+        Option<HostApiSpecificStreamInfo>,
+        Option<HostApiSpecificStreamInfo>,
+    ) {
+        (
+            self.in_params.host_api_stream_info,
+            self.out_params.host_api_stream_info,
+        )
+    }
+
     fn new_buffer(&self, frames_per_buffer: u32) -> Self::Buffer {
         let in_channel_count = self.in_params.channel_count;
         let in_buffer = Buffer::new::<I>(frames_per_buffer, in_channel_count);
@@ -591,43 +1231,220 @@ where
         (in_buffer, out_buffer)
     }
 
-    fn new_callback_args(
+    unsafe fn new_callback_args(
         input: *const raw::c_void,
         output: *mut raw::c_void,
         frame_count: raw::c_ulong,
         time_info: *const ffi::PaStreamCallbackTimeInfo,
         flags: ffi::PaStreamCallbackFlags,
-        in_channels: i32,
-        out_channels: i32,
+        in_layout: BufferLayout,
+        out_layout: BufferLayout,
     ) -> Self::CallbackArgs {
-        let flags = CallbackFlags::from_bits(flags).unwrap_or_else(|| CallbackFlags::empty());
-        let time = unsafe {
-            DuplexCallbackTimeInfo {
-                current: (*time_info).currentTime,
-                in_buffer_adc: (*time_info).inputBufferAdcTime,
-                out_buffer_dac: (*time_info).outputBufferDacTime,
-            }
-        };
-        // TODO: At the moment, we assume these buffers are interleaved. We need to check whether
-        // or not buffer is interleaved here. This should probably an extra type parameter
-        // (along-side the Sample type param).
-        let in_buffer: &[I] = {
-            let buffer_len = in_channels as usize * frame_count as usize;
-            let buffer_ptr = input as *const I;
-            unsafe { std::slice::from_raw_parts(buffer_ptr, buffer_len) }
-        };
-        let out_buffer: &mut [O] = {
-            let buffer_len = out_channels as usize * frame_count as usize;
-            let buffer_ptr = output as *mut O;
-            unsafe { std::slice::from_raw_parts_mut(buffer_ptr, buffer_len) }
-        };
-        DuplexCallbackArgs {
-            in_buffer: in_buffer,
-            out_buffer: out_buffer,
-            frames: frame_count as usize,
-            flags: flags,
-            time: time,
-        }
+        let layouts = (in_layout, out_layout);
+        duplex_callback_args(
+            input,
+            output,
+            frame_count,
+            time_info,
+            flags,
+            layouts,
+            (1, 1),
+        )
+    }
+}
+
+impl<S> Flow for DefaultDuplex<S>
+where
+    S: Sample + 'static,
+{
+    type Buffer = (Buffer, Buffer);
+    type CallbackArgs = DuplexCallbackArgs<'static, S, S>;
+    type CallbackTimeInfo = DuplexCallbackTimeInfo;
+
+    fn params_both_directions(
+        &self,
+    ) -> (
+        Option<ffi::PaStreamParameters>,
+        Option<ffi::PaStreamParameters>,
+    ) {
+        (
+            self.in_params.map(Into::into),
+            self.out_params.map(Into::into),
+        )
+    }
+
+    fn new_buffer(&self, frames_per_buffer: u32) -> Self::Buffer {
+        let channel_count = |params: Option<Parameters<S>>| params.map_or(0, |p| p.channel_count);
+        let in_buffer = Buffer::new::<S>(frames_per_buffer, channel_count(self.in_params));
+        let out_buffer = Buffer::new::<S>(frames_per_buffer, channel_count(self.out_params));
+        (in_buffer, out_buffer)
+    }
+
+    unsafe fn new_callback_args(
+        input: *const raw::c_void,
+        output: *mut raw::c_void,
+        frame_count: raw::c_ulong,
+        time_info: *const ffi::PaStreamCallbackTimeInfo,
+        flags: ffi::PaStreamCallbackFlags,
+        in_layout: BufferLayout,
+        out_layout: BufferLayout,
+    ) -> Self::CallbackArgs {
+        let layouts = (in_layout, out_layout);
+        duplex_callback_args(
+            input,
+            output,
+            frame_count,
+            time_info,
+            flags,
+            layouts,
+            (1, 1),
+        )
+    }
+}
+
+/// Construct the **InputCallbackArgs** from the raw stream callback arguments.
+///
+/// `lanes` is the number of elements of type **I** that make up a single sample, which is `1`
+/// for **Sample** types and the sample size for **Raw** byte buffers.
+unsafe fn input_callback_args<'a, I>(
+    input: *const raw::c_void,
+    frame_count: raw::c_ulong,
+    time_info: *const ffi::PaStreamCallbackTimeInfo,
+    flags: ffi::PaStreamCallbackFlags,
+    layout: BufferLayout,
+    lanes: usize,
+) -> InputCallbackArgs<'a, I> {
+    let flags = CallbackFlags::from_bits(flags).unwrap_or_else(CallbackFlags::empty);
+    let time = InputCallbackTimeInfo {
+        current: (*time_info).currentTime,
+        buffer_adc: (*time_info).inputBufferAdcTime,
+    };
+    let frames = frame_count as usize;
+    let (buffer, planar) =
+        input_buffers(input, layout.channels, frames * lanes, layout.interleaved);
+    InputCallbackArgs {
+        buffer: buffer,
+        planar: planar,
+        frames: frames,
+        flags: flags,
+        time: time,
+    }
+}
+
+/// Construct the **OutputCallbackArgs** from the raw stream callback arguments.
+///
+/// See **input_callback_args** for a description of `lanes`.
+unsafe fn output_callback_args<'a, O>(
+    output: *mut raw::c_void,
+    frame_count: raw::c_ulong,
+    time_info: *const ffi::PaStreamCallbackTimeInfo,
+    flags: ffi::PaStreamCallbackFlags,
+    layout: BufferLayout,
+    lanes: usize,
+) -> OutputCallbackArgs<'a, O> {
+    let flags = CallbackFlags::from_bits(flags).unwrap_or_else(CallbackFlags::empty);
+    let time = OutputCallbackTimeInfo {
+        current: (*time_info).currentTime,
+        buffer_dac: (*time_info).outputBufferDacTime,
+    };
+    let frames = frame_count as usize;
+    let (buffer, planar) =
+        output_buffers(output, layout.channels, frames * lanes, layout.interleaved);
+    OutputCallbackArgs {
+        buffer: buffer,
+        planar: planar,
+        frames: frames,
+        flags: flags,
+        time: time,
+    }
+}
+
+/// Construct the **DuplexCallbackArgs** from the raw stream callback arguments.
+///
+/// See **input_callback_args** for a description of the input and output `lanes`.
+unsafe fn duplex_callback_args<'a, I, O>(
+    input: *const raw::c_void,
+    output: *mut raw::c_void,
+    frame_count: raw::c_ulong,
+    time_info: *const ffi::PaStreamCallbackTimeInfo,
+    flags: ffi::PaStreamCallbackFlags,
+    (in_layout, out_layout): (BufferLayout, BufferLayout),
+    (in_lanes, out_lanes): (usize, usize),
+) -> DuplexCallbackArgs<'a, I, O> {
+    let flags = CallbackFlags::from_bits(flags).unwrap_or_else(CallbackFlags::empty);
+    let time = DuplexCallbackTimeInfo {
+        current: (*time_info).currentTime,
+        in_buffer_adc: (*time_info).inputBufferAdcTime,
+        out_buffer_dac: (*time_info).outputBufferDacTime,
+    };
+    let frames = frame_count as usize;
+    let (in_buffer, in_planar) = input_buffers(
+        input,
+        in_layout.channels,
+        frames * in_lanes,
+        in_layout.interleaved,
+    );
+    let (out_buffer, out_planar) = output_buffers(
+        output,
+        out_layout.channels,
+        frames * out_lanes,
+        out_layout.interleaved,
+    );
+    DuplexCallbackArgs {
+        in_buffer: in_buffer,
+        out_buffer: out_buffer,
+        in_planar: in_planar,
+        out_planar: out_planar,
+        frames: frames,
+        flags: flags,
+        time: time,
+    }
+}
+
+/// Interpret the input buffer given to the stream callback, which is either a single buffer of
+/// interleaved samples or an array of pointers to each channel's buffer.
+unsafe fn input_buffers<'a, I>(
+    input: *const raw::c_void,
+    channels: i32,
+    frames: usize,
+    interleaved: bool,
+) -> (&'a [I], Option<PlanarBuffer<'a, I>>) {
+    // A **DefaultDuplex** stream without an input is given a null input buffer.
+    if input.is_null() {
+        return (&[], None);
+    }
+    if interleaved {
+        let buffer_len = channels as usize * frames;
+        (
+            std::slice::from_raw_parts(input as *const I, buffer_len),
+            None,
+        )
+    } else {
+        let planar = PlanarBuffer::from_raw(input as *const *const I, channels, frames);
+        (&[], Some(planar))
+    }
+}
+
+/// Interpret the output buffer given to the stream callback, which is either a single buffer of
+/// interleaved samples or an array of pointers to each channel's buffer.
+unsafe fn output_buffers<'a, O>(
+    output: *mut raw::c_void,
+    channels: i32,
+    frames: usize,
+    interleaved: bool,
+) -> (&'a mut [O], Option<PlanarBufferMut<'a, O>>) {
+    if output.is_null() {
+        return (&mut [], None);
+    }
+    if interleaved {
+        let buffer_len = channels as usize * frames;
+        (
+            std::slice::from_raw_parts_mut(output as *mut O, buffer_len),
+            None,
+        )
+    } else {
+        let planar = PlanarBufferMut::from_raw(output as *const *mut O, channels, frames);
+        (&mut [], Some(planar))
     }
 }
 
@@ -685,11 +1502,192 @@ where
     }
 }
 
+impl Flow for RawInput {
+    type Buffer = Buffer;
+    type CallbackArgs = InputCallbackArgs<'static, u8>;
+    type CallbackTimeInfo = InputCallbackTimeInfo;
+
+    fn new_buffer(&self, frames_per_buffer: u32) -> Self::Buffer {
+        Buffer::new::<u8>(frames_per_buffer, self.params.bytes_per_frame() as i32)
+    }
+
+    fn params_both_directions(
+        &self,
+    ) -> (
+        Option<ffi::PaStreamParameters>,
+        Option<ffi::PaStreamParameters>,
+    ) {
+        (Some(self.params.into()), None)
+    }
+
+    fn host_api_stream_info_both_directions(
+        &self,
+    ) -> (
+        Option<HostApiSpecificStreamInfo>,
+        Option<HostApiSpecificStreamInfo>,
+    ) {
+        (self.params.host_api_stream_info, None)
+    }
+
+    unsafe fn new_callback_args(
+        input: *const raw::c_void,
+        _output: *mut raw::c_void,
+        frame_count: raw::c_ulong,
+        time_info: *const ffi::PaStreamCallbackTimeInfo,
+        flags: ffi::PaStreamCallbackFlags,
+        in_layout: BufferLayout,
+        _out_layout: BufferLayout,
+    ) -> Self::CallbackArgs {
+        let lanes = in_layout.sample_size;
+        input_callback_args(input, frame_count, time_info, flags, in_layout, lanes)
+    }
+}
+
+impl Flow for RawOutput {
+    type Buffer = Buffer;
+    type CallbackArgs = OutputCallbackArgs<'static, u8>;
+    type CallbackTimeInfo = OutputCallbackTimeInfo;
+
+    fn new_buffer(&self, frames_per_buffer: u32) -> Self::Buffer {
+        Buffer::new::<u8>(frames_per_buffer, self.params.bytes_per_frame() as i32)
+    }
+
+    fn params_both_directions(
+        &self,
+    ) -> (
+        Option<ffi::PaStreamParameters>,
+        Option<ffi::PaStreamParameters>,
+    ) {
+        (None, Some(self.params.into()))
+    }
+
+    fn host_api_stream_info_both_directions(
+        &self,
+    ) -> (
+        Option<HostApiSpecificStreamInfo>,
+        Option<HostApiSpecificStreamInfo>,
+    ) {
+        (None, self.params.host_api_stream_info)
+    }
+
+    unsafe fn new_callback_args(
+        _input: *const raw::c_void,
+        output: *mut raw::c_void,
+        frame_count: raw::c_ulong,
+        time_info: *const ffi::PaStreamCallbackTimeInfo,
+        flags: ffi::PaStreamCallbackFlags,
+        _in_layout: BufferLayout,
+        out_layout: BufferLayout,
+    ) -> Self::CallbackArgs {
+        let lanes = out_layout.sample_size;
+        output_callback_args(output, frame_count, time_info, flags, out_layout, lanes)
+    }
+}
+
+impl Flow for RawDuplex {
+    type Buffer = (Buffer, Buffer);
+    type CallbackArgs = DuplexCallbackArgs<'static, u8, u8>;
+    type CallbackTimeInfo = DuplexCallbackTimeInfo;
+
+    fn new_buffer(&self, frames_per_buffer: u32) -> Self::Buffer {
+        let in_bytes_per_frame = self.in_params.bytes_per_frame() as i32;
+        let out_bytes_per_frame = self.out_params.bytes_per_frame() as i32;
+        (
+            Buffer::new::<u8>(frames_per_buffer, in_bytes_per_frame),
+            Buffer::new::<u8>(frames_per_buffer, out_bytes_per_frame),
+        )
+    }
+
+    fn params_both_directions(
+        &self,
+    ) -> (
+        Option<ffi::PaStreamParameters>,
+        Option<ffi::PaStreamParameters>,
+    ) {
+        (Some(self.in_params.into()), Some(self.out_params.into()))
+    }
+
+    fn host_api_stream_info_both_directions(
+        &self,
+    ) -> (
+        Option<HostApiSpecificStreamInfo>,
+        Option<HostApiSpecificStreamInfo>,
+    ) {
+        (
+            self.in_params.host_api_stream_info,
+            self.out_params.host_api_stream_info,
+        )
+    }
+
+    unsafe fn new_callback_args(
+        input: *const raw::c_void,
+        output: *mut raw::c_void,
+        frame_count: raw::c_ulong,
+        time_info: *const ffi::PaStreamCallbackTimeInfo,
+        flags: ffi::PaStreamCallbackFlags,
+        in_layout: BufferLayout,
+        out_layout: BufferLayout,
+    ) -> Self::CallbackArgs {
+        let lanes = (in_layout.sample_size, out_layout.sample_size);
+        let layouts = (in_layout, out_layout);
+        duplex_callback_args(input, output, frame_count, time_info, flags, layouts, lanes)
+    }
+}
+
+// The "channels" of a **Raw** stream's blocking buffer are its bytes, so that a frame is read or
+// written as `bytes_per_frame` `u8`s.
+
+impl Reader for RawInput {
+    type Sample = u8;
+    fn readable_buffer(blocking: &Blocking<<RawInput as Flow>::Buffer>) -> &Buffer {
+        &blocking.buffer
+    }
+    fn channel_count(&self) -> i32 {
+        self.params.bytes_per_frame() as i32
+    }
+}
+
+impl Reader for RawDuplex {
+    type Sample = u8;
+    fn readable_buffer(blocking: &Blocking<<RawDuplex as Flow>::Buffer>) -> &Buffer {
+        &blocking.buffer.0
+    }
+    fn channel_count(&self) -> i32 {
+        self.in_params.bytes_per_frame() as i32
+    }
+}
+
+impl Writer for RawOutput {
+    type Sample = u8;
+    fn writable_buffer(blocking: &mut Blocking<<RawOutput as Flow>::Buffer>) -> &mut Buffer {
+        &mut blocking.buffer
+    }
+    fn channel_count(&self) -> i32 {
+        self.params.bytes_per_frame() as i32
+    }
+}
+
+impl Writer for RawDuplex {
+    type Sample = u8;
+    fn writable_buffer(blocking: &mut Blocking<<RawDuplex as Flow>::Buffer>) -> &mut Buffer {
+        &mut blocking.buffer.1
+    }
+    fn channel_count(&self) -> i32 {
+        self.out_params.bytes_per_frame() as i32
+    }
+}
+
 /// The buffer used to transfer audio data between the input and output streams.
 pub struct Buffer {
     data: *mut libc::c_void,
+    /// The size of the allocation in bytes.
+    len: usize,
 }
 
+// The buffer is an allocation owned by the **Buffer**, so it may be moved to another thread along
+// with its blocking **Stream**.
+unsafe impl Send for Buffer {}
+
 pub mod flags {
     //! A type safe wrapper around PortAudio's stream flags.
     use ffi;
@@ -750,6 +1748,20 @@ pub enum Available {
     OutputUnderflowed,
 }
 
+/// The state of a stream, as determined by **Stream::state** from both `Pa_IsStreamStopped` and
+/// `Pa_IsStreamActive`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum State {
+    /// The stream hasn't been started, or has been stopped or aborted since.
+    Stopped,
+    /// The stream is running, i.e. playing or recording audio.
+    Active,
+    /// The stream callback returned **Complete** or **Abort** and the last buffer has been
+    /// played, but the stream hasn't been stopped yet. Call **Stream::stop** to leave this state
+    /// before starting the stream again.
+    Finished,
+}
+
 pub mod callback_flags {
     //! A type safe wrapper around PortAudio's stream callback flags.
     use ffi;
@@ -779,6 +1791,40 @@ pub mod callback_flags {
         }
     }
 
+    impl CallbackFlags {
+        /// Whether zero samples were inserted into the input buffer to compensate for an input
+        /// underflow.
+        pub fn input_underflow(&self) -> bool {
+            self.contains(INPUT_UNDERFLOW)
+        }
+
+        /// Whether input data was discarded due to an input overflow.
+        pub fn input_overflow(&self) -> bool {
+            self.contains(INPUT_OVERFLOW)
+        }
+
+        /// Whether output data (or a gap) was inserted due to an output underflow, e.g. because
+        /// the callback is taking too long.
+        pub fn output_underflow(&self) -> bool {
+            self.contains(OUTPUT_UNDERFLOW)
+        }
+
+        /// Whether output data will be discarded because no room is available.
+        pub fn output_overflow(&self) -> bool {
+            self.contains(OUTPUT_OVERFLOW)
+        }
+
+        /// Whether any input or output underflow or overflow is reported.
+        pub fn has_xrun(&self) -> bool {
+            self.intersects(INPUT_UNDERFLOW | INPUT_OVERFLOW | OUTPUT_UNDERFLOW | OUTPUT_OVERFLOW)
+        }
+
+        /// Whether the output of this callback is being used to prime the stream.
+        pub fn priming_output(&self) -> bool {
+            self.contains(PRIMING_OUTPUT)
+        }
+    }
+
     impl ::std::fmt::Display for CallbackFlags {
         fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
             write!(
@@ -798,31 +1844,78 @@ pub mod callback_flags {
     }
 }
 
-/// Timing information for the buffers passed to the stream callback.
+/// All timing information PortAudio provides for the buffers passed to the stream callback,
+/// regardless of the **Stream**'s **Flow**.
+///
+/// The callback arguments of each **Flow** carry only the relevant subset of these times (e.g.
+/// **OutputCallbackTimeInfo**), which can be converted into this type.
 ///
 /// Time values are expressed in seconds and are synchronised with the time base used by
 /// `Stream::time` method for the associated stream.
-#[doc(hidden)]
-#[derive(Clone, Copy, Debug)]
-#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CallbackTimeInfo {
-    /// The time when the first sample of the input buffer was captured by the
+    /// The time when the first sample of the input buffer was captured at the ADC input, or `0.0`
+    /// for an output-only stream.
     pub input_buffer_adc_time: Time,
-    /// The time when the tream callback was invoked.
+    /// The time when the stream callback was invoked.
     pub current_time: Time,
+    /// The time when the first sample of the output buffer will output the DAC, or `0.0` for an
+    /// input-only stream.
     pub output_buffer_dac_time: Time,
 }
 
+impl From<ffi::PaStreamCallbackTimeInfo> for CallbackTimeInfo {
+    fn from(info: ffi::PaStreamCallbackTimeInfo) -> Self {
+        CallbackTimeInfo {
+            input_buffer_adc_time: info.inputBufferAdcTime,
+            current_time: info.currentTime,
+            output_buffer_dac_time: info.outputBufferDacTime,
+        }
+    }
+}
+
+impl From<InputCallbackTimeInfo> for CallbackTimeInfo {
+    fn from(info: InputCallbackTimeInfo) -> Self {
+        CallbackTimeInfo {
+            input_buffer_adc_time: info.buffer_adc,
+            current_time: info.current,
+            output_buffer_dac_time: 0.0,
+        }
+    }
+}
+
+impl From<OutputCallbackTimeInfo> for CallbackTimeInfo {
+    fn from(info: OutputCallbackTimeInfo) -> Self {
+        CallbackTimeInfo {
+            input_buffer_adc_time: 0.0,
+            current_time: info.current,
+            output_buffer_dac_time: info.buffer_dac,
+        }
+    }
+}
+
+impl From<DuplexCallbackTimeInfo> for CallbackTimeInfo {
+    fn from(info: DuplexCallbackTimeInfo) -> Self {
+        CallbackTimeInfo {
+            input_buffer_adc_time: info.in_buffer_adc,
+            current_time: info.current,
+            output_buffer_dac_time: info.out_buffer_dac,
+        }
+    }
+}
+
 /// A structure containing unchanging information about an open stream.
+///
+/// The latencies are the values negotiated by PortAudio when the stream was opened, which may
+/// differ substantially from the `suggested_latency` given in the stream's **Parameters**.
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
-#[repr(C)]
 pub struct Info {
     /// Struct version
     pub struct_version: i32,
-    /// The input latency for this open stream
-    pub input_latency: Time,
-    /// The output latency for this open stream
-    pub output_latency: Time,
+    /// The input latency for this open stream, or zero for an output-only stream
+    pub input_latency: Duration,
+    /// The output latency for this open stream, or zero for an input-only stream
+    pub output_latency: Duration,
     /// The sample rate for this open stream
     pub sample_rate: f64,
 }
@@ -831,8 +1924,8 @@ impl From<ffi::PaStreamInfo> for Info {
     fn from(info: ffi::PaStreamInfo) -> Info {
         Info {
             struct_version: info.structVersion,
-            input_latency: info.inputLatency,
-            output_latency: info.outputLatency,
+            input_latency: time_to_duration(info.inputLatency),
+            output_latency: time_to_duration(info.outputLatency),
             sample_rate: info.sampleRate,
         }
     }
@@ -841,9 +1934,68 @@ impl From<ffi::PaStreamInfo> for Info {
 impl<B> Mode for Blocking<B> {}
 impl Mode for NonBlocking {}
 
-impl<S: Sample> Parameters<S> {
-    /// Converts the given `C_PaStreamParameters` into their respective **Parameters**.
-    ///
+impl NonBlocking {
+    /// Wrap the `callback` for a stream with the given parameters, ready to be passed to
+    /// PortAudio as the stream's `user_data`.
+    fn new<F, C>(
+        in_params: Option<ffi::PaStreamParameters>,
+        out_params: Option<ffi::PaStreamParameters>,
+        mut callback: C,
+    ) -> Self
+    where
+        F: Flow,
+        C: FnMut(F::CallbackArgs) -> ffi::PaStreamCallbackResult + Send + 'static,
+    {
+        let in_layout = BufferLayout::from_c_params(in_params);
+        let out_layout = BufferLayout::from_c_params(out_params);
+
+        let callback_wrapper_fn = move |input: *const raw::c_void,
+                                        output: *mut raw::c_void,
+                                        frame_count: raw::c_ulong,
+                                        time_info: *const ffi::PaStreamCallbackTimeInfo,
+                                        flags: ffi::PaStreamCallbackFlags|
+              -> ffi::PaStreamCallbackResult {
+            if flags & XRUN_FLAGS != 0 {
+                log_event!(
+                    warn,
+                    "xrun in stream callback: {:?}",
+                    CallbackFlags::from_bits_truncate(flags)
+                );
+            }
+            // PortAudio hands the callback buffers matching the stream's parameters.
+            let args = unsafe {
+                F::new_callback_args(
+                    input,
+                    output,
+                    frame_count,
+                    time_info,
+                    flags,
+                    in_layout,
+                    out_layout,
+                )
+            };
+            callback(args)
+        };
+
+        NonBlocking {
+            // Here, we `Box` the wrapper so that we can collect the pointer from the callback.
+            //
+            // TODO: See if it is possible to pass a ptr to the callback_fn itself instead of
+            // requiring the wrapper at all. It seems like DST will be a problem here though.
+            callback: Box::new(CallbackFnWrapper {
+                // Here we `Box` the callback fn as we can't handle generic types in the c callback
+                // function.
+                f: Box::new(callback_wrapper_fn),
+                finished: None,
+                panic: Mutex::new(None),
+            }),
+        }
+    }
+}
+
+impl<S: Sample> Parameters<S> {
+    /// Converts the given `C_PaStreamParameters` into their respective **Parameters**.
+    ///
     /// Returns `None` if the `sample_format` differs to that of the **S** **Sample** parameter.
     ///
     /// Returns `None` if the `device` index is neither a valid index or a
@@ -866,6 +2018,8 @@ impl<S: Sample> Parameters<S> {
             suggested_latency: c_params.suggestedLatency,
             is_interleaved: is_interleaved,
             sample_format: std::marker::PhantomData,
+            raw_format: None,
+            host_api_stream_info: None,
         })
     }
 }
@@ -873,23 +2027,28 @@ impl<S: Sample> Parameters<S> {
 impl<S: Sample> From<Parameters<S>> for ffi::PaStreamParameters {
     /// Converts the **Parameters** into its matching `C_PaStreamParameters`.
     fn from(params: Parameters<S>) -> Self {
-        let Parameters {
-            device,
-            channel_count,
-            suggested_latency,
-            is_interleaved,
-            ..
-        } = params;
-        let sample_format = S::sample_format();
+        params.to_c_params(S::sample_format())
+    }
+}
+
+impl From<Parameters<Raw>> for ffi::PaStreamParameters {
+    /// Converts the **Parameters** into its matching `C_PaStreamParameters`.
+    fn from(params: Parameters<Raw>) -> Self {
+        params.to_c_params(params.sample_format())
+    }
+}
+
+impl<S> Parameters<S> {
+    fn to_c_params(&self, sample_format: SampleFormat) -> ffi::PaStreamParameters {
         let mut sample_format_flags = sample_format.flags();
-        if !is_interleaved {
+        if !self.is_interleaved {
             sample_format_flags.insert(sample_format_flags::NON_INTERLEAVED);
         }
         ffi::PaStreamParameters {
-            device: device.into(),
-            channelCount: channel_count as raw::c_int,
+            device: self.device.into(),
+            channelCount: self.channel_count as raw::c_int,
             sampleFormat: sample_format_flags.bits(),
-            suggestedLatency: suggested_latency,
+            suggestedLatency: self.suggested_latency,
             hostApiSpecificStreamInfo: ptr::null_mut(),
         }
     }
@@ -941,21 +2100,89 @@ impl<I, O> Settings for DuplexSettings<I, O> {
     }
 }
 
+impl Settings for RawInputSettings {
+    type Flow = RawInput;
+    fn into_flow_and_settings(self) -> (Self::Flow, f64, u32, Flags) {
+        let flow = RawInput {
+            params: self.params,
+        };
+        (flow, self.sample_rate, self.frames_per_buffer, self.flags)
+    }
+}
+
+impl Settings for RawOutputSettings {
+    type Flow = RawOutput;
+    fn into_flow_and_settings(self) -> (Self::Flow, f64, u32, Flags) {
+        let flow = RawOutput {
+            params: self.params,
+        };
+        (flow, self.sample_rate, self.frames_per_buffer, self.flags)
+    }
+}
+
+impl Settings for RawDuplexSettings {
+    type Flow = RawDuplex;
+    fn into_flow_and_settings(self) -> (Self::Flow, f64, u32, Flags) {
+        let flow = RawDuplex {
+            in_params: self.in_params,
+            out_params: self.out_params,
+        };
+        (flow, self.sample_rate, self.frames_per_buffer, self.flags)
+    }
+}
+
 impl Buffer {
     /// Construct a new **Buffer** for transferring audio on a stream with the given format.
+    ///
+    /// **Stream::open** rejects sizes that overflow, so those only occur for streams wrapped via
+    /// **Stream::from_raw**, whose **Buffer** is then left empty.
     fn new<S>(frames_per_buffer: u32, channel_count: i32) -> Buffer {
-        let sample_format_bytes = ::std::mem::size_of::<S>() as libc::size_t;
-        let n_frames = frames_per_buffer as libc::size_t;
-        let n_channels = channel_count as libc::size_t;
-        let malloc_size = sample_format_bytes * n_frames * n_channels;
+        let sample_format_bytes = ::std::mem::size_of::<S>();
+        let malloc_size =
+            buffer_bytes(frames_per_buffer, channel_count, sample_format_bytes).unwrap_or(0);
+        let data = unsafe { libc::malloc(malloc_size as libc::size_t) };
         Buffer {
-            data: unsafe { libc::malloc(malloc_size) as *mut libc::c_void },
+            data: data,
+            len: if data.is_null() { 0 } else { malloc_size },
         }
     }
 
+    /// Grow the **Buffer** if necessary so that it holds `frames` frames of `channels` samples of
+    /// `S`.
+    fn reserve<S>(&mut self, frames: u32, channels: i32) -> Result<(), Error>
+    where
+        S: Sample,
+    {
+        let required = buffer_bytes(frames, channels, sample_bytes::<S>()?)?;
+        if required > self.len {
+            let data = unsafe { libc::realloc(self.data, required) };
+            if data.is_null() {
+                return Err(Error::InsufficientMemory);
+            }
+            self.data = data;
+            self.len = required;
+        }
+        Ok(())
+    }
+
+    /// Check that `frames` frames of `channels` samples of `S` fit within the **Buffer**.
+    fn check_frames<S>(&self, frames: u32, channels: i32) -> Result<(), Error>
+    where
+        S: Sample,
+    {
+        let expected = buffer_bytes(frames, channels, sample_bytes::<S>()?)?;
+        if expected > self.len {
+            return Err(Error::InvalidBuffer {
+                expected: expected,
+                actual: self.len,
+            });
+        }
+        Ok(())
+    }
+
     /// Convert the **Buffer**'s data field into a slice with the given format.
-    unsafe fn slice<'a, S>(&'a self, frames: u32, channels: i32) -> &'a [S] {
-        let len = (frames * channels as u32) as usize;
+    unsafe fn slice<S>(&self, frames: u32, channels: i32) -> &[S] {
+        let len = frames as usize * channels as usize;
         // TODO: At the moment, we assume this buffer is interleaved. We need to check whether
         // or not buffer is interleaved here. This should probably an extra type parameter
         // (along-side the Sample type param).
@@ -963,8 +2190,8 @@ impl Buffer {
     }
 
     /// Convert the **Buffer**'s data field into a mutable slice with the given format.
-    unsafe fn slice_mut<'a, S>(&'a mut self, frames: u32, channels: i32) -> &'a mut [S] {
-        let len = (frames * channels as u32) as usize;
+    unsafe fn slice_mut<S>(&mut self, frames: u32, channels: i32) -> &mut [S] {
+        let len = frames as usize * channels as usize;
         // TODO: At the moment, we assume this buffer is interleaved. We need to check whether
         // or not buffer is interleaved here. This should probably an extra type parameter
         // (along-side the Sample type param).
@@ -978,24 +2205,215 @@ impl Drop for Buffer {
     }
 }
 
+/// The C parameters for one direction of a stream along with the host-API-specific info that they
+/// point to, which must outlive the call to Pa_OpenStream.
+struct CParams {
+    params: ffi::PaStreamParameters,
+    // Boxed so that the `hostApiSpecificStreamInfo` pointer remains valid when moved.
+    _host_api_stream_info: Option<Box<CHostApiSpecificStreamInfo>>,
+}
+
+/// The C structs corresponding to each **HostApiSpecificStreamInfo**.
+enum CHostApiSpecificStreamInfo {
+    #[cfg(windows)]
+    Wasapi(ffi::PaWasapiStreamInfo),
+    /// The C struct's device string points into the accompanying **AlsaStreamInfo**.
+    #[cfg(target_os = "linux")]
+    Alsa(ffi::PaAlsaStreamInfo, AlsaStreamInfo),
+    Custom(CustomStreamInfo),
+}
+
+impl CParams {
+    fn new(mut params: ffi::PaStreamParameters, info: Option<HostApiSpecificStreamInfo>) -> Self {
+        let info = info.filter(|info| {
+            let host_specific_device: ffi::PaDeviceIndex =
+                DeviceKind::UseHostApiSpecificDeviceSpecification.into();
+            params.device == host_specific_device
+                || device_host_api_type(params.device) == Some(info.host_api_type())
+        });
+        let c_info = info.map(|info| {
+            let mut c_info = Box::new(match info {
+                #[cfg(windows)]
+                HostApiSpecificStreamInfo::Wasapi(wasapi) => {
+                    CHostApiSpecificStreamInfo::Wasapi(wasapi.to_c_info())
+                }
+                #[cfg(target_os = "linux")]
+                HostApiSpecificStreamInfo::Alsa(alsa) => {
+                    CHostApiSpecificStreamInfo::Alsa(alsa.as_c_info(), alsa)
+                }
+                HostApiSpecificStreamInfo::Custom(custom) => {
+                    CHostApiSpecificStreamInfo::Custom(custom)
+                }
+            });
+            params.hostApiSpecificStreamInfo = match *c_info {
+                #[cfg(windows)]
+                CHostApiSpecificStreamInfo::Wasapi(ref mut c) => c as *mut _ as *mut raw::c_void,
+                #[cfg(target_os = "linux")]
+                CHostApiSpecificStreamInfo::Alsa(ref mut c, ref alsa) => {
+                    // Point at the boxed copy of the device string, and have PortAudio use it
+                    // in place of the device index.
+                    *c = alsa.as_c_info();
+                    params.device = DeviceKind::UseHostApiSpecificDeviceSpecification.into();
+                    c as *mut _ as *mut raw::c_void
+                }
+                CHostApiSpecificStreamInfo::Custom(ref mut custom) => {
+                    custom.data.as_mut_ptr() as *mut raw::c_void
+                }
+            };
+            c_info
+        });
+        CParams {
+            params: params,
+            _host_api_stream_info: c_info,
+        }
+    }
+}
+
+impl HostApiSpecificStreamInfo {
+    /// The host API to which the options apply.
+    pub fn host_api_type(&self) -> HostApiTypeId {
+        match *self {
+            #[cfg(windows)]
+            HostApiSpecificStreamInfo::Wasapi(_) => HostApiTypeId::WASAPI,
+            #[cfg(target_os = "linux")]
+            HostApiSpecificStreamInfo::Alsa(_) => HostApiTypeId::ALSA,
+            HostApiSpecificStreamInfo::Custom(ref custom) => custom.host_api,
+        }
+    }
+}
+
+impl PartialEq for CustomStreamInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.host_api == other.host_api && self.bytes() == other.bytes()
+    }
+}
+
+impl ::std::fmt::Debug for CustomStreamInfo {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_struct("CustomStreamInfo")
+            .field("host_api", &self.host_api)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl CustomStreamInfo {
+    /// The bytes of the copied C struct.
+    fn bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr() as *const u8, self.size) }
+    }
+}
+
+/// The type of the host API that the given device belongs to, if the device exists.
+fn device_host_api_type(device: ffi::PaDeviceIndex) -> Option<HostApiTypeId> {
+    use num::FromPrimitive;
+    if device < 0 {
+        return None;
+    }
+    unsafe {
+        let device_info = ffi::Pa_GetDeviceInfo(device);
+        if device_info.is_null() {
+            return None;
+        }
+        let host_api_info = ffi::Pa_GetHostApiInfo((*device_info).hostApi);
+        if host_api_info.is_null() {
+            return None;
+        }
+        HostApiTypeId::from_u32((*host_api_info).type_)
+    }
+}
+
+/// The **CallbackFlags** bits that indicate an input or output xrun.
+const XRUN_FLAGS: ffi::PaStreamCallbackFlags =
+    ffi::INPUT_UNDERFLOW | ffi::INPUT_OVERFLOW | ffi::OUTPUT_UNDERFLOW | ffi::OUTPUT_OVERFLOW;
+
+/// Describes the configuration of a newly opened stream for its `log` record.
+#[cfg(feature = "log")]
+struct LogStreamConfig {
+    sample_rate: f64,
+    frames_per_buffer: u32,
+    input: Option<(ffi::PaDeviceIndex, i32)>,
+    output: Option<(ffi::PaDeviceIndex, i32)>,
+    /// `None` should PortAudio fail to describe the stream.
+    info: Option<Info>,
+}
+
+#[cfg(feature = "log")]
+impl LogStreamConfig {
+    fn new<M, F>(
+        stream: &Stream<M, F>,
+        in_params: Option<ffi::PaStreamParameters>,
+        out_params: Option<ffi::PaStreamParameters>,
+    ) -> Self {
+        LogStreamConfig {
+            sample_rate: stream.sample_rate,
+            frames_per_buffer: stream.frames_per_buffer,
+            input: in_params.map(|p| (p.device, p.channelCount)),
+            output: out_params.map(|p| (p.device, p.channelCount)),
+            info: stream.info().ok(),
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl ::std::fmt::Display for LogStreamConfig {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let info = match self.info {
+            Some(ref info) => info,
+            None => {
+                return write!(
+                    f,
+                    "requested {} Hz, {} frames per buffer",
+                    self.sample_rate, self.frames_per_buffer
+                )
+            }
+        };
+        write!(
+            f,
+            "requested {} Hz ({} Hz actual), {} frames per buffer",
+            self.sample_rate, info.sample_rate, self.frames_per_buffer
+        )?;
+        if let Some((device, channels)) = self.input {
+            write!(
+                f,
+                ", input device {} with {} channels and {:?} latency",
+                device, channels, info.input_latency
+            )?;
+        }
+        if let Some((device, channels)) = self.output {
+            write!(
+                f,
+                ", output device {} with {} channels and {:?} latency",
+                device, channels, info.output_latency
+            )?;
+        }
+        Ok(())
+    }
+}
+
 fn open_blocking_stream(
     in_params: Option<ffi::PaStreamParameters>,
     out_params: Option<ffi::PaStreamParameters>,
+    host_api_stream_info: (
+        Option<HostApiSpecificStreamInfo>,
+        Option<HostApiSpecificStreamInfo>,
+    ),
     sample_rate: f64,
     frames_per_buffer: u32,
     flags: Flags,
 ) -> Result<*mut raw::c_void, Error> {
     // The pointer to which PortAudio will attach the stream.
     let mut c_stream_ptr: *mut raw::c_void = ptr::null_mut();
-    let in_c_params = in_params.map(|p| p.into());
-    let out_c_params = out_params.map(|p| p.into());
+    let (in_info, out_info) = host_api_stream_info;
+    let in_c_params = in_params.map(|p| CParams::new(p, in_info));
+    let out_c_params = out_params.map(|p| CParams::new(p, out_info));
     let in_c_params_ptr = in_c_params
         .as_ref()
-        .map(|p| p as *const _)
+        .map(|p| &p.params as *const _)
         .unwrap_or(ptr::null());
     let out_c_params_ptr = out_c_params
         .as_ref()
-        .map(|p| p as *const _)
+        .map(|p| &p.params as *const _)
         .unwrap_or(ptr::null());
     let c_flags = flags.bits();
 
@@ -1011,7 +2429,7 @@ fn open_blocking_stream(
             None,
             ptr::null_mut(),
         );
-        let error = FromPrimitive::from_i32(error_code).unwrap();
+        let error = Error::from_code(error_code);
         match error {
             Error::NoError => Ok(c_stream_ptr),
             err => Err(err),
@@ -1022,6 +2440,10 @@ fn open_blocking_stream(
 fn open_non_blocking_stream(
     in_params: Option<ffi::PaStreamParameters>,
     out_params: Option<ffi::PaStreamParameters>,
+    host_api_stream_info: (
+        Option<HostApiSpecificStreamInfo>,
+        Option<HostApiSpecificStreamInfo>,
+    ),
     sample_rate: f64,
     frames_per_buffer: u32,
     flags: Flags,
@@ -1029,15 +2451,16 @@ fn open_non_blocking_stream(
 ) -> Result<*mut raw::c_void, Error> {
     // The pointer to which PortAudio will attach the stream.
     let mut c_stream_ptr: *mut raw::c_void = ptr::null_mut();
-    let in_c_params = in_params.map(|p| p.into());
-    let out_c_params = out_params.map(|p| p.into());
+    let (in_info, out_info) = host_api_stream_info;
+    let in_c_params = in_params.map(|p| CParams::new(p, in_info));
+    let out_c_params = out_params.map(|p| CParams::new(p, out_info));
     let in_c_params_ptr = in_c_params
         .as_ref()
-        .map(|p| p as *const _)
+        .map(|p| &p.params as *const _)
         .unwrap_or(ptr::null());
     let out_c_params_ptr = out_c_params
         .as_ref()
-        .map(|p| p as *const _)
+        .map(|p| &p.params as *const _)
         .unwrap_or(ptr::null());
     let c_flags = flags.bits();
 
@@ -1068,7 +2491,7 @@ fn open_non_blocking_stream(
             Some(stream_callback_proc),
             user_data,
         );
-        let error = FromPrimitive::from_i32(error_code).unwrap();
+        let error = Error::from_code(error_code);
         match error {
             Error::NoError => Ok(c_stream_ptr),
             err => Err(err),
@@ -1077,58 +2500,141 @@ fn open_non_blocking_stream(
 }
 
 impl<M, F> Stream<M, F> {
-    fn new_unopened(mode: M, flow: F, life: std::sync::Arc<super::Life>) -> Self {
+    fn new_unopened(
+        mode: M,
+        flow: F,
+        sample_rate: f64,
+        frames_per_buffer: u32,
+        flags: Flags,
+        life: std::sync::Arc<super::Life>,
+    ) -> Self {
         Stream {
             pa_stream: ptr::null_mut(),
             mode: mode,
             flow: flow,
+            sample_rate: sample_rate,
+            frames_per_buffer: frames_per_buffer,
+            flags: flags,
             port_audio_life: life,
         }
     }
 
+    /// Aborts and closes the underlying PortAudio stream ahead of re-opening it, discarding any
+    /// errors as the stream's device may no longer exist.
+    fn close_for_reopen(&mut self) {
+        if !self.pa_stream.is_null() {
+            self.abort().ok();
+            self.close_in_place().ok();
+            self.pa_stream = ptr::null_mut();
+        }
+    }
+
     /// Closes an audio stream.
     ///
     /// If the audio stream is active it discards any pending buffers as if Stream::abort had been
     /// called.
-    pub fn close(&mut self) -> Result<(), Error> {
+    ///
+    /// Once closed, the stream callback is never called again. The **Stream** is consumed, so the
+    /// stream is closed exactly once: dropping it afterwards doesn't call into PortAudio again.
+    /// If PortAudio fails to close the stream, the error is returned and closing is attempted once
+    /// more as the **Stream** is dropped, so that the callback's state isn't dropped while
+    /// PortAudio may still call it.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.close_in_place()
+    }
+
+    /// Closes the stream without consuming it, doing nothing if it is already closed, e.g. ahead
+    /// of re-opening it or when it is dropped.
+    ///
+    /// The stream is only marked as closed if PortAudio closes it successfully.
+    fn close_in_place(&mut self) -> Result<(), Error> {
+        if self.pa_stream.is_null() {
+            return Ok(());
+        }
         let error_code = unsafe { ffi::Pa_CloseStream(self.pa_stream) };
-        let error = FromPrimitive::from_i32(error_code).unwrap();
+        let error = Error::from_code(error_code);
+        log_event!(debug, "close stream {:p}: {:?}", self.pa_stream, error);
         match error {
-            Error::NoError => Ok(()),
+            Error::NoError => {
+                // PortAudio frees the stream, so it must not be used again, e.g. when dropped.
+                self.pa_stream = ptr::null_mut();
+                Ok(())
+            }
             err => Err(err),
         }
     }
 
     /// Commences audio processing.
+    ///
+    /// Streams are opened stopped, so nothing is recorded or played until the stream is started.
+    /// A stream can be started again after it was stopped via **Stream::stop** or
+    /// **Stream::abort**, any number of times, e.g. to reuse one stream for a series of recorded
+    /// takes. Blocking streams can be read from and written to again as soon as they have been
+    /// started. **Stream::restart** stops and starts a stream in one go.
+    ///
+    /// Returns `Err(Error::StreamIsNotStopped)` if the stream has already been started, which
+    /// includes a stream whose callback has completed but that hasn't been stopped yet.
     pub fn start(&mut self) -> Result<(), Error> {
         let error_code = unsafe { ffi::Pa_StartStream(self.pa_stream) };
-        let error = FromPrimitive::from_i32(error_code).unwrap();
-        match error {
-            0 => Ok(()),
-            err => Err(FromPrimitive::from_i32(err).unwrap()),
+        let result = match Error::from_code(error_code) {
+            Error::NoError => Ok(()),
+            err => Err(err),
+        };
+        log_event!(debug, "start stream {:p}: {:?}", self.pa_stream, result);
+        result
+    }
+
+    /// Commences audio processing, returning a **StreamGuard** that stops the stream again when it
+    /// goes out of scope.
+    ///
+    /// This suits streams that should only run for the duration of a block, e.g. a short
+    /// recording, as the stream is stopped on every path out of the block, including early
+    /// returns and panics.
+    pub fn scoped_start(&mut self) -> Result<StreamGuard<'_, M, F>, Error> {
+        self.start()?;
+        Ok(StreamGuard { stream: self })
+    }
+
+    /// Stop the stream unless it is already stopped, then start it again, e.g. to re-trigger a
+    /// stream for the next take.
+    ///
+    /// As with **Stream::stop**, this waits until any pending output has been played. Unlike
+    /// **Stream::start**, this also starts a stream whose callback has completed, as such a
+    /// stream is finished but not yet stopped.
+    pub fn restart(&mut self) -> Result<(), Error> {
+        if !self.is_stopped()? {
+            self.stop()?;
         }
+        self.start()
     }
 
     /// Terminates audio processing.
     ///
-    /// It waits until all pending audio buffers have been played before it returns.
+    /// It waits until all pending audio buffers have been played before it returns. Use
+    /// `Stream::abort` to cut the audio off without waiting for them to drain.
     pub fn stop(&mut self) -> Result<(), Error> {
         let error_code = unsafe { ffi::Pa_StopStream(self.pa_stream) };
-        let error = FromPrimitive::from_i32(error_code).unwrap();
-        match error {
-            0 => Ok(()),
-            err => Err(FromPrimitive::from_i32(err).unwrap()),
-        }
+        let result = match Error::from_code(error_code) {
+            Error::NoError => Ok(()),
+            err => Err(err),
+        };
+        log_event!(debug, "stop stream {:p}: {:?}", self.pa_stream, result);
+        result
     }
 
     /// Terminates audio processing immediately without waiting for pending buffers to complete.
+    ///
+    /// Unlike `Stream::stop`, any output that has been written (or generated by the callback) but
+    /// not yet played is discarded. This is available for both **Blocking** and **NonBlocking**
+    /// streams, and the stream is no longer active once it returns.
     pub fn abort(&mut self) -> Result<(), Error> {
         let error_code = unsafe { ffi::Pa_AbortStream(self.pa_stream) };
-        let error = FromPrimitive::from_i32(error_code).unwrap();
-        match error {
-            0 => Ok(()),
-            err => Err(FromPrimitive::from_i32(err).unwrap()),
-        }
+        let result = match Error::from_code(error_code) {
+            Error::NoError => Ok(()),
+            err => Err(err),
+        };
+        log_event!(debug, "abort stream {:p}: {:?}", self.pa_stream, result);
+        result
     }
 
     /// Determine whether the stream is stopped.
@@ -1151,7 +2657,7 @@ impl<M, F> Stream<M, F> {
         match error_code {
             1 => Ok(true),
             0 => Ok(false),
-            err => Err(FromPrimitive::from_i32(err).unwrap()),
+            err => Err(Error::from_code(err)),
         }
     }
 
@@ -1174,7 +2680,25 @@ impl<M, F> Stream<M, F> {
         match error_code {
             0 => Ok(false),
             1 => Ok(true),
-            err => Err(FromPrimitive::from_i32(err).unwrap()),
+            err => Err(Error::from_code(err)),
+        }
+    }
+
+    /// Determine the state of the stream, combining **is_stopped** and **is_active** into a single
+    /// answer, e.g. for the state machine of a user interface that controls the stream.
+    ///
+    /// The two booleans disagree in the transitional window after the stream callback returns
+    /// **Complete** or **Abort**: the stream nevertheless remains **Active** until the buffers that
+    /// are already queued have been played, and only then becomes **Finished**. As the two
+    /// queries aren't atomic, a stream that finishes between them is reported as **Finished**
+    /// rather than **Active**, so the result is never older than the call itself.
+    pub fn state(&self) -> Result<State, Error> {
+        if self.is_stopped()? {
+            Ok(State::Stopped)
+        } else if self.is_active()? {
+            Ok(State::Active)
+        } else {
+            Ok(State::Finished)
         }
     }
 
@@ -1189,16 +2713,93 @@ impl<M, F> Stream<M, F> {
     /// Starting and stopping the stream does not affect the passage of time returned by this
     /// method.
     ///
-    /// Returns the stream's current time in seconds, or 0 if an error occurred.
-    pub fn time(&self) -> Time {
-        unsafe { ffi::Pa_GetStreamTime(self.pa_stream) }
+    /// The origin of the clock is arbitrary, so only the difference between two times (e.g. how
+    /// far ahead of the current time the output buffer will reach the DAC) is meaningful.
+    ///
+    /// Returns the stream's current time, or a zero **Duration** if an error occurred.
+    pub fn time(&self) -> Duration {
+        time_to_duration(unsafe { ffi::Pa_GetStreamTime(self.pa_stream) })
     }
 
     /// Retrieve a Info structure containing information about the stream.
-    pub fn info(&self) -> Info {
+    ///
+    /// Returns `Err(Error::BadStreamPtr)` if the stream isn't open, e.g. because `reopen` failed
+    /// to open it again, as PortAudio has no information about an invalid stream.
+    pub fn info(&self) -> Result<Info, Error> {
         unsafe {
             let info = ffi::Pa_GetStreamInfo(self.pa_stream);
-            Info::from(*info)
+            if info.is_null() {
+                return Err(Error::BadStreamPtr);
+            }
+            Ok(Info::from(*info))
+        }
+    }
+
+    /// The input latency of the stream as negotiated by PortAudio. Shorthand for
+    /// `self.info().input_latency`.
+    ///
+    /// Returns a zero **Duration** if the stream isn't open.
+    pub fn input_latency(&self) -> Duration {
+        self.info()
+            .map(|info| info.input_latency)
+            .unwrap_or_default()
+    }
+
+    /// The output latency of the stream as negotiated by PortAudio. Shorthand for
+    /// `self.info().output_latency`.
+    ///
+    /// Returns a zero **Duration** if the stream isn't open.
+    pub fn output_latency(&self) -> Duration {
+        self.info()
+            .map(|info| info.output_latency)
+            .unwrap_or_default()
+    }
+
+    /// The wall-clock time that the given number of frames spans at the stream's sample rate, as
+    /// negotiated by PortAudio. See the free function **frames_to_duration**.
+    pub fn frames_to_duration(&self, frames: u64) -> Duration {
+        types::frames_to_duration(frames, self.negotiated_sample_rate())
+    }
+
+    /// The number of frames that span the given `duration` at the stream's sample rate, as
+    /// negotiated by PortAudio. See the free function **duration_to_frames**.
+    pub fn duration_to_frames(&self, duration: Duration) -> u64 {
+        types::duration_to_frames(duration, self.negotiated_sample_rate())
+    }
+
+    /// The input latency of the stream in frames at its sample rate, rounded to the nearest frame
+    /// as with **Stream::duration_to_frames**. This is zero for an output-only stream.
+    ///
+    /// The latency that PortAudio reports already accounts for the stream's buffering, including
+    /// its `frames_per_buffer`, so the buffer size must not be added to it again.
+    pub fn input_latency_frames(&self) -> u64 {
+        self.duration_to_frames(self.input_latency())
+    }
+
+    /// The output latency of the stream in frames at its sample rate, rounded to the nearest
+    /// frame as with **Stream::input_latency_frames**. This is zero for an input-only stream.
+    pub fn output_latency_frames(&self) -> u64 {
+        self.duration_to_frames(self.output_latency())
+    }
+
+    /// The number of frames between a frame arriving at the input and a frame written in
+    /// response to it leaving the output, i.e. the sum of **Stream::input_latency_frames** and
+    /// **Stream::output_latency_frames**.
+    ///
+    /// Each direction is rounded before they are summed, so the total always equals the sum of
+    /// the two. For a stream that only has one direction, this is that direction's latency.
+    pub fn total_roundtrip_latency_frames(&self) -> u64 {
+        self.input_latency_frames()
+            .saturating_add(self.output_latency_frames())
+    }
+
+    /// The sample rate from the stream's **Info**, or the requested one if the stream isn't open.
+    fn negotiated_sample_rate(&self) -> f64 {
+        let info = unsafe { ffi::Pa_GetStreamInfo(self.pa_stream) };
+        if info.is_null() {
+            self.sample_rate
+        } else {
+            unsafe { (*info).sampleRate }
         }
     }
 
@@ -1207,6 +2808,25 @@ impl<M, F> Stream<M, F> {
     pub fn unsafe_pa_stream(&self) -> *mut ffi::PaStream {
         self.pa_stream
     }
+
+    /// The underlying `PaStream` pointer, e.g. for passing to a C library that uses PortAudio.
+    ///
+    /// The **Stream** retains ownership, so the pointer is only valid until the **Stream** is
+    /// closed or dropped, and must not be closed via `Pa_CloseStream`. The pointer is null if the
+    /// stream isn't open, e.g. because `reopen` failed to open it again.
+    pub fn as_raw(&self) -> *mut ffi::PaStream {
+        self.pa_stream
+    }
+
+    /// The number of frames per buffer that the stream was opened with.
+    pub(crate) fn frames_per_buffer(&self) -> u32 {
+        self.frames_per_buffer
+    }
+
+    /// The stream's **Flow**, which describes its channels.
+    pub(crate) fn flow(&self) -> &F {
+        &self.flow
+    }
 }
 
 impl<F> Stream<Blocking<F::Buffer>, F>
@@ -1219,22 +2839,106 @@ where
         S: Settings<Flow = F>,
     {
         let (flow, sample_rate, frames_per_buffer, flags) = settings.into_flow_and_settings();
+        let (in_params, out_params) = flow.params_both_directions();
+        for params in in_params.iter().chain(out_params.iter()) {
+            let sample_bytes = SampleFormat::from_ffi(params.sampleFormat).size_in_bytes() as usize;
+            buffer_bytes(frames_per_buffer, params.channelCount, sample_bytes)?;
+        }
         let buffer = flow.new_buffer(frames_per_buffer);
         let blocking = Blocking { buffer: buffer };
-        let (in_params, out_params) = flow.params_both_directions();
-        let mut stream = Stream::new_unopened(blocking, flow, life);
-        open_blocking_stream(in_params, out_params, sample_rate, frames_per_buffer, flags).map(
-            |pa_stream| {
-                stream.pa_stream = pa_stream;
-                stream
-            },
-        )
+        let mut stream =
+            Stream::new_unopened(blocking, flow, sample_rate, frames_per_buffer, flags, life);
+        stream.reopen().map(|()| stream)
+    }
+
+    /// Wrap a blocking `PaStream` that was opened elsewhere, e.g. by a C library, taking
+    /// ownership of it.
+    ///
+    /// The `settings` describe how the stream was opened and are used to allocate the buffers for
+    /// reading and writing, as well as by `reopen`.
+    ///
+    /// # Safety
+    ///
+    /// - `pa_stream` must be an open stream that was opened via `Pa_OpenStream` (or
+    ///   `Pa_OpenDefaultStream`) without a callback, within the PortAudio instance initialised by
+    ///   `port_audio`.
+    /// - The parameters, sample rate and frames per buffer of `settings` must match those that
+    ///   the stream was opened with, as reads and writes trust them for the buffer sizes.
+    /// - The returned **Stream** closes `pa_stream` when it is closed or dropped, so nothing else
+    ///   may close or otherwise use it after this call, unless it is released again via
+    ///   `into_raw`.
+    pub unsafe fn from_raw<S>(
+        port_audio: &super::PortAudio,
+        pa_stream: *mut ffi::PaStream,
+        settings: S,
+    ) -> Self
+    where
+        S: Settings<Flow = F>,
+    {
+        let (flow, sample_rate, frames_per_buffer, flags) = settings.into_flow_and_settings();
+        let buffer = flow.new_buffer(frames_per_buffer);
+        let blocking = Blocking { buffer: buffer };
+        let life = port_audio.life.clone();
+        let mut stream =
+            Stream::new_unopened(blocking, flow, sample_rate, frames_per_buffer, flags, life);
+        stream.pa_stream = pa_stream;
+        stream
+    }
+
+    /// Release ownership of the underlying `PaStream` without closing it, returning the pointer.
+    ///
+    /// The caller becomes responsible for closing the stream via `Pa_CloseStream`, or for handing
+    /// it back to a **Stream** via **Stream::from_raw**. The stream is only usable while
+    /// PortAudio remains initialised, i.e. while some **PortAudio** instance is alive.
+    ///
+    /// This is only available for **Blocking** streams, as the callback of a **NonBlocking**
+    /// stream is owned by its **Stream**.
+    pub fn into_raw(mut self) -> *mut ffi::PaStream {
+        let pa_stream = self.pa_stream;
+        // With the pointer nulled, dropping `self` frees the buffers without closing the stream.
+        self.pa_stream = ptr::null_mut();
+        pa_stream
+    }
+
+    /// Closes the underlying PortAudio stream and opens it again with the settings that the
+    /// **Stream** was originally opened with.
+    ///
+    /// This is useful for recovering from a device being disconnected, after which stream
+    /// methods begin returning errors such as `Error::DeviceUnavailable`. The re-opened stream is
+    /// stopped and must be started again.
+    ///
+    /// Note that the stream's **DeviceIndex**es are reused as-is. As PortAudio may have
+    /// re-enumerated its devices in the meantime, an index may now refer to a different physical
+    /// device, so it can be worth checking the devices via **PortAudio::devices** before
+    /// re-opening.
+    pub fn reopen(&mut self) -> Result<(), Error> {
+        let (in_params, out_params) = self.flow.params_both_directions();
+        self.port_audio_life
+            .check_stream_params(in_params, out_params)?;
+        self.close_for_reopen();
+        let pa_stream = open_blocking_stream(
+            in_params,
+            out_params,
+            self.flow.host_api_stream_info_both_directions(),
+            self.sample_rate,
+            self.frames_per_buffer,
+            self.flags,
+        )?;
+        self.pa_stream = pa_stream;
+        log_event!(
+            info,
+            "opened blocking stream {:p}: {}",
+            pa_stream,
+            LogStreamConfig::new(self, in_params, out_params)
+        );
+        Ok(())
     }
 }
 
 impl<F> Stream<Blocking<F::Buffer>, F>
 where
     F: Flow + Reader,
+    F::Sample: Sample,
 {
     /// Retrieve the number of frames that can be read from the stream without waiting.
     ///
@@ -1245,15 +2949,17 @@ where
     ///     - Flags indicating whether or not there has been input overflow or output underflow.
     /// - An Err variant in the case PortAudio is not initialized or some error is encountered.
     ///
+    /// Passing at most the returned number of frames to **Stream::read** will not block, which
+    /// allows a blocking stream to be serviced from an event loop without stalling it.
+    ///
     /// See the blocking.rs example for a usage example.
     pub fn read_available(&self) -> Result<Available, Error> {
         match unsafe { ffi::Pa_GetStreamReadAvailable(self.pa_stream) } {
             n if n >= 0 => Ok(Available::Frames(n)),
-            n => match FromPrimitive::from_i64(n as i64) {
-                Some(Error::InputOverflowed) => Ok(Available::InputOverflowed),
-                Some(Error::OutputUnderflowed) => Ok(Available::OutputUnderflowed),
-                Some(err) => Err(err),
-                _ => panic!("Undefined error code: {:?}", n),
+            n => match Error::from_code(n as i32) {
+                Error::InputOverflowed => Ok(Available::InputOverflowed),
+                Error::OutputUnderflowed => Ok(Available::OutputUnderflowed),
+                err => Err(err),
             },
         }
     }
@@ -1268,11 +2974,16 @@ where
     ///
     /// Returns an interleaved slice containing the read audio data.
     ///
+    /// The data is read into a buffer of the stream's `frames_per_buffer` frames, so `frames` must
+    /// not exceed it, otherwise **Error::InvalidBuffer** is returned. Use **Stream::read_into**
+    /// for streams opened with **FRAMES_PER_BUFFER_UNSPECIFIED**.
+    ///
     /// Returns an `Error` if some error occurred.
     ///
     /// TODO: Research and document exactly what errors can occur.
-    pub fn read<'b>(&'b self, frames: u32) -> Result<&'b [F::Sample], Error> {
+    pub fn read(&self, frames: u32) -> Result<&[F::Sample], Error> {
         let buffer = F::readable_buffer(&self.mode);
+        buffer.check_frames::<F::Sample>(frames, Reader::channel_count(&self.flow))?;
         let err = unsafe {
             ffi::Pa_ReadStream(
                 self.pa_stream,
@@ -1285,14 +2996,330 @@ where
                 let channel_count = Reader::channel_count(&self.flow);
                 Ok(buffer.slice(frames, channel_count))
             },
-            err => Err(FromPrimitive::from_i32(err).unwrap()),
+            err => Err(Error::from_code(err)),
+        }
+    }
+
+    /// Read interleaved samples from an input stream until the given `buffer` is full.
+    ///
+    /// Unlike **Stream::read**, the `buffer` may hold any number of frames. The transfer is split
+    /// into chunks of at most the stream's `frames_per_buffer`, the last of which may be shorter.
+    /// Returns **Error::InvalidBuffer** if the `buffer` ends part way through a frame.
+    ///
+    /// Returns the number of frames that were read.
+    pub fn read_into(&mut self, buffer: &mut [F::Sample]) -> Result<usize, Error> {
+        let channels = Reader::channel_count(&self.flow) as usize;
+        check_whole_frames::<F::Sample>(buffer.len(), channels)?;
+        let pa_stream = self.pa_stream;
+        let ptr = buffer.as_mut_ptr();
+        transfer_chunked(
+            buffer.len(),
+            channels,
+            self.frames_per_buffer,
+            |offset, frames| unsafe {
+                ffi::Pa_ReadStream(pa_stream, ptr.add(offset) as *mut raw::c_void, frames)
+            },
+        )
+    }
+
+    /// Read interleaved samples from an input stream until the given `buffer` is full, giving up
+    /// with **Error::TimedOut** if that takes longer than `timeout`, e.g. because the device has
+    /// stalled.
+    ///
+    /// PortAudio has no timeout of its own, so this polls **Stream::read_available** every
+    /// **TIMEOUT_POLL_INTERVAL** and only reads the frames that are already available. The
+    /// timeout is therefore only as precise as the poll interval. Any frames read before timing
+    /// out are left at the start of the `buffer`.
+    ///
+    /// Returns the number of frames that were read.
+    pub fn read_timeout(
+        &mut self,
+        buffer: &mut [F::Sample],
+        timeout: Duration,
+    ) -> Result<usize, Error> {
+        let channels = Reader::channel_count(&self.flow) as usize;
+        check_whole_frames::<F::Sample>(buffer.len(), channels)?;
+        let pa_stream = self.pa_stream;
+        let ptr = buffer.as_mut_ptr();
+        transfer_with_timeout(
+            buffer.len(),
+            channels,
+            timeout,
+            || self.read_available(),
+            |offset, frames| unsafe {
+                ffi::Pa_ReadStream(pa_stream, ptr.add(offset) as *mut raw::c_void, frames)
+            },
+        )
+    }
+
+    /// Read samples from a non-interleaved input stream into a separate buffer per channel, until
+    /// the buffers are full.
+    ///
+    /// There must be a buffer for each of the stream's channels, and all of them must be of the
+    /// same length, otherwise **Error::InvalidChannelCount** or **Error::InvalidBuffer** is
+    /// returned respectively. As with **Stream::read_into**, the buffers may hold any number of
+    /// frames and are read in chunks of at most the stream's `frames_per_buffer`. Returns
+    /// **Error::BadBufferPtr** if the stream is interleaved, in which case **Stream::read_into**
+    /// is used instead.
+    ///
+    /// Returns the number of frames that were read.
+    pub fn read_planar(&mut self, channels: &mut [&mut [F::Sample]]) -> Result<usize, Error> {
+        let layout = BufferLayout::from_c_params(self.flow.params_both_directions().0);
+        let lens: Vec<usize> = channels.iter().map(|channel| channel.len()).collect();
+        let (frames, lanes) = planar_frames::<F::Sample>(layout, &lens)?;
+        let pa_stream = self.pa_stream;
+        let planes: Vec<*mut F::Sample> = channels.iter_mut().map(|c| c.as_mut_ptr()).collect();
+        let mut offset_planes = Vec::with_capacity(planes.len());
+        transfer_chunked(frames, 1, self.frames_per_buffer, |offset, frames| {
+            offset_planes.clear();
+            offset_planes.extend(planes.iter().map(|&p| unsafe { p.add(offset * lanes) }));
+            unsafe {
+                ffi::Pa_ReadStream(
+                    pa_stream,
+                    offset_planes.as_mut_ptr() as *mut raw::c_void,
+                    frames,
+                )
+            }
+        })
+    }
+
+    /// Convert the stream into an **Iterator** over blocks of `frames_per_buffer` frames.
+    ///
+    /// Each block is read via **Stream::read** and holds exactly `frames_per_buffer * channels`
+    /// interleaved samples. See **BlockIterator** for when the iteration ends.
+    pub fn blocks(self) -> BlockIterator<F> {
+        BlockIterator {
+            stream: self,
+            is_finished: false,
         }
     }
 }
 
+/// An **Iterator** yielding fixed-size blocks of interleaved samples read from a blocking
+/// stream, produced by **Stream::blocks**.
+///
+/// The iteration ends once the stream is stopped, or after yielding any error other than
+/// **Error::InputOverflowed**. An overflow is yielded as an `Err` too, but as the stream goes on
+/// recording, reading continues with the next block. The stream must have been opened with a
+/// specified `frames_per_buffer`, otherwise **Error::BufferTooSmall** is yielded.
+pub struct BlockIterator<F>
+where
+    F: Flow + Reader,
+{
+    stream: Stream<Blocking<F::Buffer>, F>,
+    is_finished: bool,
+}
+
+impl<F> BlockIterator<F>
+where
+    F: Flow + Reader,
+{
+    /// Unwrap the underlying **Stream**, e.g. to stop and close it.
+    pub fn into_inner(self) -> Stream<Blocking<F::Buffer>, F> {
+        self.stream
+    }
+}
+
+impl<F> Iterator for BlockIterator<F>
+where
+    F: Flow + Reader,
+    F::Sample: Sample,
+{
+    type Item = Result<Vec<F::Sample>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+        if self.stream.frames_per_buffer == 0 {
+            self.is_finished = true;
+            return Some(Err(Error::BufferTooSmall));
+        }
+        match self.stream.is_stopped() {
+            Ok(false) => (),
+            Ok(true) | Err(Error::StreamIsStopped) => {
+                self.is_finished = true;
+                return None;
+            }
+            Err(err) => {
+                self.is_finished = true;
+                return Some(Err(err));
+            }
+        }
+        match self.stream.read(self.stream.frames_per_buffer) {
+            Ok(samples) => Some(Ok(samples.to_vec())),
+            Err(Error::InputOverflowed) => Some(Err(Error::InputOverflowed)),
+            Err(Error::StreamIsStopped) => {
+                self.is_finished = true;
+                None
+            }
+            Err(err) => {
+                self.is_finished = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Split a transfer of an interleaved buffer of `len` samples into chunks of at most
+/// `frames_per_buffer` frames (or a single chunk if it is unspecified), passing the sample offset
+/// of each chunk to `transfer` along with its length in frames.
+///
+/// Returns the total number of frames transferred.
+fn transfer_chunked<T>(
+    len: usize,
+    channels: usize,
+    frames_per_buffer: u32,
+    mut transfer: T,
+) -> Result<usize, Error>
+where
+    T: FnMut(usize, raw::c_ulong) -> ffi::PaError,
+{
+    if channels == 0 {
+        return Ok(0);
+    }
+    let frames = len / channels;
+    let chunk_frames = match frames_per_buffer {
+        0 => frames,
+        n => n as usize,
+    };
+    let mut done = 0;
+    while done < frames {
+        let n = std::cmp::min(chunk_frames, frames - done);
+        match transfer(done * channels, n as raw::c_ulong) {
+            0 => done += n,
+            err => return Err(Error::from_code(err)),
+        }
+    }
+    Ok(done)
+}
+
+/// The number of bytes in `frames` frames of `channels` samples that are `sample_bytes` each.
+///
+/// Returns `Err(Error::BufferTooLarge)` rather than overflowing, and
+/// `Err(Error::InvalidChannelCount)` for a negative channel count.
+fn buffer_bytes(frames: u32, channels: i32, sample_bytes: usize) -> Result<usize, Error> {
+    if channels < 0 {
+        return Err(Error::InvalidChannelCount);
+    }
+    (frames as usize)
+        .checked_mul(channels as usize)
+        .and_then(|samples| samples.checked_mul(sample_bytes))
+        .ok_or(Error::BufferTooLarge)
+}
+
+/// The size in bytes that PortAudio assumes for each sample of `S`.
+///
+/// Returns **Error::InvalidBuffer** if `S` is laid out with a different size, as PortAudio would
+/// then read past or stop short of the samples in a buffer of `S`.
+fn sample_bytes<S>() -> Result<usize, Error>
+where
+    S: Sample,
+{
+    let expected = super::get_sample_size(S::sample_format())? as usize;
+    let actual = std::mem::size_of::<S>();
+    if expected != actual {
+        return Err(Error::InvalidBuffer {
+            expected: expected,
+            actual: actual,
+        });
+    }
+    Ok(expected)
+}
+
+/// Check that a buffer of `len` samples of `S` is made up of whole frames of `channels` samples.
+fn check_whole_frames<S>(len: usize, channels: usize) -> Result<(), Error>
+where
+    S: Sample,
+{
+    let sample_bytes = sample_bytes::<S>()?;
+    if channels > 0 && !len.is_multiple_of(channels) {
+        return Err(Error::InvalidBuffer {
+            expected: (len - len % channels) * sample_bytes,
+            actual: len * sample_bytes,
+        });
+    }
+    Ok(())
+}
+
+/// Check the per-channel buffers of `lens` samples each, that are to be transferred to or from a
+/// non-interleaved stream direction with the given `layout`.
+///
+/// Returns the number of frames in each buffer along with the number of elements of type `S`
+/// that make up a single sample, which is `1` unless the stream is **Raw**.
+fn planar_frames<S>(layout: BufferLayout, lens: &[usize]) -> Result<(usize, usize), Error>
+where
+    S: Sample,
+{
+    if layout.interleaved {
+        return Err(Error::BadBufferPtr);
+    }
+    if lens.len() != layout.channels as usize {
+        return Err(Error::InvalidChannelCount);
+    }
+    let sample_bytes = sample_bytes::<S>()?;
+    let lanes = std::cmp::max(layout.sample_size / sample_bytes, 1);
+    let len = lens.first().cloned().unwrap_or(0);
+    for &other in lens {
+        if other != len || !other.is_multiple_of(lanes) {
+            return Err(Error::InvalidBuffer {
+                expected: (len - len % lanes) * sample_bytes,
+                actual: other * sample_bytes,
+            });
+        }
+    }
+    Ok((len / lanes, lanes))
+}
+
+/// The interval at which **Stream::read_timeout** and **Stream::write_timeout** poll the stream
+/// for available frames.
+pub const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Transfer an interleaved buffer of `len` samples in chunks of however many frames `available`
+/// reports, so that `transfer` never blocks, until the buffer is done or `timeout` elapses.
+///
+/// Returns the total number of frames transferred.
+fn transfer_with_timeout<A, T>(
+    len: usize,
+    channels: usize,
+    timeout: Duration,
+    mut available: A,
+    mut transfer: T,
+) -> Result<usize, Error>
+where
+    A: FnMut() -> Result<Available, Error>,
+    T: FnMut(usize, raw::c_ulong) -> ffi::PaError,
+{
+    if channels == 0 {
+        return Ok(0);
+    }
+    let deadline = std::time::Instant::now() + timeout;
+    let frames = len / channels;
+    let mut done = 0;
+    while done < frames {
+        let n = match available()? {
+            Available::Frames(n) => std::cmp::min(n.max(0) as usize, frames - done),
+            // The xrun has been flagged, so poll again for the frames that are available now.
+            Available::InputOverflowed | Available::OutputUnderflowed => 0,
+        };
+        if n > 0 {
+            match transfer(done * channels, n as raw::c_ulong) {
+                0 => done += n,
+                err => return Err(Error::from_code(err)),
+            }
+        } else if std::time::Instant::now() >= deadline {
+            return Err(Error::TimedOut);
+        } else {
+            std::thread::sleep(TIMEOUT_POLL_INTERVAL);
+        }
+    }
+    Ok(done)
+}
+
 impl<F> Stream<Blocking<F::Buffer>, F>
 where
     F: Flow + Writer,
+    <F as Writer>::Sample: Sample,
 {
     /// Retrieve the number of frames that can be written to the stream without waiting.
     ///
@@ -1303,15 +3330,17 @@ where
     ///     - Flags indicating whether or not there has been input overflow or output underflow.
     /// - An Err variant in the case PortAudio is not initialized or some error is encountered.
     ///
+    /// Passing at most the returned number of frames to **Stream::write** will not block, which
+    /// allows a blocking stream to be serviced from an event loop without stalling it.
+    ///
     /// See the blocking.rs example for a usage example.
     pub fn write_available(&self) -> Result<Available, Error> {
         match unsafe { ffi::Pa_GetStreamWriteAvailable(self.pa_stream) } {
             n if n >= 0 => Ok(Available::Frames(n)),
-            n => match FromPrimitive::from_i64(n as i64) {
-                Some(Error::InputOverflowed) => Ok(Available::InputOverflowed),
-                Some(Error::OutputUnderflowed) => Ok(Available::OutputUnderflowed),
-                Some(err) => Err(err),
-                _ => panic!("Undefined error code: {:?}", n),
+            n => match Error::from_code(n as i32) {
+                Error::InputOverflowed => Ok(Available::InputOverflowed),
+                Error::OutputUnderflowed => Ok(Available::OutputUnderflowed),
+                err => Err(err),
             },
         }
     }
@@ -1326,6 +3355,9 @@ where
     /// * frames - The number of frames in the buffer.
     /// * write_fn - The buffer contains samples in the format specified by S.
     ///
+    /// The stream's buffer is grown if `frames` exceeds the `frames_per_buffer` that it was
+    /// opened with, e.g. when that is **FRAMES_PER_BUFFER_UNSPECIFIED**, which allocates.
+    ///
     /// Returns Ok(()) on success and an Err(Error) variant on failure.
     pub fn write<WF>(&mut self, frames: u32, write_fn: WF) -> Result<(), Error>
     where
@@ -1334,6 +3366,7 @@ where
         let pa_stream = self.pa_stream;
         let channels = Writer::channel_count(&self.flow);
         let out_buffer = F::writable_buffer(&mut self.mode);
+        out_buffer.reserve::<F::Sample>(frames, channels)?;
         let written_slice = {
             let slice = unsafe { out_buffer.slice_mut(frames, channels) };
             write_fn(slice);
@@ -1345,9 +3378,87 @@ where
         };
         match result {
             0 => Ok(()),
-            err => Err(FromPrimitive::from_i32(err).unwrap()),
+            err => Err(Error::from_code(err)),
         }
     }
+
+    /// Write all of the interleaved samples in the given `buffer` to an output stream.
+    ///
+    /// Unlike **Stream::write**, the `buffer` may hold any number of frames. The transfer is
+    /// split into chunks of at most the stream's `frames_per_buffer`, the last of which may be
+    /// shorter. Returns **Error::InvalidBuffer** if the `buffer` ends part way through a frame.
+    ///
+    /// Returns the number of frames that were written.
+    pub fn write_from(&mut self, buffer: &[F::Sample]) -> Result<usize, Error> {
+        let channels = Writer::channel_count(&self.flow) as usize;
+        check_whole_frames::<F::Sample>(buffer.len(), channels)?;
+        let pa_stream = self.pa_stream;
+        let ptr = buffer.as_ptr();
+        transfer_chunked(
+            buffer.len(),
+            channels,
+            self.frames_per_buffer,
+            |offset, frames| unsafe {
+                ffi::Pa_WriteStream(pa_stream, ptr.add(offset) as *const raw::c_void, frames)
+            },
+        )
+    }
+
+    /// Write all of the samples in a separate buffer per channel to a non-interleaved output
+    /// stream, the counterpart of **Stream::read_planar**.
+    ///
+    /// There must be a buffer for each of the stream's channels, and all of them must be of the
+    /// same length, otherwise **Error::InvalidChannelCount** or **Error::InvalidBuffer** is
+    /// returned respectively. Returns **Error::BadBufferPtr** if the stream is interleaved.
+    ///
+    /// Returns the number of frames that were written.
+    pub fn write_planar(&mut self, channels: &[&[F::Sample]]) -> Result<usize, Error> {
+        let layout = BufferLayout::from_c_params(self.flow.params_both_directions().1);
+        let lens: Vec<usize> = channels.iter().map(|channel| channel.len()).collect();
+        let (frames, lanes) = planar_frames::<F::Sample>(layout, &lens)?;
+        let pa_stream = self.pa_stream;
+        let mut offset_planes = Vec::with_capacity(channels.len());
+        transfer_chunked(frames, 1, self.frames_per_buffer, |offset, frames| {
+            offset_planes.clear();
+            offset_planes.extend(channels.iter().map(|c| c[offset * lanes..].as_ptr()));
+            unsafe {
+                ffi::Pa_WriteStream(
+                    pa_stream,
+                    offset_planes.as_ptr() as *const raw::c_void,
+                    frames,
+                )
+            }
+        })
+    }
+
+    /// Write all of the interleaved samples in the given `buffer` to an output stream, giving up
+    /// with **Error::TimedOut** if that takes longer than `timeout`, e.g. because the device has
+    /// stalled.
+    ///
+    /// As with **Stream::read_timeout**, the stream is polled every **TIMEOUT_POLL_INTERVAL**
+    /// and only as many frames as it has room for are written, so the timeout is only as precise
+    /// as the poll interval.
+    ///
+    /// Returns the number of frames that were written.
+    pub fn write_timeout(
+        &mut self,
+        buffer: &[F::Sample],
+        timeout: Duration,
+    ) -> Result<usize, Error> {
+        let channels = Writer::channel_count(&self.flow) as usize;
+        check_whole_frames::<F::Sample>(buffer.len(), channels)?;
+        let pa_stream = self.pa_stream;
+        let ptr = buffer.as_ptr();
+        transfer_with_timeout(
+            buffer.len(),
+            channels,
+            timeout,
+            || self.write_available(),
+            |offset, frames| unsafe {
+                ffi::Pa_WriteStream(pa_stream, ptr.add(offset) as *const raw::c_void, frames)
+            },
+        )
+    }
 }
 
 impl<F> Stream<NonBlocking, F> {
@@ -1355,76 +3466,280 @@ impl<F> Stream<NonBlocking, F> {
     pub fn open<S, C>(
         life: std::sync::Arc<super::Life>,
         settings: S,
-        mut callback: C,
+        callback: C,
     ) -> Result<Self, Error>
     where
         S: Settings<Flow = F>,
         F: Flow,
-        C: FnMut(F::CallbackArgs) -> ffi::PaStreamCallbackResult + 'static,
+        C: FnMut(F::CallbackArgs) -> ffi::PaStreamCallbackResult + Send + 'static,
     {
         let (flow, sample_rate, frames_per_buffer, flags) = settings.into_flow_and_settings();
         let (in_params, out_params) = flow.params_both_directions();
-        let in_channels = in_params.map(|p| p.channelCount).unwrap_or(0);
-        let out_channels = out_params.map(|p| p.channelCount).unwrap_or(0);
+        let non_blocking = NonBlocking::new::<F, C>(in_params, out_params, callback);
+        let mut stream = Stream::new_unopened(
+            non_blocking,
+            flow,
+            sample_rate,
+            frames_per_buffer,
+            flags,
+            life,
+        );
+        stream.reopen().map(|()| stream)
+    }
 
-        let callback_wrapper_fn = move |input: *const raw::c_void,
-                                        output: *mut raw::c_void,
-                                        frame_count: raw::c_ulong,
-                                        time_info: *const ffi::PaStreamCallbackTimeInfo,
-                                        flags: ffi::PaStreamCallbackFlags|
-              -> ffi::PaStreamCallbackResult {
-            let args = F::new_callback_args(
-                input,
-                output,
-                frame_count,
-                time_info,
-                flags,
-                in_channels,
-                out_channels,
-            );
-            callback(args)
-        };
+    /// Closes the underlying PortAudio stream and opens it again with the settings and callback
+    /// that the **Stream** was originally opened with.
+    ///
+    /// This is useful for recovering from a device being disconnected, after which stream
+    /// methods begin returning errors such as `Error::DeviceUnavailable`. The callback is kept,
+    /// along with any state it owns. The re-opened stream is stopped and must be started again.
+    ///
+    /// Note that the stream's **DeviceIndex**es are reused as-is. As PortAudio may have
+    /// re-enumerated its devices in the meantime, an index may now refer to a different physical
+    /// device, so it can be worth checking the devices via **PortAudio::devices** before
+    /// re-opening.
+    pub fn reopen(&mut self) -> Result<(), Error>
+    where
+        F: Flow,
+    {
+        let (in_params, out_params) = self.flow.params_both_directions();
+        self.port_audio_life
+            .check_stream_params(in_params, out_params)?;
+        self.close_for_reopen();
+        let pa_stream = open_non_blocking_stream(
+            in_params,
+            out_params,
+            self.flow.host_api_stream_info_both_directions(),
+            self.sample_rate,
+            self.frames_per_buffer,
+            self.flags,
+            &mut self.mode.callback,
+        )?;
+        self.pa_stream = pa_stream;
+        log_event!(
+            info,
+            "opened non-blocking stream {:p}: {}",
+            pa_stream,
+            LogStreamConfig::new(self, in_params, out_params)
+        );
+        if self.mode.callback.finished.is_some() {
+            self.register_finished_callback()?;
+        }
+        Ok(())
+    }
 
-        let non_blocking = NonBlocking {
-            // Here, we `Box` the wrapper so that we can collect the pointer from the callback.
-            //
-            // TODO: See if it is possible to pass a ptr to the callback_fn itself instead of
-            // requiring the wrapper at all. It seems like DST will be a problem here though.
-            callback: Box::new(CallbackFnWrapper {
-                // Here we `Box` the callback fn as we can't handle generic types in the c callback
-                // function.
-                f: Box::new(callback_wrapper_fn),
-            }),
+    /// Set a function to be called once the stream becomes inactive, i.e. once a call to
+    /// `Stream::stop` would no longer block.
+    ///
+    /// This happens after the stream callback returns `Complete` or `Abort` and any generated
+    /// output has finished playing, or after `Stream::stop` or `Stream::abort` is called. The
+    /// function is called once each time the stream becomes inactive, which makes it suitable for
+    /// e.g. advancing to the next track of a playlist.
+    ///
+    /// The function is called from a PortAudio thread. It replaces any previously set finished
+    /// callback and is dropped along with the **Stream**.
+    ///
+    /// The stream must be stopped, otherwise `Error::StreamIsNotStopped` is returned.
+    pub fn set_finished_callback<C>(&mut self, callback: C) -> Result<(), Error>
+    where
+        C: FnMut() + Send + 'static,
+    {
+        // Make sure PortAudio can't be calling the current finished callback while we replace it.
+        if !self.is_stopped()? {
+            return Err(Error::StreamIsNotStopped);
+        }
+        self.mode.callback.finished = Some(Box::new(callback));
+        self.register_finished_callback()
+    }
+
+    /// Register `stream_finished_proc` with PortAudio. The finished function itself is looked up
+    /// via the same `user_data` pointer as the stream callback.
+    fn register_finished_callback(&mut self) -> Result<(), Error> {
+        let error_code = unsafe {
+            ffi::Pa_SetStreamFinishedCallback(self.pa_stream, Some(stream_finished_proc))
         };
+        match Error::from_code(error_code) {
+            Error::NoError => Ok(()),
+            err => Err(err),
+        }
+    }
 
-        let mut stream = Stream::new_unopened(non_blocking, flow, life);
-        open_non_blocking_stream(
-            in_params,
-            out_params,
-            sample_rate,
-            frames_per_buffer,
-            flags,
-            &mut stream.mode.callback,
-        )
-        .map(|pa_stream| {
-            stream.pa_stream = pa_stream;
-            stream
-        })
+    /// Take the payload of a panic caught in the stream callback or the finished callback, if any.
+    ///
+    /// A panic must not unwind into PortAudio, so it is caught on PortAudio's thread instead and
+    /// a panicking stream callback aborts the stream, as though it had returned **Abort**. The
+    /// payload is kept until it is taken, e.g. to be passed on to `std::panic::resume_unwind`.
+    /// Only the first payload is kept until then.
+    ///
+    /// Note that the callback is still called if the stream is started again, in which case it
+    /// must cope with any state that it left behind when it panicked.
+    pub fn take_callback_panic(&mut self) -> Option<Box<dyn Any + Send>> {
+        let mut panic = self
+            .mode
+            .callback
+            .panic
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        panic.take()
     }
 
     /// Retrieve CPU usage information for the specified stream.
     ///
     /// The "CPU Load" is a fraction of total CPU time consumed by a callback stream's audio
     /// processing routines including, but not limited to the client supplied stream callback.
+    ///
+    /// The value is typically between `0.0` and `1.0`, where `1.0` means that the callback is
+    /// taking all of the time available per buffer. Values above `1.0` are possible and indicate
+    /// that the callback can't keep up. PortAudio only measures the load of callback streams,
+    /// which is why this is only provided for **NonBlocking** streams; a blocking stream's load
+    /// would always be reported as `0.0`.
+    ///
+    /// See the cpu_load.rs example for a usage example.
     pub fn cpu_load(&self) -> f64 {
         unsafe { ffi::Pa_GetStreamCpuLoad(self.pa_stream) }
     }
 }
 
+impl<S> Stream<NonBlocking, DefaultDuplex<S>>
+where
+    S: Sample + 'static,
+{
+    /// Open a new **NonBlocking** **Stream** on the default devices via `Pa_OpenDefaultStream`.
+    ///
+    /// See **PortAudio::open_default_stream_raw**.
+    pub fn open_default<C>(
+        life: std::sync::Arc<super::Life>,
+        num_input_channels: i32,
+        num_output_channels: i32,
+        sample_rate: f64,
+        frames_per_buffer: u32,
+        callback: C,
+    ) -> Result<Self, Error>
+    where
+        C: FnMut(DuplexCallbackArgs<'static, S, S>) -> ffi::PaStreamCallbackResult + Send + 'static,
+    {
+        // PortAudio chooses the latencies itself, so they are only known once the stream is open.
+        let params = |channels: i32, device: ffi::PaDeviceIndex| {
+            if channels == 0 || device < 0 {
+                return None;
+            }
+            let device = DeviceIndex(device as u32).into();
+            Some(Parameters::<S>::new_internal(device, channels, true, 0.0))
+        };
+        let in_params = params(num_input_channels, unsafe {
+            ffi::Pa_GetDefaultInputDevice()
+        });
+        let out_params = params(num_output_channels, unsafe {
+            ffi::Pa_GetDefaultOutputDevice()
+        });
+        let flow = DefaultDuplex {
+            in_params: in_params,
+            out_params: out_params,
+        };
+        let (in_c_params, out_c_params) = flow.params_both_directions();
+        life.check_stream_params(in_c_params, out_c_params)?;
+
+        let non_blocking =
+            NonBlocking::new::<DefaultDuplex<S>, C>(in_c_params, out_c_params, callback);
+        let flags = Flags::empty();
+        let mut stream = Stream::new_unopened(
+            non_blocking,
+            flow,
+            sample_rate,
+            frames_per_buffer,
+            flags,
+            life,
+        );
+
+        // See `open_non_blocking_stream` regarding the aliased `user_data`.
+        let user_data = {
+            let callback_fn_ptr = &mut *stream.mode.callback as *mut CallbackFnWrapper;
+            callback_fn_ptr as *mut raw::c_void
+        };
+        let mut c_stream_ptr: *mut raw::c_void = ptr::null_mut();
+        let error_code = unsafe {
+            ffi::Pa_OpenDefaultStream(
+                &mut c_stream_ptr,
+                num_input_channels,
+                num_output_channels,
+                S::sample_format().flags().bits(),
+                sample_rate,
+                frames_per_buffer as raw::c_ulong,
+                Some(stream_callback_proc),
+                user_data,
+            )
+        };
+        match Error::from_code(error_code) {
+            Error::NoError => (),
+            err => return Err(err),
+        }
+        stream.pa_stream = c_stream_ptr;
+
+        // Record the latencies that PortAudio chose so that `reopen` requests the same.
+        let info = stream.info()?;
+        if let Some(ref mut params) = stream.flow.in_params {
+            params.suggested_latency = info.input_latency.as_secs_f64();
+        }
+        if let Some(ref mut params) = stream.flow.out_params {
+            params.suggested_latency = info.output_latency.as_secs_f64();
+        }
+        log_event!(
+            info,
+            "opened default non-blocking stream {:p}: {}",
+            c_stream_ptr,
+            LogStreamConfig::new(&stream, in_c_params, out_c_params)
+        );
+        Ok(stream)
+    }
+}
+
 impl<M, F> Drop for Stream<M, F> {
+    /// Stops and closes the stream if it is still open.
+    ///
+    /// This runs before any of the fields are dropped. `Pa_CloseStream` only returns once the
+    /// callback has returned for the last time, so the callback's state owned by the
+    /// **NonBlocking** mode is never dropped while PortAudio may still call it. This holds
+    /// regardless of whether the callback has already returned **Complete** or **Abort**.
     fn drop(&mut self) {
-        self.stop().ok();
-        self.close().ok();
+        if !self.pa_stream.is_null() {
+            self.stop().ok();
+            self.close_in_place().ok();
+        }
+    }
+}
+
+impl<'a, M, F> StreamGuard<'a, M, F> {
+    /// Stop the stream now, returning any error encountered in doing so.
+    pub fn stop(self) -> Result<(), Error> {
+        let result = self.stream.stop();
+        // The stream has been stopped, or failed to, so there is nothing left for `drop` to do.
+        std::mem::forget(self);
+        result
+    }
+}
+
+impl<'a, M, F> std::ops::Deref for StreamGuard<'a, M, F> {
+    type Target = Stream<M, F>;
+    fn deref(&self) -> &Stream<M, F> {
+        self.stream
+    }
+}
+
+impl<'a, M, F> std::ops::DerefMut for StreamGuard<'a, M, F> {
+    fn deref_mut(&mut self) -> &mut Stream<M, F> {
+        self.stream
+    }
+}
+
+impl<'a, M, F> Drop for StreamGuard<'a, M, F> {
+    /// Stops the stream unless it has already been stopped, e.g. via the **Stream** itself.
+    fn drop(&mut self) {
+        // A stream whose callback has finished isn't stopped yet, so it is stopped as well.
+        if let Ok(false) = self.stream.is_stopped() {
+            if let Err(_err) = self.stream.stop() {
+                log_event!(warn, "failed to stop stream on guard drop: {}", _err);
+            }
+        }
     }
 }
 
@@ -1439,5 +3754,32 @@ extern "C" fn stream_callback_proc(
     user_callback_ptr: *mut raw::c_void,
 ) -> ffi::PaStreamCallbackResult {
     let callback = user_callback_ptr as *mut CallbackFnWrapper;
-    unsafe { ((*callback).f)(input, output, frame_count, time_info, flags) }
+    // Unwinding into PortAudio's C code is undefined behaviour, so a panicking callback aborts
+    // the stream instead and leaves the payload for the user to take.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+        ((*callback).f)(input, output, frame_count, time_info, flags)
+    }));
+    match result {
+        Ok(result) => result,
+        Err(payload) => {
+            log_event!(error, "stream callback panicked, aborting the stream");
+            unsafe { (*callback).store_panic(payload) };
+            ffi::PA_ABORT
+        }
+    }
+}
+
+/// A callback procedure to be used by portaudio in the case that a finished callback has been set
+/// via `Stream::set_finished_callback`.
+extern "C" fn stream_finished_proc(user_callback_ptr: *mut raw::c_void) {
+    let callback = user_callback_ptr as *mut CallbackFnWrapper;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+        if let Some(ref mut finished) = (*callback).finished {
+            finished();
+        }
+    }));
+    if let Err(payload) = result {
+        log_event!(error, "stream finished callback panicked");
+        unsafe { (*callback).store_panic(payload) };
+    }
 }
@@ -0,0 +1,75 @@
+//!
+//! A sine tone generator for filling output buffers, e.g. within a non-blocking stream callback.
+//!
+
+use std::f64::consts::PI;
+
+/// Generates a sine tone, continuing its phase from one buffer to the next so that the tone has
+/// no clicks at buffer boundaries.
+///
+/// ```
+/// use portaudio::tone::SineGenerator;
+///
+/// // Filling two buffers produces the same tone as filling one buffer of the same total length.
+/// let mut split = SineGenerator::new(440.0, 48_000.0, 0.5, 2);
+/// let mut first = [0.0; 128];
+/// let mut second = [0.0; 128];
+/// split.fill(&mut first);
+/// split.fill(&mut second);
+///
+/// let mut whole = [0.0; 256];
+/// SineGenerator::new(440.0, 48_000.0, 0.5, 2).fill(&mut whole);
+/// assert_eq!(&whole[..128], &first[..]);
+/// assert_eq!(&whole[128..], &second[..]);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SineGenerator {
+    /// The frequency of the tone in Hz.
+    pub freq: f64,
+    /// The sample rate of the buffers being filled.
+    pub sample_rate: f64,
+    /// The peak amplitude of the tone, where `1.0` is full scale.
+    pub amplitude: f32,
+    /// The number of interleaved channels of the buffers being filled, each of which is given
+    /// the same tone.
+    pub channels: usize,
+    /// The phase of the next frame, in cycles within `[0.0, 1.0)`.
+    phase: f64,
+}
+
+impl SineGenerator {
+    /// Construct a generator starting at zero phase.
+    pub fn new(freq: f64, sample_rate: f64, amplitude: f32, channels: usize) -> Self {
+        SineGenerator {
+            freq: freq,
+            sample_rate: sample_rate,
+            amplitude: amplitude,
+            channels: channels,
+            phase: 0.0,
+        }
+    }
+
+    /// Fill the interleaved `out` buffer with the following frames of the tone.
+    ///
+    /// Changing `freq` between calls continues from the current phase, so the tone glides to
+    /// the new frequency without a click. Any trailing samples that don't make up a whole frame
+    /// are set to silence.
+    ///
+    /// This may be called directly from a non-blocking output callback, e.g.
+    /// `generator.fill(args.buffer)`, as it neither allocates nor locks.
+    pub fn fill(&mut self, out: &mut [f32]) {
+        let channels = self.channels.max(1);
+        let step = self.freq / self.sample_rate;
+        let mut frames = out.chunks_exact_mut(channels);
+        for frame in &mut frames {
+            let sample = self.amplitude * (2.0 * PI * self.phase).sin() as f32;
+            for s in frame.iter_mut() {
+                *s = sample;
+            }
+            self.phase = (self.phase + step).fract();
+        }
+        for s in frames.into_remainder() {
+            *s = 0.0;
+        }
+    }
+}
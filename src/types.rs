@@ -26,6 +26,7 @@
 use ffi;
 use num::FromPrimitive;
 use std::os::raw;
+use std::time::Duration;
 
 pub use self::sample_format_flags::SampleFormatFlags;
 
@@ -47,6 +48,15 @@ pub enum DeviceKind {
     UseHostApiSpecificDeviceSpecification,
 }
 
+/// The direction in which audio flows through a device or stream.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Audio that is recorded from a device.
+    Input,
+    /// Audio that is played back through a device.
+    Output,
+}
+
 impl From<DeviceIndex> for ffi::PaDeviceIndex {
     fn from(idx: DeviceIndex) -> ffi::PaDeviceIndex {
         let DeviceIndex(idx) = idx;
@@ -72,8 +82,21 @@ impl From<DeviceKind> for ffi::PaDeviceIndex {
 /// The special value may be used to request that the stream callback will receive an optimal (and
 /// possibly varying) number of frames based on host requirements and the requested latency
 /// settings.
+///
+/// This is often the lowest latency choice, so callbacks must use the `frames` that they are
+/// passed rather than assume a fixed buffer size. Blocking streams have no fixed block size
+/// either: the frames reported by **Stream::read_available** and **Stream::write_available** may
+/// vary from one call to the next, and **Stream::read_into** and **Stream::write_from** transfer
+/// any number of frames at once. **Stream::read** and **Stream::blocks** need a fixed block size
+/// and return an error for such streams.
 pub const FRAMES_PER_BUFFER_UNSPECIFIED: u32 = 0;
 
+/// The sample rates probed by **PortAudio::supported_sample_rates**, in Hz.
+pub const STANDARD_SAMPLE_RATES: [f64; 10] = [
+    8_000.0, 11_025.0, 16_000.0, 22_050.0, 44_100.0, 48_000.0, 88_200.0, 96_000.0, 176_400.0,
+    192_000.0,
+];
+
 /// The type used to enumerate to host APIs at runtime.
 /// Values of this type range from 0 to (pa::get_host_api_count()-1).
 pub type HostApiIndex = ffi::PaHostApiIndex;
@@ -81,9 +104,250 @@ pub type HostApiIndex = ffi::PaHostApiIndex;
 /// The type used to represent monotonic time in seconds.
 pub type Time = ffi::PaTime;
 
+/// Convert a **Time** in seconds into a **Duration**, treating negative and non-finite values
+/// (which PortAudio uses to signal errors) as zero.
+pub(crate) fn time_to_duration(time: Time) -> Duration {
+    if time.is_finite() && time > 0.0 {
+        Duration::from_secs_f64(time)
+    } else {
+        Duration::from_secs(0)
+    }
+}
+
+/// The number of nanoseconds in a second.
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// The wall-clock time that the given number of frames spans at the given sample rate, rounded to
+/// the nearest nanosecond.
+///
+/// Integral sample rates are computed exactly in integer arithmetic, so e.g. 44 100 frames at
+/// 44.1 kHz are exactly one second. A sample rate that isn't positive produces a zero duration.
+///
+/// **Stream::frames_to_duration** uses the stream's own sample rate instead.
+///
+/// ```
+/// # extern crate portaudio;
+/// use portaudio::{duration_to_frames, frames_to_duration};
+/// use std::time::Duration;
+/// # fn main() {
+/// assert_eq!(frames_to_duration(44_100, 44_100.0), Duration::from_secs(1));
+/// assert_eq!(frames_to_duration(1, 48_000.0), Duration::from_nanos(20_833));
+/// assert_eq!(duration_to_frames(Duration::from_millis(10), 44_100.0), 441);
+/// # }
+/// ```
+pub fn frames_to_duration(frames: u64, sample_rate: f64) -> Duration {
+    match integral_rate(sample_rate) {
+        Some(rate) => {
+            let rate = rate as u64;
+            let nanos = ((frames % rate) as u128 * NANOS_PER_SEC + rate as u128 / 2) / rate as u128;
+            Duration::from_secs(frames / rate) + Duration::from_nanos(nanos as u64)
+        }
+        None if sample_rate > 0.0 => time_to_duration(frames as f64 / sample_rate),
+        None => Duration::from_secs(0),
+    }
+}
+
+/// The number of frames that span the given `duration` at the given sample rate, rounded to the
+/// nearest frame. This is the inverse of **frames_to_duration**.
+///
+/// The result saturates at `u64::MAX`, and a sample rate that isn't positive produces zero
+/// frames.
+///
+/// **Stream::duration_to_frames** uses the stream's own sample rate instead.
+pub fn duration_to_frames(duration: Duration, sample_rate: f64) -> u64 {
+    match integral_rate(sample_rate) {
+        Some(rate) => {
+            let nanos = duration.as_nanos();
+            let frames = (nanos * rate as u128 + NANOS_PER_SEC / 2) / NANOS_PER_SEC;
+            frames.min(u64::MAX as u128) as u64
+        }
+        // Float to integer casts saturate, and produce zero for NaN.
+        None if sample_rate > 0.0 => (duration.as_secs_f64() * sample_rate).round() as u64,
+        None => 0,
+    }
+}
+
+/// The sample rate as an integer, if it is a positive whole number that fits in a `u32`.
+fn integral_rate(sample_rate: f64) -> Option<u32> {
+    if sample_rate >= 1.0 && sample_rate <= u32::MAX as f64 && sample_rate.fract() == 0.0 {
+        Some(sample_rate as u32)
+    } else {
+        None
+    }
+}
+
 /// An type alias used to represent a given number of frames.
 pub type Frames = i64;
 
+/// A structure describing the release of the PortAudio library in use.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VersionInfo {
+    /// The major version, e.g. `19` for PortAudio V19.7.0.
+    pub major: u32,
+    /// The minor version, e.g. `7` for PortAudio V19.7.0.
+    pub minor: u32,
+    /// The sub-minor version, e.g. `0` for PortAudio V19.7.0.
+    pub sub_minor: u32,
+    /// The version control revision the library was built from. This may be empty.
+    pub revision: String,
+}
+
+impl VersionInfo {
+    /// Construct a **VersionInfo** from the equivalent C struct.
+    pub fn from_c_info(c_info: ffi::PaVersionInfo) -> VersionInfo {
+        VersionInfo {
+            major: c_info.versionMajor as u32,
+            minor: c_info.versionMinor as u32,
+            sub_minor: c_info.versionSubMinor as u32,
+            revision: ffi::c_str_to_str(c_info.versionControlRevision)
+                .unwrap_or("")
+                .to_string(),
+        }
+    }
+
+    /// Parse a **VersionInfo** from a version text such as
+    /// `"PortAudio V19.6.0-devel, revision 396fe4b6699ae929d3a685b3ef8a7e97396139a4"` or
+    /// `"PortAudio V19-devel (built Sep  6 2012 10:07:21)"`.
+    ///
+    /// Components missing from the text are reported as `0`. Returns `None` if no version
+    /// number can be found at all.
+    pub fn from_version_text(text: &str) -> Option<VersionInfo> {
+        let start = text.find('V')? + 1;
+        let version: String = text[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let mut numbers = version.split('.').map(|n| n.parse::<u32>().ok());
+        let major = numbers.next()??;
+        let minor = numbers.next().unwrap_or(Some(0)).unwrap_or(0);
+        let sub_minor = numbers.next().unwrap_or(Some(0)).unwrap_or(0);
+        let revision = match text.find("revision ") {
+            Some(idx) => text[idx + "revision ".len()..]
+                .split(|c: char| c.is_whitespace() || c == ',' || c == ')')
+                .next()
+                .unwrap_or("")
+                .to_string(),
+            None => String::new(),
+        };
+        Some(VersionInfo {
+            major: major,
+            minor: minor,
+            sub_minor: sub_minor,
+            revision: revision,
+        })
+    }
+}
+
+/// A packed 24-bit signed integer sample, used for streams in the **SampleFormat::I24** format.
+///
+/// The three bytes are stored in native byte order, so a buffer of **I24**s matches the layout
+/// PortAudio expects for `paInt24` with a stride of 3 bytes per sample.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct I24(pub [u8; 3]);
+
+impl I24 {
+    /// The smallest value that can be represented by an **I24**.
+    pub const MIN: i32 = -(1 << 23);
+    /// The largest value that can be represented by an **I24**.
+    pub const MAX: i32 = (1 << 23) - 1;
+
+    /// Construct an **I24** from the given value.
+    ///
+    /// Returns `None` if the value lies outside the range `I24::MIN..=I24::MAX`.
+    pub fn new(value: i32) -> Option<I24> {
+        if (I24::MIN..=I24::MAX).contains(&value) {
+            Some(I24::wrapping_new(value))
+        } else {
+            None
+        }
+    }
+
+    /// Construct an **I24** from the lowest 24 bits of the given value.
+    pub fn wrapping_new(value: i32) -> I24 {
+        let b = value.to_le_bytes();
+        let le = [b[0], b[1], b[2]];
+        if cfg!(target_endian = "little") {
+            I24(le)
+        } else {
+            I24([le[2], le[1], le[0]])
+        }
+    }
+
+    /// The sign-extended value of the sample.
+    pub fn to_i32(self) -> i32 {
+        let I24(b) = self;
+        let le = if cfg!(target_endian = "little") {
+            b
+        } else {
+            [b[2], b[1], b[0]]
+        };
+        // Shift the sign bit into place and back again to sign-extend.
+        i32::from_le_bytes([0, le[0], le[1], le[2]]) >> 8
+    }
+}
+
+impl From<I24> for i32 {
+    fn from(sample: I24) -> i32 {
+        sample.to_i32()
+    }
+}
+
+impl ::std::fmt::Debug for I24 {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "I24({})", self.to_i32())
+    }
+}
+
+impl ::num::ToPrimitive for I24 {
+    fn to_i64(&self) -> Option<i64> {
+        Some(I24::to_i32(*self) as i64)
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        let value = I24::to_i32(*self);
+        if value >= 0 {
+            Some(value as u64)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromPrimitive for I24 {
+    fn from_i64(n: i64) -> Option<I24> {
+        if n >= I24::MIN as i64 && n <= I24::MAX as i64 {
+            Some(I24::wrapping_new(n as i32))
+        } else {
+            None
+        }
+    }
+
+    fn from_u64(n: u64) -> Option<I24> {
+        if n <= I24::MAX as u64 {
+            Some(I24::wrapping_new(n as i32))
+        } else {
+            None
+        }
+    }
+}
+
+/// Implements the arithmetic operators for **I24**, wrapping around on overflow.
+macro_rules! impl_i24_op {
+    ($($trait_name:ident $fn_name:ident $op:ident),*) => {
+        $(
+            impl ::std::ops::$trait_name for I24 {
+                type Output = I24;
+                fn $fn_name(self, rhs: I24) -> I24 {
+                    I24::wrapping_new(self.to_i32().$op(rhs.to_i32()))
+                }
+            }
+        )*
+    };
+}
+
+impl_i24_op!(Add add wrapping_add, Sub sub wrapping_sub, Mul mul wrapping_mul, Div div wrapping_div);
+
 /// A type used to dynamically represent the various standard sample formats (usually) supported by
 /// all PortAudio implementations.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -150,6 +414,56 @@ impl SampleFormat {
         }
     }
 
+    /// Converts PortAudio's raw `PaSampleFormat` into a **SampleFormat**.
+    ///
+    /// Format bits that aren't recognised, as well as the `paNonInterleaved` bit, are ignored.
+    ///
+    /// ```
+    /// # extern crate portaudio;
+    /// use portaudio::SampleFormat;
+    /// # fn main() {
+    /// let formats = [
+    ///     SampleFormat::F32,
+    ///     SampleFormat::I32,
+    ///     SampleFormat::I24,
+    ///     SampleFormat::I16,
+    ///     SampleFormat::I8,
+    ///     SampleFormat::U8,
+    ///     SampleFormat::Custom,
+    /// ];
+    /// for &format in formats.iter() {
+    ///     assert_eq!(SampleFormat::from_ffi(format.to_ffi()), format);
+    /// }
+    /// assert_eq!(SampleFormat::from_ffi(0), SampleFormat::Unknown);
+    /// # }
+    /// ```
+    pub fn from_ffi(format: ffi::PaSampleFormat) -> Self {
+        SampleFormat::from_flags(SampleFormatFlags::from_bits_truncate(format))
+    }
+
+    /// Converts `self` into PortAudio's raw `PaSampleFormat`, as passed within the
+    /// `PaStreamParameters`. **Unknown** converts to `0`.
+    pub fn to_ffi(self) -> ffi::PaSampleFormat {
+        self.flags().bits()
+    }
+
+    /// A short, lowercase name for the format, e.g. `"f32"` or `"i24"`, suitable for displaying in
+    /// a UI or storing in a configuration file.
+    ///
+    /// The names of the standard formats match the Rust types used for them, see **Sample**.
+    pub fn name(self) -> &'static str {
+        match self {
+            SampleFormat::F32 => "f32",
+            SampleFormat::I32 => "i32",
+            SampleFormat::I24 => "i24",
+            SampleFormat::I16 => "i16",
+            SampleFormat::I8 => "i8",
+            SampleFormat::U8 => "u8",
+            SampleFormat::Custom => "custom",
+            SampleFormat::Unknown => "unknown",
+        }
+    }
+
     /// Converts `self` into the respective **SampleFormatFlags**.
     pub fn flags(self) -> SampleFormatFlags {
         match self {
@@ -280,12 +594,47 @@ pub enum HostApiTypeId {
 }
 }
 
+impl HostApiTypeId {
+    /// The commonly used name of the host API, e.g. for display in a UI.
+    ///
+    /// Unlike **HostApiInfo::name**, which is whatever the host API calls itself (e.g. "Windows
+    /// WASAPI"), this is the same for every build of PortAudio.
+    ///
+    /// ```
+    /// # extern crate portaudio;
+    /// use portaudio::HostApiTypeId;
+    /// # fn main() {
+    /// assert_eq!(HostApiTypeId::CoreAudio.backend_name(), "Core Audio");
+    /// assert_eq!(HostApiTypeId::WASAPI.backend_name(), "WASAPI");
+    /// # }
+    /// ```
+    pub fn backend_name(self) -> &'static str {
+        match self {
+            HostApiTypeId::InDevelopment => "In Development",
+            HostApiTypeId::DirectSound => "DirectSound",
+            HostApiTypeId::MME => "MME",
+            HostApiTypeId::ASIO => "ASIO",
+            HostApiTypeId::SoundManager => "Sound Manager",
+            HostApiTypeId::CoreAudio => "Core Audio",
+            HostApiTypeId::OSS => "OSS",
+            HostApiTypeId::ALSA => "ALSA",
+            HostApiTypeId::AL => "Irix AL",
+            HostApiTypeId::BeOS => "BeOS",
+            HostApiTypeId::WDMKS => "WDM-KS",
+            HostApiTypeId::JACK => "JACK",
+            HostApiTypeId::WASAPI => "WASAPI",
+            HostApiTypeId::AudioScienceHPI => "AudioScience HPI",
+        }
+    }
+}
+
 /// A structure containing information about a particular host API.
 #[derive(Clone, Debug, PartialEq)]
 pub struct HostApiInfo<'a> {
     /// The version of the struct
     pub struct_version: i32,
-    /// The type of the current host
+    /// The type of the current host, which identifies the host API regardless of its `name`, e.g.
+    /// to enable features that are specific to one host API.
     pub host_type: HostApiTypeId,
     /// The name of the host
     pub name: &'a str,
@@ -332,6 +681,28 @@ impl<'a> HostApiInfo<'a> {
             default_output_device: default_output_device,
         })
     }
+
+    /// The commonly used name of the host API, as given by **HostApiTypeId::backend_name**.
+    pub fn backend_name(&self) -> &'static str {
+        self.host_type.backend_name()
+    }
+
+    /// The **HostApiTypeId** of the host API, which unlike its **HostApiIndex** is the same
+    /// across PortAudio builds.
+    ///
+    /// This is the reverse of **PortAudio::host_api_type_id_to_index**.
+    pub fn type_id(&self) -> HostApiTypeId {
+        self.host_type
+    }
+
+    /// The index to the default device for the given **Direction**, i.e. the
+    /// `default_input_device` or `default_output_device`.
+    pub fn default_device(&self, direction: Direction) -> Option<DeviceIndex> {
+        match direction {
+            Direction::Input => self.default_input_device,
+            Direction::Output => self.default_output_device,
+        }
+    }
 }
 
 impl<'a> From<HostApiInfo<'a>> for ffi::PaHostApiInfo {
@@ -356,34 +727,37 @@ impl<'a> From<HostApiInfo<'a>> for ffi::PaHostApiInfo {
 }
 
 /// Structure used to return information about a host error condition.
+///
+/// The details are copied out of PortAudio, as the underlying text buffer is overwritten by the
+/// next host error.
 #[derive(Clone, PartialEq, PartialOrd, Debug)]
-pub struct HostErrorInfo<'a> {
+pub struct HostErrorInfo {
     /// The host API which returned the error code
     pub host_api_type: HostApiTypeId,
-    /// The code of the error
-    pub code: u32,
-    /// The string which explain the error
-    pub text: &'a str,
+    /// The error code returned by the host API
+    pub error_code: i64,
+    /// The host API's textual description of the error
+    pub error_text: String,
 }
 
-impl<'a> HostErrorInfo<'a> {
+impl HostErrorInfo {
     /// Construct a HostErrorInfo from the equivalent C struct.
-    pub fn from_c_error_info(c_error: ffi::PaHostErrorInfo) -> HostErrorInfo<'a> {
+    pub fn from_c_error_info(c_error: ffi::PaHostErrorInfo) -> HostErrorInfo {
+        let error_text = if c_error.errorText.is_null() {
+            String::new()
+        } else {
+            unsafe { ::std::ffi::CStr::from_ptr(c_error.errorText) }
+                .to_string_lossy()
+                .into_owned()
+        };
+        // `c_long` is only 32 bits wide on Windows, where this widens the code.
+        #[allow(clippy::useless_conversion)]
+        let error_code = i64::from(c_error.errorCode);
         HostErrorInfo {
-            host_api_type: FromPrimitive::from_u32(c_error.hostApiType).unwrap(),
-            code: c_error.errorCode as u32,
-            text: ffi::c_str_to_str(c_error.errorText)
-                .unwrap_or("<Failed to convert str from CStr>"),
-        }
-    }
-}
-
-impl<'a> From<HostErrorInfo<'a>> for ffi::PaHostErrorInfo {
-    fn from(error: HostErrorInfo<'a>) -> Self {
-        ffi::PaHostErrorInfo {
-            hostApiType: FromPrimitive::from_i32(error.host_api_type as i32).unwrap(),
-            errorCode: error.code as raw::c_long,
-            errorText: ffi::str_to_c_str(error.text),
+            host_api_type: FromPrimitive::from_u32(c_error.hostApiType)
+                .unwrap_or(HostApiTypeId::InDevelopment),
+            error_code: error_code,
+            error_text: error_text,
         }
     }
 }
@@ -403,13 +777,17 @@ pub struct DeviceInfo<'a> {
     pub max_input_channels: i32,
     /// maximal number of output channel for this device
     pub max_output_channels: i32,
-    /// The default low latency for input with this device
+    /// The default low latency for input with this device, in seconds. See
+    /// **DeviceInfo::low_input_latency** for a **Duration**.
     pub default_low_input_latency: Time,
-    /// The default low latency for output with this device
+    /// The default low latency for output with this device, in seconds. See
+    /// **DeviceInfo::low_output_latency** for a **Duration**.
     pub default_low_output_latency: Time,
-    /// The default high latency for input with this device
+    /// The default high latency for input with this device, in seconds. See
+    /// **DeviceInfo::high_input_latency** for a **Duration**.
     pub default_high_input_latency: Time,
-    /// The default high latency for output with this device
+    /// The default high latency for output with this device, in seconds. See
+    /// **DeviceInfo::high_output_latency** for a **Duration**.
     pub default_high_output_latency: Time,
     /// The default sample rate for this device
     pub default_sample_rate: f64,
@@ -431,6 +809,176 @@ impl<'a> DeviceInfo<'a> {
             default_sample_rate: c_info.defaultSampleRate,
         }
     }
+
+    /// A string identifying the device that, unlike its **DeviceIndex**, remains the same when
+    /// devices are added or removed.
+    ///
+    /// The signature combines the device's name, the type of its host API and its channel counts.
+    /// It may be persisted and later resolved back to an index with
+    /// **PortAudio::find_device_by_signature**.
+    ///
+    /// Signatures are best-effort: PortAudio doesn't guarantee device names are unique, so two
+    /// identical devices on the same host API share a signature.
+    pub fn signature(&self) -> String {
+        let c_host_info = unsafe { ffi::Pa_GetHostApiInfo(self.host_api) };
+        let host_type: Option<HostApiTypeId> = if c_host_info.is_null() {
+            None
+        } else {
+            FromPrimitive::from_u32(unsafe { (*c_host_info).type_ })
+        };
+        let host_type = match host_type {
+            Some(ty) => format!("{:?}", ty),
+            None => "Unknown".to_string(),
+        };
+        format!(
+            "{}|{}|{}|{}",
+            self.name, host_type, self.max_input_channels, self.max_output_channels
+        )
+    }
+
+    /// Whether the device has any input channels.
+    ///
+    /// ```
+    /// # extern crate portaudio;
+    /// # fn main() {
+    /// # let info = portaudio::DeviceInfo {
+    /// #     struct_version: 2, name: "Mic", host_api: 0, default_sample_rate: 48_000.0,
+    /// #     default_low_input_latency: 0.01, default_low_output_latency: 0.01,
+    /// #     default_high_input_latency: 0.1, default_high_output_latency: 0.1,
+    /// max_input_channels: 1,
+    /// max_output_channels: 0,
+    /// # };
+    /// assert!(info.is_input());
+    /// assert!(!info.is_output());
+    /// assert!(!info.is_duplex());
+    /// # }
+    /// ```
+    pub fn is_input(&self) -> bool {
+        self.max_input_channels > 0
+    }
+
+    /// Whether the device has any output channels.
+    pub fn is_output(&self) -> bool {
+        self.max_output_channels > 0
+    }
+
+    /// Whether the device has both input and output channels, so that a single device can be
+    /// used for both directions of a duplex stream.
+    pub fn is_duplex(&self) -> bool {
+        self.is_input() && self.is_output()
+    }
+
+    /// The maximum number of channels that the device has in the given **Direction**.
+    pub fn max_channels(&self, direction: Direction) -> i32 {
+        match direction {
+            Direction::Input => self.max_input_channels,
+            Direction::Output => self.max_output_channels,
+        }
+    }
+
+    /// Whether the device has at least `count` channels in the given **Direction**.
+    ///
+    /// This only compares `count` with the maximum channel count, so
+    /// **PortAudio::is_input_format_supported** and friends remain the authority on whether a
+    /// stream can actually be opened.
+    ///
+    /// ```
+    /// # extern crate portaudio;
+    /// use portaudio::Direction;
+    /// # fn main() {
+    /// # let info = portaudio::DeviceInfo {
+    /// #     struct_version: 2, name: "Interface", host_api: 0, default_sample_rate: 48_000.0,
+    /// #     default_low_input_latency: 0.01, default_low_output_latency: 0.01,
+    /// #     default_high_input_latency: 0.1, default_high_output_latency: 0.1,
+    /// max_input_channels: 2,
+    /// max_output_channels: 8,
+    /// # };
+    /// assert!(info.supports_channels(2, Direction::Input));
+    /// assert!(!info.supports_channels(6, Direction::Input));
+    /// assert!(info.supports_channels(6, Direction::Output));
+    /// # }
+    /// ```
+    pub fn supports_channels(&self, count: i32, direction: Direction) -> bool {
+        count > 0 && count <= self.max_channels(direction)
+    }
+
+    /// The default low and high latencies that PortAudio reports for the given **Direction**, in
+    /// that order.
+    ///
+    /// The low latency suits interactive use and the high latency suits robust, non-interactive
+    /// playback or recording, e.g. of files.
+    ///
+    /// ```
+    /// # extern crate portaudio;
+    /// use portaudio::Direction;
+    /// use std::time::Duration;
+    /// # fn main() {
+    /// # let info = portaudio::DeviceInfo {
+    /// #     struct_version: 2, name: "Speakers", host_api: 0, default_sample_rate: 48_000.0,
+    /// #     max_input_channels: 0, max_output_channels: 2,
+    /// #     default_low_input_latency: 0.0, default_high_input_latency: 0.0,
+    /// default_low_output_latency: 0.01,
+    /// default_high_output_latency: 0.1,
+    /// # };
+    /// let (low, high) = info.default_latency_range(Direction::Output);
+    /// assert_eq!(low, Duration::from_millis(10));
+    /// assert_eq!(high, Duration::from_millis(100));
+    /// # }
+    /// ```
+    pub fn default_latency_range(&self, direction: Direction) -> (Duration, Duration) {
+        let (low, high) = match direction {
+            Direction::Input => (
+                self.default_low_input_latency,
+                self.default_high_input_latency,
+            ),
+            Direction::Output => (
+                self.default_low_output_latency,
+                self.default_high_output_latency,
+            ),
+        };
+        (time_to_duration(low), time_to_duration(high))
+    }
+
+    /// The default low input latency as a **Duration**, which suits interactive use.
+    ///
+    /// The latency fields hold seconds, as PortAudio reports them, which are easily mistaken for
+    /// milliseconds. Negative and non-finite values are treated as zero.
+    ///
+    /// ```
+    /// # extern crate portaudio;
+    /// use std::time::Duration;
+    /// # fn main() {
+    /// # let info = portaudio::DeviceInfo {
+    /// #     struct_version: 2, name: "Mic", host_api: 0, default_sample_rate: 48_000.0,
+    /// #     max_input_channels: 1, max_output_channels: 0,
+    /// #     default_low_output_latency: 0.0, default_high_output_latency: 0.0,
+    /// default_low_input_latency: 0.005,
+    /// default_high_input_latency: 0.08,
+    /// # };
+    /// assert_eq!(info.low_input_latency(), Duration::from_millis(5));
+    /// assert_eq!(info.high_input_latency(), Duration::from_millis(80));
+    /// # }
+    /// ```
+    pub fn low_input_latency(&self) -> Duration {
+        time_to_duration(self.default_low_input_latency)
+    }
+
+    /// The default low output latency as a **Duration**, which suits interactive use.
+    pub fn low_output_latency(&self) -> Duration {
+        time_to_duration(self.default_low_output_latency)
+    }
+
+    /// The default high input latency as a **Duration**, which suits robust, non-interactive
+    /// recording.
+    pub fn high_input_latency(&self) -> Duration {
+        time_to_duration(self.default_high_input_latency)
+    }
+
+    /// The default high output latency as a **Duration**, which suits robust, non-interactive
+    /// playback.
+    pub fn high_output_latency(&self) -> Duration {
+        time_to_duration(self.default_high_output_latency)
+    }
 }
 
 impl<'a> From<DeviceInfo<'a>> for ffi::PaDeviceInfo {
@@ -0,0 +1,749 @@
+//!
+//! A minimal WAV (RIFF) reader and writer, along with the helpers built on them for recording
+//! to and playing WAV files, **PortAudio::record_to_wav** and **PortAudio::play_wav**.
+//!
+//! Only available with the `wav` feature, which adds no dependencies.
+//!
+
+use num::ToPrimitive;
+use std::error::Error as StdError;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::error::{Error, FormatSupportError};
+use super::stream::{
+    InputSettings, NonBlocking, Output, OutputCallbackArgs, OutputSettings, Parameters, Stream,
+};
+use super::types::{DeviceIndex, SampleFormat};
+use super::{PortAudio, Sample};
+use ffi;
+
+/// The `WAVE_FORMAT_PCM` format tag, used for integer samples.
+const FORMAT_PCM: u16 = 1;
+/// The `WAVE_FORMAT_IEEE_FLOAT` format tag, used for `f32` samples.
+const FORMAT_IEEE_FLOAT: u16 = 3;
+/// The `WAVE_FORMAT_EXTENSIBLE` format tag, whose actual format follows in the `fmt ` chunk.
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+/// The size of the header written by **WavWriter**, up to the start of the sample data.
+const HEADER_LEN: u32 = 44;
+/// The number of frames that the recording and playback helpers transfer at a time.
+const FRAMES_PER_BUFFER: u32 = 1024;
+/// How often **PortAudio::play_wav** checks whether playback has finished.
+const PLAYBACK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// The most channels that **PortAudio::record_to_wav** records.
+const RECORD_MAX_CHANNELS: i32 = 2;
+
+/// The errors that may occur while reading or writing WAV files.
+#[derive(Debug)]
+pub enum WavError {
+    /// An error returned by PortAudio, e.g. while recording.
+    PortAudio(Error),
+    /// The output device doesn't support the format of the file being played, e.g. its sample
+    /// rate. The files aren't resampled.
+    Format(FormatSupportError),
+    /// An error reading or writing the file.
+    Io(io::Error),
+    /// The sample format, channel count or sample rate can't be represented in a WAV file, or
+    /// the file has grown beyond the 4 GiB that its header can describe.
+    Unsupported(&'static str),
+    /// The file being read is not a valid WAV file.
+    Malformed(&'static str),
+}
+
+/// The layout of the samples in a WAV file.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WavSpec {
+    /// The number of interleaved channels.
+    pub channels: u16,
+    /// The number of frames per second.
+    pub sample_rate: u32,
+    /// The format of each sample, which determines the bit depth of the file.
+    ///
+    /// **SampleFormat::I8** samples are stored as 8-bit WAV samples, which are unsigned, by
+    /// offsetting them by 128.
+    pub sample_format: SampleFormat,
+}
+
+/// Writes interleaved samples to a WAV file, filling in the sizes in its header on
+/// **WavWriter::finalize**.
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    spec: WavSpec,
+    data_len: u32,
+}
+
+/// Reads the interleaved samples of a WAV file.
+///
+/// Integer PCM files with 8, 16, 24 or 32 bits per sample and 32-bit float files are supported,
+/// including those with a `WAVE_FORMAT_EXTENSIBLE` header.
+pub struct WavReader<R: Read> {
+    reader: R,
+    spec: WavSpec,
+    /// The number of bytes of sample data in the file.
+    len: u32,
+    /// The number of bytes of sample data that are yet to be read.
+    remaining: u32,
+    /// The raw bytes of the samples from the last read, kept to reuse their allocation.
+    bytes: Vec<u8>,
+}
+
+/// The outcome of **PortAudio::record_to_wav**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Recording {
+    /// The number of frames that were written to the file.
+    pub frames: u64,
+    /// The number of frames that were lost to input overflows, and are missing from the file.
+    pub dropped_frames: u64,
+}
+
+impl From<Error> for WavError {
+    fn from(err: Error) -> Self {
+        WavError::PortAudio(err)
+    }
+}
+
+impl From<FormatSupportError> for WavError {
+    fn from(err: FormatSupportError) -> Self {
+        WavError::Format(err)
+    }
+}
+
+impl From<io::Error> for WavError {
+    fn from(err: io::Error) -> Self {
+        WavError::Io(err)
+    }
+}
+
+impl ::std::fmt::Display for WavError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        match *self {
+            WavError::PortAudio(ref err) => write!(f, "{}", err),
+            WavError::Format(ref err) => write!(f, "{}", err),
+            WavError::Io(ref err) => write!(f, "{}", err),
+            WavError::Unsupported(what) => write!(f, "Unsupported WAV file: {}", what),
+            WavError::Malformed(what) => write!(f, "Malformed WAV file: {}", what),
+        }
+    }
+}
+
+impl StdError for WavError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            WavError::PortAudio(ref err) => Some(err),
+            WavError::Format(ref err) => Some(&err.error),
+            WavError::Io(ref err) => Some(err),
+            WavError::Unsupported(_) | WavError::Malformed(_) => None,
+        }
+    }
+}
+
+impl WavSpec {
+    /// The number of bytes that each sample occupies in the file.
+    fn sample_bytes(&self) -> Result<u16, WavError> {
+        match self.sample_format {
+            SampleFormat::F32 | SampleFormat::I32 => Ok(4),
+            SampleFormat::I24 => Ok(3),
+            SampleFormat::I16 => Ok(2),
+            SampleFormat::I8 | SampleFormat::U8 => Ok(1),
+            _ => Err(WavError::Unsupported("sample format")),
+        }
+    }
+
+    /// The number of bytes that each frame occupies in the file.
+    fn frame_bytes(&self) -> Result<u32, WavError> {
+        Ok(self.sample_bytes()? as u32 * self.channels as u32)
+    }
+}
+
+impl WavWriter<BufWriter<File>> {
+    /// Create the WAV file at `path`, replacing any existing file.
+    pub fn create<P: AsRef<Path>>(path: P, spec: WavSpec) -> Result<Self, WavError> {
+        WavWriter::new(BufWriter::new(File::create(path)?), spec)
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Write the header of a WAV file with the given **WavSpec** to `writer`.
+    ///
+    /// Returns **WavError::Unsupported** if the spec has no channels or an unsupported sample
+    /// format.
+    pub fn new(mut writer: W, spec: WavSpec) -> Result<Self, WavError> {
+        let sample_bytes = spec.sample_bytes()?;
+        if spec.channels == 0 {
+            return Err(WavError::Unsupported("channel count"));
+        }
+        let block_align = spec
+            .channels
+            .checked_mul(sample_bytes)
+            .ok_or(WavError::Unsupported("channel count"))?;
+        let byte_rate = spec
+            .sample_rate
+            .checked_mul(block_align as u32)
+            .ok_or(WavError::Unsupported("sample rate"))?;
+        let format_tag = match spec.sample_format {
+            SampleFormat::F32 => FORMAT_IEEE_FLOAT,
+            _ => FORMAT_PCM,
+        };
+
+        // The RIFF and data chunk sizes are filled in by `finalize`.
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(HEADER_LEN - 8).to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&format_tag.to_le_bytes())?;
+        writer.write_all(&spec.channels.to_le_bytes())?;
+        writer.write_all(&spec.sample_rate.to_le_bytes())?;
+        writer.write_all(&byte_rate.to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&(sample_bytes * 8).to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(WavWriter {
+            writer: writer,
+            spec: spec,
+            data_len: 0,
+        })
+    }
+
+    /// The **WavSpec** that the file is written with.
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    /// Append interleaved samples to the file.
+    ///
+    /// The samples should hold whole frames, otherwise the channels of those that follow them are
+    /// shifted.
+    ///
+    /// Returns **WavError::Unsupported** if `S` doesn't match the spec's sample format, or if the
+    /// samples don't fit within the 4 GiB that the header can describe.
+    pub fn write_samples<S: Sample>(&mut self, samples: &[S]) -> Result<(), WavError> {
+        if S::sample_format() != self.spec.sample_format {
+            return Err(WavError::Unsupported("sample type differs from the spec"));
+        }
+        let sample_bytes = self.spec.sample_bytes()? as usize;
+        let len = samples
+            .len()
+            .checked_mul(sample_bytes)
+            .and_then(|len| len.to_u32())
+            .and_then(|len| len.checked_add(self.data_len))
+            .filter(|&len| len.checked_add(HEADER_LEN).is_some())
+            .ok_or(WavError::Unsupported("data exceeds 4 GiB"))?;
+
+        let mut bytes = Vec::with_capacity(samples.len() * sample_bytes);
+        for &sample in samples {
+            match self.spec.sample_format {
+                SampleFormat::F32 => bytes.extend(&sample.to_f32_sample().to_le_bytes()),
+                SampleFormat::I8 => {
+                    let value = sample.to_i8().unwrap_or(0);
+                    bytes.push((value as u8) ^ 0x80);
+                }
+                SampleFormat::U8 => bytes.push(sample.to_u8().unwrap_or(0x80)),
+                _ => {
+                    let value = sample.to_i32().unwrap_or(0).to_le_bytes();
+                    bytes.extend(&value[..sample_bytes]);
+                }
+            }
+        }
+        self.writer.write_all(&bytes)?;
+        self.data_len = len;
+        Ok(())
+    }
+
+    /// Fill in the sizes in the header and flush the file, returning the underlying writer.
+    pub fn finalize(mut self) -> Result<W, WavError> {
+        // Chunks are padded to an even length, which only odd 8 or 24-bit data requires.
+        let padding = self.data_len % 2;
+        if padding != 0 {
+            self.writer.write_all(&[0])?;
+        }
+        let riff_len = HEADER_LEN - 8 + self.data_len + padding;
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&riff_len.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(HEADER_LEN as u64 - 4))?;
+        self.writer.write_all(&self.data_len.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl WavReader<BufReader<File>> {
+    /// Open the WAV file at `path` and read its header.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WavError> {
+        WavReader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> WavReader<R> {
+    /// Read the header of a WAV file from `reader`, up to the start of its sample data.
+    ///
+    /// Returns **WavError::Malformed** if the header is invalid and **WavError::Unsupported** if
+    /// the file holds samples in a format other than those listed for **WavReader**.
+    pub fn new(mut reader: R) -> Result<Self, WavError> {
+        let mut riff = [0; 12];
+        reader.read_exact(&mut riff)?;
+        if &riff[..4] != b"RIFF" || &riff[8..] != b"WAVE" {
+            return Err(WavError::Malformed("not a RIFF WAVE file"));
+        }
+        let mut spec = None;
+        loop {
+            let mut chunk = [0; 8];
+            reader.read_exact(&mut chunk)?;
+            let len = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+            match &chunk[..4] {
+                b"fmt " => spec = Some(read_fmt_chunk(&mut reader, len)?),
+                b"data" => {
+                    let spec = spec.ok_or(WavError::Malformed("data precedes the fmt chunk"))?;
+                    return Ok(WavReader {
+                        reader: reader,
+                        spec: spec,
+                        len: len,
+                        remaining: len,
+                        bytes: Vec::new(),
+                    });
+                }
+                _ => {
+                    // Skip the chunk along with its padding byte, if any.
+                    let skip = len as u64 + (len % 2) as u64;
+                    let skipped = io::copy(&mut (&mut reader).take(skip), &mut io::sink())?;
+                    if skipped < skip {
+                        return Err(WavError::Malformed("truncated chunk"));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The layout of the samples in the file.
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    /// The number of whole frames in the file, according to its header.
+    pub fn frames(&self) -> u64 {
+        match self.spec.frame_bytes() {
+            Ok(frame_bytes) if frame_bytes > 0 => (self.len / frame_bytes) as u64,
+            _ => 0,
+        }
+    }
+
+    /// The index of the frame that is read next, counting from the start of the file.
+    ///
+    /// If the last read ended part way through a frame, that frame is counted as read.
+    pub fn position(&self) -> u64 {
+        match self.spec.frame_bytes() {
+            Ok(frame_bytes) if frame_bytes > 0 => {
+                let read = self.len - self.remaining;
+                read.div_ceil(frame_bytes) as u64
+            }
+            _ => 0,
+        }
+    }
+
+    /// Read the next interleaved samples into `samples`, returning the number that were read.
+    ///
+    /// Fewer samples than fit are only read once the end of the file is reached, after which
+    /// the result is `0`.
+    ///
+    /// Returns **WavError::Unsupported** if `S` doesn't match the spec's sample format.
+    pub fn read_samples<S: Sample>(&mut self, samples: &mut [S]) -> Result<usize, WavError> {
+        if S::sample_format() != self.spec.sample_format {
+            return Err(WavError::Unsupported("sample type differs from the spec"));
+        }
+        let sample_bytes = self.spec.sample_bytes()? as usize;
+        let wanted = (samples.len() * sample_bytes).min(self.remaining as usize);
+        self.bytes.resize(wanted, 0);
+        let mut len = 0;
+        while len < wanted {
+            match self.reader.read(&mut self.bytes[len..]) {
+                // Files whose data chunk is cut short still play up to where they end.
+                Ok(0) => break,
+                Ok(read) => len += read,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err.into()),
+            }
+        }
+        self.remaining -= len as u32;
+
+        let read = len / sample_bytes;
+        let chunks = self.bytes[..read * sample_bytes].chunks(sample_bytes);
+        for (sample, bytes) in samples.iter_mut().zip(chunks) {
+            let value = match self.spec.sample_format {
+                SampleFormat::F32 => Some(Sample::from_f32_sample(f32::from_le_bytes([
+                    bytes[0], bytes[1], bytes[2], bytes[3],
+                ]))),
+                SampleFormat::I32 => {
+                    S::from_i32(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                }
+                // Shift the 24 bits to the top of an `i32` and back to extend the sign.
+                SampleFormat::I24 => {
+                    S::from_i32(i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8)
+                }
+                SampleFormat::I16 => S::from_i16(i16::from_le_bytes([bytes[0], bytes[1]])),
+                _ => S::from_u8(bytes[0]),
+            };
+            *sample = value.unwrap_or_else(|| Sample::from_f32_sample(0.0));
+        }
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> WavReader<R> {
+    /// Move to the given frame, so that it is read next. Seeking beyond the last frame moves to
+    /// the end of the file, after which there is nothing left to read.
+    pub fn seek(&mut self, frame: u64) -> Result<(), WavError> {
+        let frame_bytes = self.spec.frame_bytes()? as u64;
+        let target = frame.min(self.frames()) * frame_bytes;
+        let current = (self.len - self.remaining) as u64;
+        self.reader
+            .seek(SeekFrom::Current(target as i64 - current as i64))?;
+        self.remaining = self.len - target as u32;
+        Ok(())
+    }
+}
+
+/// Parse a `fmt ` chunk whose header has been read.
+fn read_fmt_chunk<R: Read>(reader: &mut R, len: u32) -> Result<WavSpec, WavError> {
+    if len < 16 {
+        return Err(WavError::Malformed("fmt chunk is too short"));
+    }
+    let mut fmt = vec![0; len as usize + (len % 2) as usize];
+    reader.read_exact(&mut fmt)?;
+    let u16_at = |i: usize| u16::from_le_bytes([fmt[i], fmt[i + 1]]);
+    let mut format_tag = u16_at(0);
+    if format_tag == FORMAT_EXTENSIBLE && len >= 26 {
+        // The sub-format GUID starts with the format tag that applies to the samples.
+        format_tag = u16_at(24);
+    }
+    let channels = u16_at(2);
+    let sample_rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
+    let bits_per_sample = u16_at(14);
+    let sample_format = match (format_tag, bits_per_sample) {
+        (FORMAT_IEEE_FLOAT, 32) => SampleFormat::F32,
+        (FORMAT_PCM, 32) => SampleFormat::I32,
+        (FORMAT_PCM, 24) => SampleFormat::I24,
+        (FORMAT_PCM, 16) => SampleFormat::I16,
+        (FORMAT_PCM, 8) => SampleFormat::U8,
+        _ => return Err(WavError::Unsupported("sample format")),
+    };
+    if channels == 0 {
+        return Err(WavError::Malformed("no channels"));
+    }
+    Ok(WavSpec {
+        channels: channels,
+        sample_rate: sample_rate,
+        sample_format: sample_format,
+    })
+}
+
+/// The implementation of **PortAudio::record_to_wav**.
+pub(crate) fn record_to_wav<S: Sample + 'static>(
+    port_audio: &PortAudio,
+    device: DeviceIndex,
+    duration: Duration,
+    path: &Path,
+) -> Result<Recording, WavError> {
+    const INTERLEAVED: bool = true;
+    let info = port_audio.device_info(device)?;
+    let channels = info.max_input_channels.min(RECORD_MAX_CHANNELS);
+    if channels <= 0 {
+        return Err(WavError::PortAudio(Error::InvalidChannelCount));
+    }
+    let sample_rate = info.default_sample_rate;
+    let spec = WavSpec {
+        channels: channels as u16,
+        sample_rate: sample_rate.round() as u32,
+        sample_format: S::sample_format(),
+    };
+    let mut writer = WavWriter::create(path, spec)?;
+
+    let params = Parameters::<S>::new(
+        device,
+        channels,
+        INTERLEAVED,
+        info.default_high_input_latency,
+    );
+    let settings = InputSettings::new(params, sample_rate, FRAMES_PER_BUFFER);
+    let mut stream = port_audio.open_blocking_stream(settings)?;
+
+    let total_frames = (duration.as_secs_f64() * sample_rate).round() as u64;
+    let mut recording = Recording {
+        frames: 0,
+        dropped_frames: 0,
+    };
+    stream.start()?;
+    while recording.frames + recording.dropped_frames < total_frames {
+        let remaining = total_frames - recording.frames - recording.dropped_frames;
+        let frames = remaining.min(FRAMES_PER_BUFFER as u64) as u32;
+        match stream.read(frames) {
+            Ok(samples) => {
+                writer.write_samples(samples)?;
+                recording.frames += frames as u64;
+            }
+            // The block is discarded, but counted towards the duration so that the recording
+            // still ends on time.
+            Err(Error::InputOverflowed) => recording.dropped_frames += frames as u64,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    stream.stop()?;
+    stream.close()?;
+    writer.finalize()?;
+    Ok(recording)
+}
+
+/// Call `$play::<S>($($arg),*)` with the **Sample** type `S` that matches the given sample format.
+macro_rules! with_sample_type {
+    ($format:expr, $play:ident($($arg:expr),*)) => {
+        match $format {
+            SampleFormat::F32 => $play::<f32>($($arg),*),
+            SampleFormat::I32 => $play::<i32>($($arg),*),
+            SampleFormat::I24 => $play::<::types::I24>($($arg),*),
+            SampleFormat::I16 => $play::<i16>($($arg),*),
+            SampleFormat::U8 => $play::<u8>($($arg),*),
+            _ => Err(WavError::Unsupported("sample format")),
+        }
+    };
+}
+
+/// The implementation of **PortAudio::play_wav**.
+pub(crate) fn play_wav(
+    port_audio: &PortAudio,
+    path: &Path,
+    device: DeviceIndex,
+) -> Result<(), WavError> {
+    start_wav_playback(port_audio, path, device)?.wait()
+}
+
+/// The implementation of **PortAudio::start_wav_playback**.
+pub(crate) fn start_wav_playback(
+    port_audio: &PortAudio,
+    path: &Path,
+    device: DeviceIndex,
+) -> Result<PlaybackHandle, WavError> {
+    let reader = WavReader::open(path)?;
+    with_sample_type!(
+        reader.spec().sample_format,
+        play_non_blocking(port_audio, reader, device)
+    )
+}
+
+/// The implementation of **PortAudio::play_wav_blocking**.
+pub(crate) fn play_wav_blocking(
+    port_audio: &PortAudio,
+    path: &Path,
+    device: DeviceIndex,
+) -> Result<(), WavError> {
+    let reader = WavReader::open(path)?;
+    with_sample_type!(
+        reader.spec().sample_format,
+        play_blocking(port_audio, reader, device)
+    )
+}
+
+/// The settings for playing a file with the given spec on `device`, provided that the device
+/// supports its format.
+fn playback_settings<S: Sample>(
+    port_audio: &PortAudio,
+    spec: WavSpec,
+    device: DeviceIndex,
+) -> Result<OutputSettings<S>, WavError> {
+    const INTERLEAVED: bool = true;
+    let latency = port_audio.device_info(device)?.default_high_output_latency;
+    let params = Parameters::new(device, spec.channels as i32, INTERLEAVED, latency);
+    let sample_rate = spec.sample_rate as f64;
+    port_audio.check_output_format(params, sample_rate)?;
+    Ok(OutputSettings::new(params, sample_rate, FRAMES_PER_BUFFER))
+}
+
+fn play_non_blocking<S: Sample + Send + 'static>(
+    port_audio: &PortAudio,
+    mut reader: WavReader<BufReader<File>>,
+    device: DeviceIndex,
+) -> Result<PlaybackHandle, WavError> {
+    let settings = playback_settings::<S>(port_audio, reader.spec(), device)?;
+    let shared = Arc::new(Playback {
+        frames: reader.frames(),
+        position: AtomicU64::new(0),
+        seek: AtomicU64::new(NO_SEEK),
+        error: Mutex::new(None),
+    });
+    let playback = shared.clone();
+    let callback = move |OutputCallbackArgs { buffer, .. }: OutputCallbackArgs<S>| {
+        let seek = playback.seek.swap(NO_SEEK, Ordering::SeqCst);
+        let seeked = match seek {
+            NO_SEEK => Ok(()),
+            frame => reader.seek(frame),
+        };
+        let (read, result) = match seeked.and_then(|()| reader.read_samples(buffer)) {
+            Ok(read) if read == buffer.len() => (read, ffi::PA_CONTINUE),
+            Ok(read) => (read, ffi::PA_COMPLETE),
+            Err(err) => {
+                *playback.error.lock().unwrap() = Some(err);
+                (0, ffi::PA_ABORT)
+            }
+        };
+        playback.position.store(reader.position(), Ordering::SeqCst);
+        let silence = <S as Sample>::from_f32_sample(0.0);
+        for sample in &mut buffer[read..] {
+            *sample = silence;
+        }
+        result
+    };
+    let mut stream = port_audio.open_non_blocking_stream(settings, callback)?;
+    stream.start()?;
+    Ok(PlaybackHandle {
+        stream: Box::new(stream),
+        playback: shared,
+    })
+}
+
+/// The value of **Playback::seek** while no seek is pending.
+const NO_SEEK: u64 = u64::MAX;
+
+/// The state that a **PlaybackHandle** shares with its stream callback.
+struct Playback {
+    /// The number of frames in the file.
+    frames: u64,
+    /// The position of the reader after the last callback.
+    position: AtomicU64,
+    /// The frame to seek to at the start of the next callback, or **NO_SEEK**.
+    seek: AtomicU64,
+    /// The error that the callback aborted the stream with, if any.
+    error: Mutex<Option<WavError>>,
+}
+
+/// The playback stream of a **PlaybackHandle**, regardless of the sample type of its file.
+trait PlaybackStream: Send {
+    fn is_active(&self) -> Result<bool, Error>;
+    fn stop(&mut self) -> Result<(), Error>;
+    fn abort(&mut self) -> Result<(), Error>;
+    fn close(self: Box<Self>) -> Result<(), Error>;
+}
+
+impl<S: Sample + Send + 'static> PlaybackStream for Stream<NonBlocking, Output<S>> {
+    fn is_active(&self) -> Result<bool, Error> {
+        Stream::is_active(self)
+    }
+    fn stop(&mut self) -> Result<(), Error> {
+        Stream::stop(self)
+    }
+    fn abort(&mut self) -> Result<(), Error> {
+        Stream::abort(self)
+    }
+    fn close(self: Box<Self>) -> Result<(), Error> {
+        Stream::close(*self)
+    }
+}
+
+/// A WAV file that is playing in the background, as started by **PortAudio::start_wav_playback**.
+///
+/// The handle tracks the playback position and allows seeking, e.g. for the progress bar of a
+/// media player. Dropping the handle stops playback, waiting for the buffers that have already
+/// been read from the file to be played.
+pub struct PlaybackHandle {
+    stream: Box<dyn PlaybackStream>,
+    playback: Arc<Playback>,
+}
+
+impl PlaybackHandle {
+    /// The number of frames in the file.
+    pub fn frames(&self) -> u64 {
+        self.playback.frames
+    }
+
+    /// The index of the next frame to be read from the file, which advances by a buffer of the
+    /// stream on each callback.
+    ///
+    /// This is the position of the samples that have been handed to PortAudio, so the audible
+    /// position lags behind it by the output latency of the stream. Without a **seek**, the
+    /// position only ever advances, until it reaches **PlaybackHandle::frames**.
+    pub fn position(&self) -> u64 {
+        self.playback.position.load(Ordering::SeqCst)
+    }
+
+    /// Continue playback from the given frame, or finish it if the frame is beyond the end of the
+    /// file.
+    ///
+    /// The seek only takes effect at the start of the next callback, so that the buffer that is
+    /// being filled isn't cut off part way. It is therefore only accurate to the frame that the
+    /// next buffer starts with, and the buffers that were queued before are still played. As the
+    /// file is read from a new position, that callback may take longer than usual, which can
+    /// briefly cause an output underflow. If **seek** is called again before then, only the last
+    /// frame is sought.
+    pub fn seek(&self, frame: u64) {
+        // Frames beyond the end finish playback all the same, without being taken for NO_SEEK.
+        self.playback
+            .seek
+            .store(frame.min(NO_SEEK - 1), Ordering::SeqCst);
+    }
+
+    /// Whether the file is still playing, i.e. it hasn't been played to the end yet and no error
+    /// has occurred.
+    pub fn is_playing(&self) -> Result<bool, WavError> {
+        Ok(self.stream.is_active()?)
+    }
+
+    /// Wait for the file to finish playing and close the stream.
+    ///
+    /// Returns the error that occurred while reading the file, if any.
+    pub fn wait(mut self) -> Result<(), WavError> {
+        while self.stream.is_active()? {
+            thread::sleep(PLAYBACK_POLL_INTERVAL);
+        }
+        self.stream.stop()?;
+        self.finish()
+    }
+
+    /// Stop playback immediately, discarding the buffers that have yet to be played, and close
+    /// the stream.
+    pub fn stop(mut self) -> Result<(), WavError> {
+        self.stream.abort()?;
+        self.finish()
+    }
+
+    fn finish(self) -> Result<(), WavError> {
+        self.stream.close()?;
+        let error = self.playback.error.lock().unwrap().take();
+        match error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+fn play_blocking<S: Sample + 'static>(
+    port_audio: &PortAudio,
+    mut reader: WavReader<BufReader<File>>,
+    device: DeviceIndex,
+) -> Result<(), WavError> {
+    let spec = reader.spec();
+    let settings = playback_settings::<S>(port_audio, spec, device)?;
+    let mut stream = port_audio.open_blocking_stream(settings)?;
+    let channels = spec.channels as usize;
+    let mut samples =
+        vec![<S as Sample>::from_f32_sample(0.0); FRAMES_PER_BUFFER as usize * channels];
+    stream.start()?;
+    loop {
+        let read = reader.read_samples(&mut samples)?;
+        // A file that ends part way through a frame is cut at the last whole frame.
+        let read = read - read % channels;
+        if read == 0 {
+            break;
+        }
+        match stream.write_from(&samples[..read]) {
+            Ok(_) | Err(Error::OutputUnderflowed) => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+    // Stopping waits for the written samples to be played.
+    stream.stop()?;
+    stream.close()?;
+    Ok(())
+}
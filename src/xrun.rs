@@ -0,0 +1,97 @@
+//!
+//! A counter of the xruns reported to a stream callback, for monitoring from another thread.
+//!
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use stream::CallbackFlags;
+
+/// Counts the input and output underflows and overflows reported by the **CallbackFlags** that a
+/// stream callback passes to **XrunCounter::record**.
+///
+/// Clones share the same counts, so one clone can be moved into the callback while another reads
+/// the counts, e.g. for a latency tuning dashboard. Recording only increments atomics, so it
+/// neither allocates nor locks.
+#[derive(Clone, Debug, Default)]
+pub struct XrunCounter {
+    counts: Arc<Counts>,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    input_underflows: AtomicU64,
+    input_overflows: AtomicU64,
+    output_underflows: AtomicU64,
+    output_overflows: AtomicU64,
+}
+
+/// The counts of an **XrunCounter** at one point in time, as produced by
+/// **XrunCounter::snapshot**.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct XrunStats {
+    /// The number of callbacks whose input was padded with silence.
+    pub input_underflows: u64,
+    /// The number of callbacks preceded by discarded input.
+    pub input_overflows: u64,
+    /// The number of callbacks preceded by a gap in the output.
+    pub output_underflows: u64,
+    /// The number of callbacks whose output was discarded.
+    pub output_overflows: u64,
+}
+
+impl XrunCounter {
+    /// Construct a counter with all counts at zero.
+    pub fn new() -> Self {
+        XrunCounter::default()
+    }
+
+    /// Count the xruns that the given flags report, if any.
+    pub fn record(&self, flags: CallbackFlags) {
+        let counts = &*self.counts;
+        let pairs = [
+            (flags.input_underflow(), &counts.input_underflows),
+            (flags.input_overflow(), &counts.input_overflows),
+            (flags.output_underflow(), &counts.output_underflows),
+            (flags.output_overflow(), &counts.output_overflows),
+        ];
+        for &(is_set, count) in &pairs {
+            if is_set {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// The current counts.
+    ///
+    /// Each count is read separately, so counts that the callback records meanwhile may appear
+    /// in some fields of the snapshot but not others.
+    pub fn snapshot(&self) -> XrunStats {
+        let counts = &*self.counts;
+        XrunStats {
+            input_underflows: counts.input_underflows.load(Ordering::Relaxed),
+            input_overflows: counts.input_overflows.load(Ordering::Relaxed),
+            output_underflows: counts.output_underflows.load(Ordering::Relaxed),
+            output_overflows: counts.output_overflows.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Set all counts back to zero.
+    pub fn reset(&self) {
+        let counts = &*self.counts;
+        counts.input_underflows.store(0, Ordering::Relaxed);
+        counts.input_overflows.store(0, Ordering::Relaxed);
+        counts.output_underflows.store(0, Ordering::Relaxed);
+        counts.output_overflows.store(0, Ordering::Relaxed);
+    }
+}
+
+impl XrunStats {
+    /// The total of all counts.
+    pub fn total(&self) -> u64 {
+        self.input_underflows
+            + self.input_overflows
+            + self.output_underflows
+            + self.output_overflows
+    }
+}
@@ -0,0 +1,293 @@
+//! Select devices by name, as a `--device "Scarlett"` command line option would, among devices
+//! with crafted names listed by the `mock-backend` feature:
+//!
+//! ```text
+//! cargo test --features mock-backend
+//! ```
+
+#![cfg(feature = "mock-backend")]
+
+extern crate portaudio;
+
+use pa::Direction::{Input, Output};
+use portaudio as pa;
+use std::sync::MutexGuard;
+use std::time::Duration;
+
+/// The devices listed after the virtual loopback device.
+struct Devices {
+    scarlett: pa::DeviceIndex,
+    scarlett_solo: pa::DeviceIndex,
+    speakers: pa::DeviceIndex,
+    usb_mic: pa::DeviceIndex,
+    usb_mic_pro: pa::DeviceIndex,
+}
+
+/// Take the virtual device for a test and list the crafted devices after it.
+///
+/// The guard is returned first so that it outlives the **PortAudio**.
+fn setup() -> (MutexGuard<'static, ()>, pa::PortAudio, Devices) {
+    let lock = pa::mock::lock();
+    pa::mock::configure(pa::mock::MockConfig::default());
+    let add = |name, input_channels, output_channels| {
+        pa::DeviceIndex(pa::mock::add_device(name, input_channels, output_channels) as u32)
+    };
+    let devices = Devices {
+        scarlett: add("Focusrite Scarlett 2i2", 2, 2),
+        scarlett_solo: add("Scarlett Solo", 2, 0),
+        speakers: add("Speakers", 0, 2),
+        usb_mic: add("USB Mic", 1, 0),
+        usb_mic_pro: add("USB Mic Pro", 1, 0),
+    };
+    let pa = pa::PortAudio::new().unwrap();
+    (lock, pa, devices)
+}
+
+#[test]
+fn find_by_name() -> Result<(), pa::Error> {
+    let (_lock, pa, devices) = setup();
+
+    // Names are matched regardless of case, and only against devices with channels in the
+    // requested direction.
+    assert_eq!(pa.find_device("scarlett", Output), Ok(devices.scarlett));
+    assert_eq!(pa.find_device("SPEAK", Output), Ok(devices.speakers));
+    assert_eq!(
+        pa.find_device("speakers", Input),
+        Err(pa::Error::DeviceNotFound)
+    );
+    assert_eq!(
+        pa.find_device("Headphones", Output),
+        Err(pa::Error::DeviceNotFound)
+    );
+
+    // Ambiguous names are reported along with the candidates, unless one of them is a full match.
+    assert_eq!(
+        pa.find_device("scarlett", Input),
+        Err(pa::Error::AmbiguousDevice { matches: 2 })
+    );
+    assert_eq!(
+        pa.find_devices("scarlett", Input)?,
+        vec![devices.scarlett, devices.scarlett_solo]
+    );
+    assert_eq!(pa.find_device("usb mic", Input), Ok(devices.usb_mic));
+    assert_eq!(pa.find_device("usb mic p", Input), Ok(devices.usb_mic_pro));
+    Ok(())
+}
+
+#[test]
+fn out_of_range_indices() {
+    let (_lock, pa, devices) = setup();
+
+    // Indices beyond the last device or host API produce `None` rather than an error.
+    assert_eq!(
+        pa.device(devices.usb_mic_pro).map(|info| info.name),
+        Some("USB Mic Pro")
+    );
+    assert!(pa
+        .device(pa::DeviceIndex(devices.usb_mic_pro.0 + 1))
+        .is_none());
+    assert!(pa.device(pa::DeviceIndex(u32::MAX)).is_none());
+    assert_eq!(pa.host_api(0).map(|info| info.device_count), Some(6));
+    assert!(pa.host_api(1).is_none());
+    assert!(pa.host_api(-1).is_none());
+}
+
+#[test]
+fn host_apis_by_type() {
+    let (_lock, pa, _) = setup();
+
+    // Each host API is identified by its type rather than by its name.
+    let host_apis: Vec<_> = pa.host_apis().collect();
+    assert_eq!(host_apis.len(), 1);
+    for (idx, info) in host_apis {
+        assert_eq!(info.host_type, pa::HostApiTypeId::InDevelopment);
+        assert_eq!(info.backend_name(), "In Development");
+        assert_eq!(
+            pa.host_api_type_id_to_host_api_index(info.host_type),
+            Ok(idx)
+        );
+    }
+}
+
+#[test]
+fn device_host_api() -> Result<(), pa::Error> {
+    let (_lock, pa, devices) = setup();
+
+    // Each device is listed by the host API that it belongs to.
+    for device in pa.devices()? {
+        let (idx, _) = device?;
+        let (host_api, info) = pa.device_host_api(idx)?;
+        let listed = (0..info.device_count as i32)
+            .map(|i| pa.api_device_index_to_device_index(host_api, i))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert!(listed.contains(&idx));
+    }
+    assert_eq!(
+        pa.device_host_api(pa::DeviceIndex(devices.usb_mic_pro.0 + 1))
+            .err(),
+        Some(pa::Error::InvalidDevice)
+    );
+    Ok(())
+}
+
+#[test]
+fn default_devices_by_direction() -> Result<(), pa::Error> {
+    let (_lock, pa, _) = setup();
+
+    // The direction-parameterised methods agree with their named counterparts.
+    assert_eq!(pa.default_device(Input), pa.default_input_device());
+    assert_eq!(pa.default_device(Output), pa.default_output_device());
+    let host_api = pa.default_host_api()?;
+    assert_eq!(
+        pa.default_device_for_host_api(host_api, Input)?.0,
+        pa.default_input_device_for_host_api(host_api)?.0
+    );
+    let params = pa.default_stream_params::<f32>(Output, 2)?;
+    assert_eq!(Ok(params), pa.default_output_stream_params::<f32>(2));
+    let sample_rate = pa::mock::config().sample_rate;
+    assert_eq!(
+        pa.is_format_supported(Output, params, sample_rate),
+        pa.is_output_format_supported(params, sample_rate)
+    );
+    assert_eq!(
+        pa.check_format(Input, params, sample_rate),
+        pa.check_input_format(params, sample_rate)
+    );
+    Ok(())
+}
+
+#[test]
+fn channel_names() {
+    let (_lock, pa, devices) = setup();
+
+    // Only CoreAudio and ASIO name their channels, so the virtual host API's devices have none.
+    assert_eq!(pa.channel_names(devices.scarlett, Input), None);
+    assert_eq!(pa.channel_names(devices.speakers, Output), None);
+    assert_eq!(
+        pa.channel_names(pa::DeviceIndex(devices.usb_mic_pro.0 + 1), Input),
+        None
+    );
+}
+
+#[test]
+fn default_device_info() -> Result<(), pa::Error> {
+    let (_lock, pa, _) = setup();
+
+    // Every field of the default device is reported, with the latencies also as durations.
+    let default_info = pa.device_info(pa.default_output_device()?)?;
+    let config = pa::mock::config();
+    assert_eq!(default_info.max_input_channels, config.input_channels);
+    assert_eq!(default_info.max_output_channels, config.output_channels);
+    assert_eq!(default_info.default_sample_rate, config.sample_rate);
+    assert_eq!(default_info.low_input_latency(), Duration::from_millis(10));
+    assert_eq!(default_info.low_output_latency(), Duration::from_millis(10));
+    assert_eq!(
+        default_info.high_input_latency(),
+        Duration::from_millis(100)
+    );
+    assert_eq!(
+        default_info.high_output_latency(),
+        Duration::from_millis(100)
+    );
+    Ok(())
+}
+
+#[test]
+fn host_api_filter() -> Result<(), pa::Error> {
+    let (_lock, pa, _) = setup();
+
+    // Limiting PortAudio to other host APIs hides the mock's only host API and its devices.
+    let mock_api = pa::HostApiTypeId::InDevelopment;
+    let alsa_only = pa::PortAudio::initialize_with_host_apis(&[pa::HostApiTypeId::ALSA])?;
+    assert_eq!(alsa_only.host_apis().count(), 0);
+    assert_eq!(alsa_only.devices()?.count(), 0);
+    assert_eq!(
+        alsa_only.find_device("scarlett", Output),
+        Err(pa::Error::DeviceNotFound)
+    );
+    assert_eq!(
+        alsa_only.host_api_type_id_to_index(mock_api),
+        Err(pa::Error::HostApiExcluded)
+    );
+    assert_eq!(
+        alsa_only.default_host_api(),
+        Err(pa::Error::HostApiNotFound)
+    );
+    assert!(alsa_only.host_api_devices(0).is_err());
+    let loopback = pa.default_output_device()?;
+    let params = pa::StreamParameters::<f32>::new(loopback, 2, true, 0.0);
+    let settings = pa::OutputStreamSettings::new(params, 44_100.0, 256);
+    assert_eq!(
+        alsa_only.open_blocking_stream(settings).err(),
+        Some(pa::Error::HostApiExcluded)
+    );
+    alsa_only.terminate()?;
+
+    // Allowing it again leaves everything as it was.
+    let mock_only = pa::PortAudio::builder()
+        .allow_host_apis(&[pa::HostApiTypeId::ALSA, mock_api])
+        .build()?;
+    assert_eq!(mock_only.host_apis().count(), 1);
+    assert_eq!(mock_only.devices()?.count(), pa.devices()?.count());
+    assert_eq!(
+        mock_only.default_output_device(),
+        pa.default_output_device()
+    );
+    mock_only.open_blocking_stream(settings)?.close()?;
+    mock_only.terminate()
+}
+
+// Options that PortAudio needs before initialising are checked by the builder up front.
+#[cfg(all(target_os = "linux", feature = "jack"))]
+#[test]
+fn jack_client_name() -> Result<(), pa::Error> {
+    let (_lock, _, _) = setup();
+
+    assert_eq!(
+        pa::PortAudio::builder()
+            .jack_client_name("bad\0name")
+            .build()
+            .err(),
+        Some(pa::Error::InvalidFlag)
+    );
+    let named = pa::PortAudio::builder()
+        .jack_client_name("mock_find_device")
+        .allow_host_apis(&[pa::HostApiTypeId::InDevelopment])
+        .build()?;
+    assert_eq!(named.host_apis().count(), 1);
+    named.terminate()
+}
+
+#[test]
+fn thread_check() -> Result<(), pa::Error> {
+    let (_lock, pa, _) = setup();
+
+    // A thread checked PortAudio works as usual on its own thread. Elsewhere it only warns,
+    // unless the `strict-threading` feature turns that into an error.
+    let checked = pa::PortAudio::new_with_thread_check()?;
+    assert_eq!(checked.device_count()?, pa.device_count()?);
+    let elsewhere = checked.clone();
+    let count = std::thread::spawn(move || elsewhere.device_count())
+        .join()
+        .unwrap();
+    if cfg!(feature = "strict-threading") {
+        assert_eq!(count, Err(pa::Error::WrongThread));
+    } else {
+        assert_eq!(count, pa.device_count());
+    }
+    checked.terminate()
+}
+
+#[test]
+fn rescan_devices() -> Result<(), pa::Error> {
+    let (_lock, pa, _) = setup();
+
+    // Devices are only rescanned once no clone or stream shares the PortAudio.
+    let mut rescanned = pa::PortAudio::new()?;
+    let clone = rescanned.clone();
+    assert_eq!(rescanned.rescan_devices(), Err(pa::Error::PortAudioInUse));
+    drop(clone);
+    rescanned.rescan_devices()?;
+    assert_eq!(rescanned.device_count()?, pa.device_count()?);
+    rescanned.terminate()
+}
@@ -0,0 +1,827 @@
+//! Round-trip samples through the virtual loopback device of the `mock-backend` feature, which
+//! needs no audio hardware:
+//!
+//! ```text
+//! cargo test --features mock-backend
+//! ```
+
+#![cfg(feature = "mock-backend")]
+
+extern crate portaudio;
+
+use portaudio as pa;
+use std::panic;
+use std::sync::{mpsc, MutexGuard};
+use std::time::Duration;
+
+const SAMPLE_RATE: f64 = 48_000.0;
+const CHANNELS: i32 = 2;
+const FRAMES: u32 = 64;
+const DELAY: u32 = 16;
+const BLOCKS: usize = 4;
+const INTERLEAVED: bool = true;
+
+/// Take the virtual device for a test and configure it afresh, which also empties the loopback of
+/// what earlier tests left in it.
+///
+/// The guard is returned first so that it outlives the **PortAudio**.
+fn setup() -> (MutexGuard<'static, ()>, pa::PortAudio, pa::DeviceIndex) {
+    let lock = pa::mock::lock();
+    pa::mock::configure(pa::mock::MockConfig {
+        input_channels: CHANNELS,
+        output_channels: CHANNELS,
+        sample_rate: SAMPLE_RATE,
+        loopback_delay: DELAY,
+    });
+    let pa = pa::PortAudio::new().unwrap();
+    let device = pa.default_output_device().unwrap();
+    (lock, pa, device)
+}
+
+#[test]
+fn blocking_round_trip() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    let input = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let output = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::DuplexStreamSettings::new(input, output, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+
+    let block_len = (FRAMES * CHANNELS as u32) as usize;
+    let written: Vec<i16> = (0..(block_len * BLOCKS) as i16).collect();
+    let mut read = Vec::new();
+    for block in written.chunks(block_len) {
+        stream.write(FRAMES, |out| out.copy_from_slice(block))?;
+        read.extend_from_slice(stream.read(FRAMES)?);
+    }
+
+    // The input lags the output by the configured delay, which is filled with silence.
+    let delay = (DELAY * CHANNELS as u32) as usize;
+    assert!(read[..delay].iter().all(|&s| s == 0));
+    assert_eq!(&read[delay..], &written[..written.len() - delay]);
+
+    // Buffers that don't match the stream are reported rather than overrun or truncated.
+    assert_eq!(pa::get_sample_size(pa::SampleFormat::I24), Ok(3));
+    assert_eq!(
+        stream.read(FRAMES + 1).err(),
+        Some(pa::Error::InvalidBuffer {
+            expected: (FRAMES as usize + 1) * CHANNELS as usize * 2,
+            actual: block_len * 2,
+        })
+    );
+    assert_eq!(
+        stream.write_from(&[0; 3]),
+        Err(pa::Error::InvalidBuffer {
+            expected: 4,
+            actual: 6,
+        })
+    );
+    // So are frame counts whose size in bytes doesn't even fit within a `usize`.
+    assert!(matches!(
+        stream.read(u32::MAX).err(),
+        Some(pa::Error::InvalidBuffer { .. }) | Some(pa::Error::BufferTooLarge)
+    ));
+    // Separate channel buffers are only for non-interleaved streams.
+    assert_eq!(
+        stream.write_planar(&[&[0; 4], &[0; 4]]),
+        Err(pa::Error::BadBufferPtr)
+    );
+
+    stream.stop()?;
+    stream.close()
+}
+
+#[test]
+fn repeated_takes() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    const TAKES: i16 = 3;
+    let input = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let output = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::DuplexStreamSettings::new(input, output, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    // Streams are opened stopped, and only start when asked to.
+    assert_eq!(stream.state()?, pa::StreamState::Stopped);
+
+    let block_len = (FRAMES * CHANNELS as u32) as usize;
+    let mut written = Vec::new();
+    let mut read = Vec::new();
+    for take in 0..TAKES {
+        match take {
+            0 => stream.start()?,
+            1 => {
+                stream.stop()?;
+                stream.start()?;
+            }
+            _ => stream.restart()?,
+        }
+        assert_eq!(stream.state()?, pa::StreamState::Active);
+        let samples: Vec<i16> = (0..block_len as i16).map(|i| take * 1000 + i).collect();
+        stream.write_from(&samples)?;
+        read.extend_from_slice(stream.read(FRAMES)?);
+        written.extend(samples);
+    }
+    stream.stop()?;
+
+    // Each take carried on from where the loopback left off, the first one after its delay.
+    let delay = (DELAY * CHANNELS as u32) as usize;
+    assert!(read[..delay].iter().all(|&s| s == 0));
+    assert_eq!(&read[delay..], &written[..written.len() - delay]);
+    stream.close()
+}
+
+#[test]
+fn abort() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    let input = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let output = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::DuplexStreamSettings::new(input, output, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+
+    // Aborting a take with output still pending leaves the stream stopped, as stopping does.
+    let block_len = (FRAMES * CHANNELS as u32) as usize;
+    stream.start()?;
+    stream.write_from(&vec![1; block_len])?;
+    stream.abort()?;
+    assert_eq!(stream.is_active(), Ok(false));
+    assert_eq!(stream.is_stopped(), Ok(true));
+    assert_eq!(stream.state()?, pa::StreamState::Stopped);
+
+    // A stream that isn't running can't be aborted, but can be started again.
+    assert_eq!(stream.abort(), Err(pa::Error::StreamIsStopped));
+    stream.start()?;
+    assert_eq!(stream.state()?, pa::StreamState::Active);
+    stream.abort()?;
+    stream.close()
+}
+
+#[test]
+fn non_stalling_copy() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    let input = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let output = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::DuplexStreamSettings::new(input, output, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+
+    let channels = CHANNELS as usize;
+    let total_frames = FRAMES as usize * BLOCKS;
+    let written: Vec<i16> = (0..(total_frames * channels) as i16).collect();
+    let (mut written_frames, mut read) = (0, Vec::new());
+    // Each iteration stands in for a turn of an event loop, which must never block on the stream.
+    for _ in 0..total_frames {
+        if read.len() == written.len() {
+            break;
+        }
+        let writable = available_frames(stream.write_available()?);
+        let frames = writable
+            .min(total_frames - written_frames)
+            .min(FRAMES as usize);
+        if frames > 0 {
+            let start = written_frames * channels;
+            stream.write_from(&written[start..start + frames * channels])?;
+            written_frames += frames;
+        }
+
+        // Everything written so far is readable after the delay, and nothing more.
+        let read_frames = read.len() / channels;
+        let readable = available_frames(stream.read_available()?);
+        assert!(readable <= written_frames + DELAY as usize - read_frames);
+        let frames = readable
+            .min(total_frames - read_frames)
+            .min(FRAMES as usize);
+        if frames > 0 {
+            read.extend_from_slice(stream.read(frames as u32)?);
+        }
+    }
+    stream.stop()?;
+    assert_eq!(read.len(), written.len(), "the copy loop made no progress");
+
+    let delay = (DELAY * CHANNELS as u32) as usize;
+    assert!(read[..delay].iter().all(|&s| s == 0));
+    assert_eq!(&read[delay..], &written[..written.len() - delay]);
+    stream.close()
+}
+
+#[test]
+fn available_counts() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    let input = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let output = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::DuplexStreamSettings::new(input, output, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+
+    // At first only the loopback's delay can be read, while there is room to write more.
+    assert_eq!(available_frames(stream.read_available()?), DELAY as usize);
+    assert!(available_frames(stream.write_available()?) >= FRAMES as usize);
+
+    // What has been written becomes readable, and reading it leaves nothing behind.
+    let block_len = (FRAMES * CHANNELS as u32) as usize;
+    stream.write_from(&vec![1; block_len])?;
+    let readable = available_frames(stream.read_available()?);
+    assert_eq!(readable, (DELAY + FRAMES) as usize);
+    let mut read = vec![0; readable * CHANNELS as usize];
+    stream.read_into(&mut read)?;
+    assert_eq!(available_frames(stream.read_available()?), 0);
+
+    stream.stop()?;
+    stream.close()
+}
+
+/// The number of frames that a stream reports as available, which is never negative.
+fn available_frames(available: pa::StreamAvailable) -> usize {
+    match available {
+        pa::StreamAvailable::Frames(frames) => {
+            assert!(frames >= 0);
+            frames as usize
+        }
+        xrun => panic!("unexpected {:?}", xrun),
+    }
+}
+
+#[test]
+fn idempotent_close() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    let params = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::OutputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+
+    // Closing consumes the stream, whose drop then leaves the closed stream alone.
+    let stream = pa.open_blocking_stream(settings)?;
+    let calls = pa::mock::close_stream_calls();
+    stream.close()?;
+    assert_eq!(pa::mock::close_stream_calls(), calls + 1);
+
+    // A stream that was never closed explicitly is closed when it is dropped.
+    let stream = pa.open_blocking_stream(settings)?;
+    drop(stream);
+    assert_eq!(pa::mock::close_stream_calls(), calls + 2);
+    Ok(())
+}
+
+#[test]
+fn invalid_handles() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    let params = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::OutputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+
+    // PortAudio has no information about a stream that failed to re-open, e.g. because its
+    // device lost channels, which is reported rather than read.
+    let mut stream = pa.open_blocking_stream(settings)?;
+    assert!(stream.info().is_ok());
+    let config = pa::mock::config();
+    pa::mock::configure(pa::mock::MockConfig {
+        output_channels: 1,
+        ..config
+    });
+    assert_eq!(stream.reopen(), Err(pa::Error::InvalidChannelCount));
+    pa::mock::configure(config);
+    assert_eq!(stream.info(), Err(pa::Error::BadStreamPtr));
+    assert_eq!(stream.output_latency(), Duration::from_secs(0));
+    drop(stream);
+
+    // As are devices and host APIs past the end of PortAudio's lists.
+    let device_count = pa.device_count()?;
+    assert_eq!(
+        pa.device_info(pa::DeviceIndex(device_count)).err(),
+        Some(pa::Error::InvalidDevice)
+    );
+    let host_api_count = pa.host_api_count()?;
+    assert!(pa.host_api_info(host_api_count).is_none());
+    assert!(pa.host_api_info(-1).is_none());
+    Ok(())
+}
+
+#[test]
+fn host_api_specific_info() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    use std::os::raw::{c_uint, c_ulong};
+
+    // The header that every host API's `hostApiSpecificStreamInfo` struct begins with.
+    #[repr(C)]
+    #[derive(Copy, Clone)]
+    struct Header {
+        size: c_ulong,
+        host_api_type: c_uint,
+        version: c_ulong,
+    }
+
+    let open = |host_api: pa::HostApiTypeId| {
+        let info = Header {
+            size: std::mem::size_of::<Header>() as c_ulong,
+            host_api_type: host_api as c_uint,
+            version: 1,
+        };
+        let params = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.0);
+        let params = unsafe { params.with_host_api_specific_info(host_api, &info) };
+        let settings = pa::OutputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+        pa.open_blocking_stream(settings)
+            .map(|stream| stream.close())
+    };
+    // The virtual device's host API is handed the struct, which it doesn't accept.
+    assert_eq!(
+        open(pa::HostApiTypeId::InDevelopment).err(),
+        Some(pa::Error::IncompatibleHostApiSpecificStreamInfo)
+    );
+    // The struct is dropped for the devices of other host APIs.
+    open(pa::HostApiTypeId::ALSA)??;
+    Ok(())
+}
+
+#[test]
+fn default_streams() -> Result<(), pa::Error> {
+    let (_lock, pa, _) = setup();
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // The virtual device is stereo in both directions, so the default streams are too.
+    let frames_played = Arc::new(AtomicUsize::new(0));
+    let played = frames_played.clone();
+    let mut output = pa.open_default_output_stream(move |args| {
+        assert_eq!(args.buffer.len(), args.frames * CHANNELS as usize);
+        played.fetch_add(args.frames, Ordering::SeqCst);
+        pa::Continue
+    })?;
+    assert_eq!(output.info()?.sample_rate, SAMPLE_RATE);
+    output.start()?;
+    while frames_played.load(Ordering::SeqCst) == 0 {
+        pa.sleep(1);
+    }
+    output.stop()?;
+    output.close()?;
+
+    let mut input = pa.open_default_input_stream(|args| {
+        assert_eq!(args.buffer.len(), args.frames * CHANNELS as usize);
+        pa::Complete
+    })?;
+    assert_eq!(input.info()?.sample_rate, SAMPLE_RATE);
+    input.start()?;
+    while input.is_active()? {
+        pa.sleep(1);
+    }
+    input.stop()?;
+    input.close()?;
+
+    // `Pa_OpenDefaultStream` picks the device and latency itself, and with no input channels the
+    // input buffer is empty.
+    let frames_played = Arc::new(AtomicUsize::new(0));
+    let played = frames_played.clone();
+    let mut raw =
+        pa.open_default_stream_raw::<f32, _>(0, CHANNELS, SAMPLE_RATE, FRAMES, move |args| {
+            assert!(args.in_buffer.is_empty());
+            assert_eq!(args.out_buffer.len(), args.frames * CHANNELS as usize);
+            played.fetch_add(args.frames, Ordering::SeqCst);
+            pa::Continue
+        })?;
+    assert_eq!(raw.info()?.sample_rate, SAMPLE_RATE);
+    assert!(raw.output_latency() > Duration::from_secs(0));
+    raw.start()?;
+    while frames_played.load(Ordering::SeqCst) == 0 {
+        pa.sleep(1);
+    }
+    raw.stop()?;
+    // The stream is re-opened via `Pa_OpenStream` with the latency that PortAudio chose.
+    let latency = raw.output_latency();
+    raw.reopen()?;
+    assert_eq!(raw.output_latency(), latency);
+    raw.close()
+}
+
+#[test]
+fn oversized_buffers() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    // Settings such as these may come from an untrusted configuration file, and are rejected
+    // before anything is allocated for them.
+    let params = pa::StreamParameters::<f32>::new(device, i32::MAX, INTERLEAVED, 0.0);
+    let settings = pa::OutputStreamSettings::new(params, SAMPLE_RATE, u32::MAX);
+    assert_eq!(
+        pa.open_blocking_stream(settings).err(),
+        Some(pa::Error::BufferTooLarge)
+    );
+    let params = pa::StreamParameters::<f32>::new(device, -1, INTERLEAVED, 0.0);
+    let settings = pa::InputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+    assert_eq!(
+        pa.open_blocking_stream(settings).err(),
+        Some(pa::Error::InvalidChannelCount)
+    );
+    Ok(())
+}
+
+#[test]
+fn planar_round_trip() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    const PLANAR_CHANNELS: i32 = 3;
+    pa::mock::configure(pa::mock::MockConfig {
+        input_channels: PLANAR_CHANNELS,
+        output_channels: PLANAR_CHANNELS,
+        ..pa::mock::config()
+    });
+    let input = pa::StreamParameters::<i16>::new(device, PLANAR_CHANNELS, false, 0.0);
+    let output = pa::StreamParameters::<i16>::new(device, PLANAR_CHANNELS, false, 0.0);
+    let settings = pa::DuplexStreamSettings::new(input, output, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+
+    // Each channel counts up from its own offset, across more frames than fit in one buffer.
+    let frames = FRAMES as usize * BLOCKS + 5;
+    let written: Vec<Vec<i16>> = (0..PLANAR_CHANNELS as i16)
+        .map(|channel| (0..frames as i16).map(|i| channel * 1000 + i).collect())
+        .collect();
+    {
+        let planes: Vec<&[i16]> = written.iter().map(|plane| &plane[..]).collect();
+        assert_eq!(stream.write_planar(&planes)?, frames);
+    }
+    let mut read = vec![vec![0; frames]; PLANAR_CHANNELS as usize];
+    {
+        let mut planes: Vec<&mut [i16]> = read.iter_mut().map(|plane| &mut plane[..]).collect();
+        assert_eq!(stream.read_planar(&mut planes)?, frames);
+    }
+    for (read, written) in read.iter().zip(&written) {
+        assert!(read[..DELAY as usize].iter().all(|&s| s == 0));
+        assert_eq!(&read[DELAY as usize..], &written[..frames - DELAY as usize]);
+    }
+
+    // The buffers must match the stream's channels and be of the same length.
+    assert_eq!(
+        stream.write_planar(&[&written[0][..], &written[1][..]]),
+        Err(pa::Error::InvalidChannelCount)
+    );
+    assert_eq!(
+        stream.write_planar(&[&written[0][..], &written[1][..], &written[2][1..]]),
+        Err(pa::Error::InvalidBuffer {
+            expected: frames * 2,
+            actual: (frames - 1) * 2,
+        })
+    );
+    stream.stop()?;
+    stream.close()
+}
+
+#[test]
+fn callback_round_trip() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    // Without a delay, the recording begins with the first sample that was played.
+    pa::mock::configure(pa::mock::MockConfig {
+        loopback_delay: 0,
+        ..pa::mock::config()
+    });
+    let settings = pa.default_output_stream_settings::<f32>(CHANNELS, SAMPLE_RATE, FRAMES)?;
+    let mut count = 0;
+    let callback = move |pa::OutputStreamCallbackArgs { buffer, .. }| {
+        for sample in buffer.iter_mut() {
+            *sample = count as f32;
+            count += 1;
+        }
+        if count < FRAMES as usize * CHANNELS as usize * BLOCKS {
+            pa::Continue
+        } else {
+            pa::Complete
+        }
+    };
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+    assert_eq!(stream.state()?, pa::StreamState::Stopped);
+    stream.start()?;
+    while stream.state()? == pa::StreamState::Active {
+        pa.sleep(1);
+    }
+    // Completing from the callback finishes the stream without stopping it.
+    assert_eq!(stream.state()?, pa::StreamState::Finished);
+    stream.stop()?;
+    assert_eq!(stream.state()?, pa::StreamState::Stopped);
+    stream.close()?;
+
+    let params = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::InputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+    let mut read = Vec::new();
+    for _ in 0..BLOCKS {
+        read.extend_from_slice(stream.read(FRAMES)?);
+    }
+    let expected: Vec<f32> = (0..read.len()).map(|i| i as f32).collect();
+    assert_eq!(read, expected);
+
+    stream.stop()?;
+    stream.close()
+}
+
+#[test]
+fn capture_only() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    // Without a delay, the recording begins with the first sample that was played.
+    pa::mock::configure(pa::mock::MockConfig {
+        loopback_delay: 0,
+        ..pa::mock::config()
+    });
+    let block_len = (FRAMES * CHANNELS as u32) as usize;
+    let written: Vec<f32> = (0..block_len * BLOCKS).map(|i| i as f32).collect();
+    let params = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::OutputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+    stream.write_from(&written)?;
+    stream.stop()?;
+    stream.close()?;
+
+    // Input settings need no output parameters, and the callback is only given the recorded
+    // buffer, as a shared slice.
+    let params = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::InputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+    let (sender, receiver) = mpsc::channel();
+    let mut blocks = 0;
+    let callback = move |args: pa::InputStreamCallbackArgs<f32>| {
+        let buffer: &[f32] = args.buffer;
+        sender.send(buffer.to_vec()).unwrap();
+        blocks += 1;
+        if blocks < BLOCKS {
+            pa::Continue
+        } else {
+            pa::Complete
+        }
+    };
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+    stream.start()?;
+    while stream.is_active()? {
+        pa.sleep(1);
+    }
+    stream.stop()?;
+    stream.close()?;
+    let read: Vec<f32> = receiver.try_iter().flatten().collect();
+    assert_eq!(read, written);
+    Ok(())
+}
+
+#[test]
+fn recorder() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    // Without a delay, the recording begins with the first sample that was played.
+    pa::mock::configure(pa::mock::MockConfig {
+        loopback_delay: 0,
+        ..pa::mock::config()
+    });
+    let block_len = (FRAMES * CHANNELS as u32) as usize;
+    let written: Vec<f32> = (0..block_len * BLOCKS).map(|i| i as f32).collect();
+    let params = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::OutputStreamSettings::new(params, SAMPLE_RATE, FRAMES);
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+    stream.write_from(&written)?;
+    stream.stop()?;
+    stream.close()?;
+
+    // The recording begins with the looped back output, followed by silence once it has all
+    // been captured.
+    let mut recorder = pa::Recorder::<f32>::new(&pa, device, CHANNELS, SAMPLE_RATE)?;
+    assert_eq!(recorder.stop().err(), Some(pa::Error::StreamIsStopped));
+    recorder.start()?;
+    assert!(recorder.is_recording());
+    assert_eq!(recorder.start().err(), Some(pa::Error::StreamIsNotStopped));
+    pa.sleep(50);
+    let recorded = recorder.stop()?;
+    assert!(!recorder.is_recording());
+    assert_eq!(recorded.len() % CHANNELS as usize, 0);
+    assert!(recorded.len() > written.len());
+    assert_eq!(&recorded[..written.len()], &written[..]);
+    assert!(recorded[written.len()..].iter().all(|&s| s == 0.0));
+    assert_eq!(recorder.dropped_frames(), 0);
+
+    // Each take starts with an empty recording.
+    recorder.start()?;
+    pa.sleep(10);
+    let recorded = recorder.stop()?;
+    assert!(recorded.iter().all(|&s| s == 0.0));
+    Ok(())
+}
+
+#[test]
+fn unspecified_frames() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    // Without a delay, the recording begins with the first sample that was played.
+    pa::mock::configure(pa::mock::MockConfig {
+        loopback_delay: 0,
+        ..pa::mock::config()
+    });
+    // The callback is passed a varying number of frames, so it must go by `frames` each time.
+    let settings = pa.default_output_stream_settings::<f32>(
+        CHANNELS,
+        SAMPLE_RATE,
+        pa::FRAMES_PER_BUFFER_UNSPECIFIED,
+    )?;
+    let (sender, receiver) = mpsc::channel();
+    let mut count = 0;
+    let callback = move |pa::OutputStreamCallbackArgs { buffer, frames, .. }| {
+        assert_eq!(buffer.len(), frames * CHANNELS as usize);
+        sender.send(frames).unwrap();
+        count += 1;
+        if count < BLOCKS {
+            pa::Continue
+        } else {
+            pa::Complete
+        }
+    };
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+    stream.start()?;
+    while stream.is_active()? {
+        pa.sleep(1);
+    }
+    stream.stop()?;
+    stream.close()?;
+    let frames: Vec<usize> = receiver.try_iter().collect();
+    assert!(frames.windows(2).any(|pair| pair[0] != pair[1]));
+
+    // Blocking transfers aren't tied to a block size either.
+    let input = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let output = pa::StreamParameters::<i16>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::DuplexStreamSettings::new(
+        input,
+        output,
+        SAMPLE_RATE,
+        pa::FRAMES_PER_BUFFER_UNSPECIFIED,
+    );
+    let mut stream = pa.open_blocking_stream(settings)?;
+    stream.start()?;
+    let written: Vec<i16> = (0..300 * CHANNELS as i16).collect();
+    let (first, rest) = written.split_at(100 * CHANNELS as usize);
+    stream.write(100, |out| out.copy_from_slice(first))?;
+    stream.write_from(rest)?;
+    let mut read = vec![0; written.len()];
+    stream.read_into(&mut read)?;
+    assert_eq!(read, written);
+
+    stream.stop()?;
+    stream.close()
+}
+
+#[test]
+fn suggested_settings() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    // The virtual device reports default latencies of 10ms and 100ms, i.e. 480 and 4800 frames.
+    let low = pa.suggest_low_latency_settings::<f32>(device, CHANNELS, SAMPLE_RATE)?;
+    assert_eq!(low.frames_per_buffer, 512);
+    let high = pa.suggest_high_latency_settings::<f32>(device, CHANNELS, SAMPLE_RATE)?;
+    assert_eq!(high.frames_per_buffer, 8192);
+
+    let mut stream = pa.open_non_blocking_stream(low, |_| pa::Complete)?;
+    assert_eq!(stream.frames_to_duration(4_800), Duration::from_millis(100));
+    assert_eq!(stream.duration_to_frames(Duration::from_millis(10)), 480);
+    // The virtual device reports the suggested latency as the stream's latency.
+    assert_eq!(stream.output_latency_frames(), 480);
+    assert_eq!(stream.input_latency_frames(), 0);
+    assert_eq!(stream.total_roundtrip_latency_frames(), 480);
+    stream.start()?;
+    while stream.is_active()? {
+        pa.sleep(1);
+    }
+    stream.stop()?;
+    stream.close()?;
+
+    // A duplex stream's round trip spans the latencies of both directions.
+    let input = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.1);
+    let output = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.01);
+    let settings = pa::DuplexStreamSettings::new(input, output, SAMPLE_RATE, FRAMES);
+    let duplex = pa.open_blocking_stream(settings)?;
+    assert_eq!(duplex.input_latency_frames(), 4_800);
+    assert_eq!(duplex.output_latency_frames(), 480);
+    assert_eq!(duplex.total_roundtrip_latency_frames(), 5_280);
+    duplex.close()
+}
+
+#[test]
+fn scoped_start() -> Result<(), pa::Error> {
+    let (_lock, pa, _) = setup();
+    let settings = pa.default_output_stream_settings::<f32>(CHANNELS, SAMPLE_RATE, FRAMES)?;
+    let mut stream = pa.open_non_blocking_stream(settings, |_| pa::Continue)?;
+    {
+        let guard = stream.scoped_start()?;
+        assert_eq!(guard.state()?, pa::StreamState::Active);
+    }
+    assert_eq!(stream.state()?, pa::StreamState::Stopped);
+
+    // The guard can also be stopped explicitly to handle any error.
+    let guard = stream.scoped_start()?;
+    guard.stop()?;
+    assert_eq!(stream.state()?, pa::StreamState::Stopped);
+    stream.close()
+}
+
+#[test]
+fn common_sample_rate() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    // The virtual device only supports the configured rate, which isn't the preferred 48 kHz.
+    pa::mock::configure(pa::mock::MockConfig {
+        sample_rate: 96_000.0,
+        ..pa::mock::config()
+    });
+    assert_eq!(
+        pa.default_sample_rate_for(device, device, CHANNELS),
+        Ok(96_000.0)
+    );
+    assert_eq!(
+        pa.default_sample_rate_for(device, device, CHANNELS + 1),
+        Err(pa::Error::NoCommonSampleRate)
+    );
+    pa::mock::configure(pa::mock::MockConfig {
+        sample_rate: SAMPLE_RATE,
+        ..pa::mock::config()
+    });
+    assert_eq!(
+        pa.default_sample_rate_for(device, device, CHANNELS),
+        Ok(SAMPLE_RATE)
+    );
+    Ok(())
+}
+
+#[test]
+fn panicking_callback() -> Result<(), pa::Error> {
+    let (_lock, pa, _) = setup();
+    let settings = pa.default_output_stream_settings::<f32>(CHANNELS, SAMPLE_RATE, FRAMES)?;
+    let mut count = 0;
+    let callback = move |_: pa::OutputStreamCallbackArgs<f32>| {
+        count += 1;
+        if count == BLOCKS {
+            panic!("deliberate panic in block {}", count);
+        }
+        pa::Continue
+    };
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+    assert!(stream.take_callback_panic().is_none());
+
+    // Keep the expected panic's message out of the test's output.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    stream.start()?;
+    while stream.is_active()? {
+        pa.sleep(1);
+    }
+    panic::set_hook(hook);
+
+    // The panic aborted the stream rather than unwinding into PortAudio.
+    assert_eq!(stream.state()?, pa::StreamState::Finished);
+    let payload = stream
+        .take_callback_panic()
+        .expect("the callback should have panicked");
+    assert_eq!(
+        payload.downcast_ref::<String>().map(|s| &s[..]),
+        Some("deliberate panic in block 4")
+    );
+    assert!(stream.take_callback_panic().is_none());
+    stream.stop()?;
+    stream.close()
+}
+
+#[test]
+fn slow_callback() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    const SLOW_BLOCK: usize = 1;
+    let input = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let output = pa::StreamParameters::<f32>::new(device, CHANNELS, INTERLEAVED, 0.0);
+    let settings = pa::DuplexStreamSettings::new(input, output, SAMPLE_RATE, FRAMES);
+    let (sender, receiver) = mpsc::channel();
+    let mut blocks = 0;
+    let callback = move |args: pa::DuplexStreamCallbackArgs<f32, f32>| {
+        sender.send(args.flags).unwrap();
+        // Take several buffers' worth of time over one of them.
+        if blocks == SLOW_BLOCK {
+            let buffer = Duration::from_secs_f64(f64::from(FRAMES) / SAMPLE_RATE);
+            std::thread::sleep(buffer * 4);
+        }
+        blocks += 1;
+        if blocks < BLOCKS {
+            pa::Continue
+        } else {
+            pa::Complete
+        }
+    };
+    let mut stream = pa.open_non_blocking_stream(settings, callback)?;
+    stream.start()?;
+    while stream.is_active()? {
+        pa.sleep(1);
+    }
+    stream.stop()?;
+    stream.close()?;
+
+    let flags: Vec<pa::StreamCallbackFlags> = receiver.try_iter().collect();
+    assert_eq!(flags.len(), BLOCKS);
+    let late = flags[SLOW_BLOCK + 1];
+    assert!(late.input_overflow());
+    assert!(late.output_underflow());
+    assert!(!late.input_underflow() && !late.output_overflow());
+    Ok(())
+}
+
+#[test]
+fn roundtrip_latency() -> Result<(), pa::Error> {
+    let (_lock, pa, device) = setup();
+    let latency = pa.measure_roundtrip_latency(device, device, SAMPLE_RATE)?;
+    assert_eq!(latency, pa::frames_to_duration(DELAY as u64, SAMPLE_RATE));
+
+    // A chirp delayed beyond the measured second looks the same as no loopback at all.
+    pa::mock::configure(pa::mock::MockConfig {
+        loopback_delay: 2 * SAMPLE_RATE as u32,
+        ..pa::mock::config()
+    });
+    assert_eq!(
+        pa.measure_roundtrip_latency(device, device, SAMPLE_RATE),
+        Err(pa::Error::NoLoopbackDetected)
+    );
+    Ok(())
+}
@@ -0,0 +1,180 @@
+//! Play WAV files into the loopback of the `mock-backend` feature and record them back, checking
+//! that the recordings hold the same samples:
+//!
+//! ```text
+//! cargo test --features "mock-backend wav"
+//! ```
+
+#![cfg(all(feature = "mock-backend", feature = "wav"))]
+
+extern crate portaudio;
+
+use pa::wav::{WavError, WavReader, WavSpec, WavWriter};
+use portaudio as pa;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::MutexGuard;
+use std::thread;
+use std::time::Duration;
+
+const CHANNELS: u16 = 2;
+const SAMPLE_RATE: u32 = 48_000;
+const FRAMES: usize = 12_000;
+
+/// Take the virtual device for a test and configure it to loop back without a delay.
+///
+/// The guard is returned first so that it outlives the **PortAudio**.
+fn setup() -> (MutexGuard<'static, ()>, pa::PortAudio, pa::DeviceIndex) {
+    let lock = pa::mock::lock();
+    pa::mock::configure(pa::mock::MockConfig {
+        input_channels: CHANNELS as i32,
+        output_channels: CHANNELS as i32,
+        sample_rate: SAMPLE_RATE as f64,
+        loopback_delay: 0,
+    });
+    let pa = pa::PortAudio::new().unwrap();
+    let device = pa.default_output_device().unwrap();
+    (lock, pa, device)
+}
+
+/// A path within the temporary directory for the named file of a test.
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("portaudio_mock_wav_{}.wav", name))
+}
+
+fn duration() -> Duration {
+    Duration::from_millis((FRAMES as u64 * 1000) / SAMPLE_RATE as u64)
+}
+
+fn float_samples() -> Vec<f32> {
+    (0..FRAMES * CHANNELS as usize)
+        .map(|i| (i as f32 * 0.01).sin() * 0.5)
+        .collect()
+}
+
+#[test]
+fn blocking_i16_round_trip() -> Result<(), WavError> {
+    let (_lock, pa, device) = setup();
+    let played = temp_path("i16_played");
+    let recorded = temp_path("i16_recorded");
+
+    // 16-bit samples are played from the calling thread.
+    let samples: Vec<i16> = (0..FRAMES * CHANNELS as usize)
+        .map(|i| (i % 2000) as i16 - 1000)
+        .collect();
+    write_wav(&played, &samples)?;
+    pa.play_wav_blocking(&played, device)?;
+    let recording = pa.record_to_wav::<i16, _>(device, duration(), &recorded)?;
+    assert_eq!(recording.frames, FRAMES as u64);
+    assert_eq!(read_wav::<i16>(&recorded)?, samples);
+    Ok(())
+}
+
+#[test]
+fn callback_f32_round_trip() -> Result<(), WavError> {
+    let (_lock, pa, device) = setup();
+    let played = temp_path("f32_played");
+    let recorded = temp_path("f32_recorded");
+
+    // Float samples are played from the stream callback, which pads its last buffer with silence
+    // that the recording stops short of.
+    let samples = float_samples();
+    write_wav(&played, &samples)?;
+    pa.play_wav(&played, device)?;
+    pa.record_to_wav::<f32, _>(device, duration(), &recorded)?;
+    assert_eq!(read_wav::<f32>(&recorded)?, samples);
+    Ok(())
+}
+
+#[test]
+fn unsupported_sample_rate() -> Result<(), WavError> {
+    let (_lock, pa, device) = setup();
+    let played = temp_path("unsupported_rate");
+
+    // Files at a rate the device lacks are rejected rather than played at the wrong speed.
+    let spec = WavSpec {
+        channels: CHANNELS,
+        sample_rate: 44_100,
+        sample_format: pa::SampleFormat::I16,
+    };
+    WavWriter::create(&played, spec)?.finalize()?;
+    match pa.play_wav(&played, device) {
+        Err(WavError::Format(err)) => {
+            assert_eq!(err.error, pa::Error::InvalidSampleRate);
+            assert_eq!(err.default_sample_rate, Some(SAMPLE_RATE as f64));
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+    Ok(())
+}
+
+#[test]
+fn playback_position() -> Result<(), WavError> {
+    let (_lock, pa, device) = setup();
+    let played = temp_path("position");
+
+    // Background playback reports a position that only advances, up to the end of the file.
+    write_wav(&played, &float_samples())?;
+    let playback = pa.start_wav_playback(&played, device)?;
+    assert_eq!(playback.frames(), FRAMES as u64);
+    let mut positions = vec![playback.position()];
+    while playback.is_playing()? {
+        positions.push(playback.position());
+        thread::sleep(Duration::from_millis(1));
+    }
+    positions.push(playback.position());
+    assert!(positions.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(positions.iter().any(|&p| p > 0 && p < FRAMES as u64));
+    assert_eq!(positions.last(), Some(&(FRAMES as u64)));
+    playback.wait()?;
+    Ok(())
+}
+
+#[test]
+fn playback_seek() -> Result<(), WavError> {
+    let (_lock, pa, device) = setup();
+    let played = temp_path("seek");
+
+    // Seeking back replays the file from the start, and seeking past the end finishes it.
+    write_wav(&played, &float_samples())?;
+    let playback = pa.start_wav_playback(&played, device)?;
+    while playback.position() < FRAMES as u64 / 2 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    playback.seek(0);
+    while playback.position() >= FRAMES as u64 / 2 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    playback.seek(FRAMES as u64 * 2);
+    while playback.is_playing()? {
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(playback.position(), FRAMES as u64);
+    playback.wait()?;
+
+    // A playback may also be stopped before it reaches the end.
+    let playback = pa.start_wav_playback(&played, device)?;
+    playback.stop()?;
+    Ok(())
+}
+
+fn write_wav<S: pa::Sample>(path: &Path, samples: &[S]) -> Result<(), WavError> {
+    let spec = WavSpec {
+        channels: CHANNELS,
+        sample_rate: SAMPLE_RATE,
+        sample_format: S::sample_format(),
+    };
+    let mut writer = WavWriter::create(path, spec)?;
+    writer.write_samples(samples)?;
+    writer.finalize()?;
+    Ok(())
+}
+
+fn read_wav<S: pa::Sample>(path: &Path) -> Result<Vec<S>, WavError> {
+    let mut reader = WavReader::open(path)?;
+    assert_eq!(reader.spec().sample_format, S::sample_format());
+    let mut samples = vec![<S as pa::Sample>::from_f32_sample(0.0); FRAMES * CHANNELS as usize];
+    let read = reader.read_samples(&mut samples)?;
+    assert_eq!(read, samples.len());
+    Ok(samples)
+}